@@ -2,16 +2,37 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{TokenAccount, Mint, Token};
 use anchor_spl::metadata::Metadata;
 use crate::state::admin::Admin;
+use crate::state::attestation::{AttestationNonce, EligibilityAttestation};
+use crate::state::eligibility::AttributeDisclosure;
+use anchor_lang::solana_program::sysvar::instructions as instructions_sysvar;
+use anchor_lang::solana_program::sysvar::slot_hashes;
 use crate::state::researcher::Researcher;
 use crate::state::participant::Participant;
 use crate::state::study::Study;
-use crate::state::consent::Consent;
+use crate::state::consent::{Consent, StudyConsent};
+use crate::state::enrollment::{Enrollment, EnrollmentStatus};
+use crate::state::feedback::Feedback;
+use crate::state::waitlist::Waitlist;
+use crate::state::ethics::EthicsApproval;
+use crate::state::allocation::{Allocation, UnblindingRecord};
+use crate::state::dispute::Dispute;
+use crate::state::privacy::ErasureTombstone;
+use crate::state::identity::IdentityRecord;
+use crate::state::guardianship::Guardianship;
+use crate::state::wallet::ParticipantWallet;
+use crate::state::team::{StudyTeam, TeamPermission, TeamRole};
 use crate::error::RecruSearchError;
 // For metadata, use UncheckedAccount as a placeholder for Metaplex metadata accounts
 
 #[derive(Accounts)]
 pub struct Initialize<'info> {
-    #[account(init, payer = authority, space = 8 + 32)]
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Admin::INIT_SPACE,
+        seeds = [b"admin"],
+        bump
+    )]
     pub admin: Account<'info, Admin>,
     #[account(mut)]
     pub authority: Signer<'info>,
@@ -20,7 +41,13 @@ pub struct Initialize<'info> {
 
 #[derive(Accounts)]
 pub struct RegisterResearcher<'info> {
-    #[account(init, payer = authority, space = 8 + 32 + 100 + 100 + 1)]
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Researcher::INIT_SPACE,
+        seeds = [b"researcher", authority.key().as_ref()],
+        bump
+    )]
     pub researcher: Account<'info, Researcher>,
     #[account(mut)]
     pub authority: Signer<'info>,
@@ -29,29 +56,126 @@ pub struct RegisterResearcher<'info> {
 
 #[derive(Accounts)]
 pub struct CreateStudy<'info> {
-    #[account(init, payer = researcher, space = 8 + 32 + 100 + 500 + 100 + 8 + 4 + 4 + 1 + 8)]
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Study::INIT_SPACE,
+        seeds = [b"study", researcher.key().as_ref(), &researcher.studies_created.to_le_bytes()],
+        bump
+    )]
     pub study: Account<'info, Study>,
-    #[account(mut)]
+    #[account(mut, has_one = authority @ RecruSearchError::UnauthorizedResearcher)]
     pub researcher: Account<'info, Researcher>,
+    // Program-owned escrow holding reward_amount * max_participants
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"vault", study.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::authority = vault,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    pub reward_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = authority,
+    )]
+    pub researcher_token_account: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + StudyTeam::INIT_SPACE,
+        seeds = [b"study_team", study.key().as_ref()],
+        bump
+    )]
+    pub team: Account<'info, StudyTeam>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
 pub struct RegisterParticipant<'info> {
-    #[account(init, payer = authority, space = 8 + 32 + 100 + 8 + 4)]
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Participant::INIT_SPACE,
+        seeds = [b"participant", authority.key().as_ref()],
+        bump
+    )]
     pub participant: Account<'info, Participant>,
+    /// CHECK: Tombstone of an earlier, erased profile, if any; read for its reputation
+    #[account(seeds = [b"tombstone", participant.key().as_ref()], bump)]
+    pub tombstone: UncheckedAccount<'info>,
     #[account(mut)]
     pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
+#[instruction(disclosures: Vec<AttributeDisclosure>, attestation: Option<EligibilityAttestation>)]
 pub struct JoinStudy<'info> {
     #[account(mut)]
     pub study: Account<'info, Study>,
-    #[account(mut)]
+    #[account(seeds = [b"admin"], bump = admin.bump)]
+    pub admin: Account<'info, Admin>,
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = !participant.banned && !participant.suspended @ RecruSearchError::InvalidParticipantStatus
+    )]
     pub participant: Account<'info, Participant>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Enrollment::INIT_SPACE,
+        seeds = [b"enrollment", study.key().as_ref(), participant.key().as_ref()],
+        bump
+    )]
+    pub enrollment: Account<'info, Enrollment>,
+    #[account(seeds = [b"ethics_approval", study.key().as_ref()], bump = ethics_approval.bump)]
+    pub ethics_approval: Account<'info, EthicsApproval>,
+    // Required unless the study is exempt from written consent
+    #[account(
+        seeds = [b"study_consent", study.key().as_ref(), participant.key().as_ref()],
+        bump = study_consent.bump,
+    )]
+    pub study_consent: Option<Account<'info, StudyConsent>>,
+    // Required for studies that need guardian consent
+    #[account(seeds = [b"guardianship", participant.key().as_ref()], bump = guardianship.bump)]
+    pub guardianship: Option<Account<'info, Guardianship>>,
+    // Read to check the escrow still funds another slot
+    #[account(seeds = [b"vault", study.key().as_ref()], bump = study.vault_bump)]
+    pub vault: Account<'info, TokenAccount>,
+    // Required once the study has anyone queued or holding an offer
+    #[account(mut, seeds = [b"waitlist", study.key().as_ref()], bump = waitlist.bump)]
+    pub waitlist: Option<Account<'info, Waitlist>>,
+    // Required for randomized studies
+    #[account(mut, seeds = [b"allocation", study.key().as_ref()], bump = allocation.bump)]
+    pub allocation: Option<Account<'info, Allocation>>,
+    // Only supplied with an attestation; creating it twice is what rejects a reused nonce
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AttestationNonce::INIT_SPACE,
+        seeds = [
+            b"attestation_nonce",
+            attestation.as_ref().map(|a| a.verifier).unwrap_or_default().as_ref(),
+            &attestation.as_ref().map(|a| a.nonce).unwrap_or_default().to_le_bytes(),
+        ],
+        bump
+    )]
+    pub attestation_nonce: Option<Account<'info, AttestationNonce>>,
+    /// CHECK: Instructions sysvar, read to find the verifier's Ed25519 signature
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    #[account(mut)]
     pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
@@ -60,20 +184,421 @@ pub struct CompleteStudy<'info> {
     pub study: Account<'info, Study>,
     #[account(mut)]
     pub participant: Account<'info, Participant>,
+    #[account(
+        mut,
+        seeds = [b"enrollment", study.key().as_ref(), participant.key().as_ref()],
+        bump = enrollment.bump,
+    )]
+    pub enrollment: Account<'info, Enrollment>,
+    #[account(has_one = authority @ RecruSearchError::UnauthorizedResearcher)]
+    pub researcher: Account<'info, Researcher>,
+    #[account(
+        seeds = [b"study_team", study.key().as_ref()],
+        bump = team.bump,
+        constraint = team.allows(&researcher.key(), TeamPermission::RecordProgress) @ RecruSearchError::UnauthorizedResearcher
+    )]
+    pub team: Account<'info, StudyTeam>,
+    /// Lead researcher, credited with the completion whoever on the team confirms it
+    #[account(mut, address = study.authority)]
+    pub lead_researcher: Account<'info, Researcher>,
+    #[account(
+        mut,
+        seeds = [b"vault", study.key().as_ref()],
+        bump = study.vault_bump,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = study.reward_mint,
+        token::authority = participant.authority,
+    )]
+    pub participant_token_account: Account<'info, TokenAccount>,
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimCompletion<'info> {
     #[account(mut)]
+    pub study: Account<'info, Study>,
+    #[account(has_one = authority)]
+    pub participant: Account<'info, Participant>,
+    #[account(
+        mut,
+        seeds = [b"enrollment", study.key().as_ref(), participant.key().as_ref()],
+        bump = enrollment.bump,
+    )]
+    pub enrollment: Account<'info, Enrollment>,
+    pub authority: Signer<'info>,
+}
+
+// Permissionless once a claim has gone unanswered for the study's confirmation window
+#[derive(Accounts)]
+pub struct FinalizeCompletion<'info> {
+    #[account(mut)]
+    pub study: Account<'info, Study>,
+    /// Lead researcher, credited with the completion
+    #[account(mut, address = study.authority)]
     pub researcher: Account<'info, Researcher>,
     #[account(mut)]
-    pub researcher_token_account: Account<'info, TokenAccount>,
+    pub participant: Account<'info, Participant>,
+    #[account(
+        mut,
+        seeds = [b"enrollment", study.key().as_ref(), participant.key().as_ref()],
+        bump = enrollment.bump,
+    )]
+    pub enrollment: Account<'info, Enrollment>,
+    #[account(
+        mut,
+        seeds = [b"vault", study.key().as_ref()],
+        bump = study.vault_bump,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = study.reward_mint,
+        token::authority = participant.authority,
+    )]
+    pub participant_token_account: Account<'info, TokenAccount>,
+    pub cranker: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RejectCompletion<'info> {
+    pub study: Account<'info, Study>,
+    #[account(has_one = authority @ RecruSearchError::UnauthorizedResearcher)]
+    pub researcher: Account<'info, Researcher>,
+    // Rejecting puts the participant into a dispute, so research assistants cannot
+    #[account(
+        seeds = [b"study_team", study.key().as_ref()],
+        bump = team.bump,
+        constraint = team.allows(&researcher.key(), TeamPermission::ManageParticipants) @ RecruSearchError::UnauthorizedResearcher
+    )]
+    pub team: Account<'info, StudyTeam>,
+    #[account(mut)]
+    pub participant: Account<'info, Participant>,
+    #[account(
+        mut,
+        seeds = [b"enrollment", study.key().as_ref(), participant.key().as_ref()],
+        bump = enrollment.bump,
+    )]
+    pub enrollment: Account<'info, Enrollment>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Dispute::INIT_SPACE,
+        seeds = [b"dispute", enrollment.key().as_ref()],
+        bump
+    )]
+    pub dispute: Account<'info, Dispute>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenDispute<'info> {
+    #[account(mut)]
+    pub study: Account<'info, Study>,
+    #[account(mut, has_one = authority)]
+    pub participant: Account<'info, Participant>,
+    #[account(
+        seeds = [b"enrollment", study.key().as_ref(), participant.key().as_ref()],
+        bump = enrollment.bump,
+    )]
+    pub enrollment: Account<'info, Enrollment>,
+    #[account(
+        seeds = [b"vault", study.key().as_ref()],
+        bump = study.vault_bump,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Dispute::INIT_SPACE,
+        seeds = [b"dispute", enrollment.key().as_ref()],
+        bump
+    )]
+    pub dispute: Account<'info, Dispute>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Either party: the participant, or a team member signing through their researcher account
+#[derive(Accounts)]
+pub struct SubmitDisputeEvidence<'info> {
+    pub study: Account<'info, Study>,
+    #[account(
+        seeds = [b"study_team", study.key().as_ref()],
+        bump = team.bump,
+    )]
+    pub team: Account<'info, StudyTeam>,
+    #[account(has_one = authority @ RecruSearchError::UnauthorizedResearcher)]
+    pub researcher: Option<Account<'info, Researcher>>,
+    pub participant: Account<'info, Participant>,
+    #[account(
+        mut,
+        has_one = study,
+        has_one = participant,
+        seeds = [b"dispute", dispute.enrollment.as_ref()],
+        bump = dispute.bump,
+    )]
+    pub dispute: Account<'info, Dispute>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump = admin.bump,
+        constraint = admin.is_arbitrator(&arbitrator.key()) @ RecruSearchError::UnauthorizedArbitrator
+    )]
+    pub admin: Account<'info, Admin>,
+    #[account(mut)]
+    pub study: Account<'info, Study>,
+    /// Lead researcher, whose reputation the ruling affects
+    #[account(mut, address = study.authority)]
+    pub researcher: Account<'info, Researcher>,
     #[account(mut)]
+    pub participant: Account<'info, Participant>,
+    #[account(
+        mut,
+        seeds = [b"enrollment", study.key().as_ref(), participant.key().as_ref()],
+        bump = enrollment.bump,
+    )]
+    pub enrollment: Account<'info, Enrollment>,
+    #[account(
+        mut,
+        has_one = study,
+        has_one = enrollment,
+        seeds = [b"dispute", enrollment.key().as_ref()],
+        bump = dispute.bump,
+    )]
+    pub dispute: Account<'info, Dispute>,
+    #[account(
+        mut,
+        seeds = [b"vault", study.key().as_ref()],
+        bump = study.vault_bump,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = study.reward_mint,
+        token::authority = participant.authority,
+    )]
+    pub participant_token_account: Account<'info, TokenAccount>,
+    // Overturning a rejected claim frees its slot: required once the study has anyone
+    // queued or holding an offer
+    #[account(mut, seeds = [b"waitlist", study.key().as_ref()], bump = waitlist.bump)]
+    pub waitlist: Option<Account<'info, Waitlist>>,
+    /// Consent to this study, revoked if the claim is overturned
+    #[account(
+        mut,
+        seeds = [b"study_consent", study.key().as_ref(), participant.key().as_ref()],
+        bump = study_consent.bump,
+    )]
+    pub study_consent: Option<Account<'info, StudyConsent>>,
+    pub arbitrator: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFromStudy<'info> {
+    #[account(mut)]
+    pub study: Account<'info, Study>,
+    #[account(mut, has_one = authority)]
+    pub participant: Account<'info, Participant>,
+    #[account(
+        mut,
+        seeds = [b"enrollment", study.key().as_ref(), participant.key().as_ref()],
+        bump = enrollment.bump,
+    )]
+    pub enrollment: Account<'info, Enrollment>,
+    #[account(
+        mut,
+        seeds = [b"vault", study.key().as_ref()],
+        bump = study.vault_bump,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = study.reward_mint,
+        token::authority = participant.authority,
+    )]
+    pub participant_token_account: Account<'info, TokenAccount>,
+    // Required once the study has anyone queued or holding an offer
+    #[account(mut, seeds = [b"waitlist", study.key().as_ref()], bump = waitlist.bump)]
+    pub waitlist: Option<Account<'info, Waitlist>>,
+    /// Consent to this study, revoked along with the enrollment
+    #[account(
+        mut,
+        seeds = [b"study_consent", study.key().as_ref(), participant.key().as_ref()],
+        bump = study_consent.bump,
+    )]
+    pub study_consent: Option<Account<'info, StudyConsent>>,
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveParticipantFromStudy<'info> {
+    #[account(mut)]
+    pub study: Account<'info, Study>,
+    #[account(has_one = authority @ RecruSearchError::UnauthorizedResearcher)]
+    pub researcher: Account<'info, Researcher>,
+    #[account(
+        seeds = [b"study_team", study.key().as_ref()],
+        bump = team.bump,
+        constraint = team.allows(&researcher.key(), TeamPermission::ManageParticipants) @ RecruSearchError::UnauthorizedResearcher
+    )]
+    pub team: Account<'info, StudyTeam>,
+    #[account(mut)]
+    pub participant: Account<'info, Participant>,
+    #[account(
+        mut,
+        seeds = [b"enrollment", study.key().as_ref(), participant.key().as_ref()],
+        bump = enrollment.bump,
+    )]
+    pub enrollment: Account<'info, Enrollment>,
+    #[account(
+        mut,
+        seeds = [b"vault", study.key().as_ref()],
+        bump = study.vault_bump,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = study.reward_mint,
+        token::authority = participant.authority,
+    )]
     pub participant_token_account: Account<'info, TokenAccount>,
+    // Required once the study has anyone queued or holding an offer
+    #[account(mut, seeds = [b"waitlist", study.key().as_ref()], bump = waitlist.bump)]
+    pub waitlist: Option<Account<'info, Waitlist>>,
+    /// Consent to this study, revoked along with the enrollment
+    #[account(
+        mut,
+        seeds = [b"study_consent", study.key().as_ref(), participant.key().as_ref()],
+        bump = study_consent.bump,
+    )]
+    pub study_consent: Option<Account<'info, StudyConsent>>,
+    pub authority: Signer<'info>,
     pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct JoinWaitlist<'info> {
+    #[account(mut)]
+    pub study: Account<'info, Study>,
+    #[account(
+        has_one = authority,
+        constraint = !participant.banned && !participant.suspended @ RecruSearchError::InvalidParticipantStatus
+    )]
+    pub participant: Account<'info, Participant>,
+    /// CHECK: Only checked to be uninitialized, so enrolled participants cannot queue
+    #[account(
+        seeds = [b"enrollment", study.key().as_ref(), participant.key().as_ref()],
+        bump,
+        constraint = enrollment.data_is_empty() @ RecruSearchError::AlreadyEnrolled
+    )]
+    pub enrollment: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Waitlist::INIT_SPACE,
+        seeds = [b"waitlist", study.key().as_ref()],
+        bump
+    )]
+    pub waitlist: Account<'info, Waitlist>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
+// Overdue (enrollment, participant) pairs are passed as writable remaining accounts
+#[derive(Accounts)]
+pub struct CrankExpire<'info> {
+    #[account(mut)]
+    pub study: Account<'info, Study>,
+    // Required once the study has anyone queued or holding an offer
+    #[account(mut, seeds = [b"waitlist", study.key().as_ref()], bump = waitlist.bump)]
+    pub waitlist: Option<Account<'info, Waitlist>>,
+    pub cranker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureStudy<'info> {
+    #[account(mut)]
+    pub study: Account<'info, Study>,
+    #[account(has_one = authority @ RecruSearchError::UnauthorizedResearcher)]
+    pub researcher: Account<'info, Researcher>,
+    #[account(
+        seeds = [b"study_team", study.key().as_ref()],
+        bump = team.bump,
+        constraint = team.allows(&researcher.key(), TeamPermission::ManageStudy) @ RecruSearchError::UnauthorizedResearcher
+    )]
+    pub team: Account<'info, StudyTeam>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TransitionStudy<'info> {
+    #[account(mut)]
+    pub study: Account<'info, Study>,
+    #[account(has_one = authority @ RecruSearchError::UnauthorizedResearcher)]
+    pub researcher: Account<'info, Researcher>,
+    #[account(
+        seeds = [b"study_team", study.key().as_ref()],
+        bump = team.bump,
+        constraint = team.allows(&researcher.key(), TeamPermission::ManageStudy) @ RecruSearchError::UnauthorizedResearcher
+    )]
+    pub team: Account<'info, StudyTeam>,
+    // Read to check the approving board is still registered
+    #[account(seeds = [b"admin"], bump = admin.bump)]
+    pub admin: Account<'info, Admin>,
+    // Required when opening recruitment
+    #[account(seeds = [b"ethics_approval", study.key().as_ref()], bump = ethics_approval.bump)]
+    pub ethics_approval: Option<Account<'info, EthicsApproval>>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RefundEscrow<'info> {
+    #[account(mut)]
+    pub study: Account<'info, Study>,
+    #[account(has_one = authority @ RecruSearchError::UnauthorizedResearcher)]
+    pub researcher: Account<'info, Researcher>,
+    #[account(
+        seeds = [b"study_team", study.key().as_ref()],
+        bump = team.bump,
+        constraint = team.allows(&researcher.key(), TeamPermission::ManageStudy) @ RecruSearchError::UnauthorizedResearcher
+    )]
+    pub team: Account<'info, StudyTeam>,
+    #[account(
+        mut,
+        seeds = [b"vault", study.key().as_ref()],
+        bump = study.vault_bump,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = study.reward_mint,
+        token::authority = authority,
+    )]
+    pub researcher_token_account: Account<'info, TokenAccount>,
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
 #[derive(Accounts)]
 pub struct VerifyResearcher<'info> {
     #[account(mut)]
     pub researcher: Account<'info, Researcher>,
+    #[account(seeds = [b"admin"], bump = admin.bump)]
     pub admin: Account<'info, Admin>,
     #[account(mut)]
     pub authority: Signer<'info>,
@@ -85,13 +610,14 @@ pub struct InitializeConsentNFT<'info> {
     pub authority: Signer<'info>,
     
     // Admin account is required to verify admin authority
+    #[account(seeds = [b"admin"], bump = admin.bump)]
     pub admin: Account<'info, Admin>,
     
     // Account for the consent NFT (this will be initialized)
     #[account(
         init_if_needed,
         payer = authority,
-        space = 8 + 32 + 32 + 40 + 40 + 8 + 9 + 1 + 32 + 1 + 8 + 8 + 200, // Appropriate space for Consent
+        space = 8 + Consent::INIT_SPACE,
         seeds = [b"consent_nft", admin.key().as_ref()],
         bump
     )]
@@ -135,7 +661,13 @@ pub struct CreateTokenAccount<'info> {
 
 #[derive(Accounts)]
 pub struct InitializeAdmin<'info> {
-    #[account(init, payer = payer, space = 8 + 32 + 8 + 8 + 8)]
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Admin::INIT_SPACE,
+        seeds = [b"admin"],
+        bump
+    )]
     pub admin: Account<'info, Admin>,
     #[account(mut)]
     pub payer: Signer<'info>,
@@ -144,7 +676,7 @@ pub struct InitializeAdmin<'info> {
 
 #[derive(Accounts)]
 pub struct ReviewResearcherVerification<'info> {
-    #[account(mut)]
+    #[account(mut, seeds = [b"admin"], bump = admin.bump)]
     pub admin: Account<'info, Admin>,
     #[account(mut)]
     pub researcher: Account<'info, Researcher>,
@@ -154,36 +686,101 @@ pub struct ReviewResearcherVerification<'info> {
 
 #[derive(Accounts)]
 pub struct UpdateStudyStatus<'info> {
-    #[account(mut)]
+    #[account(mut, seeds = [b"admin"], bump = admin.bump)]
     pub admin: Account<'info, Admin>,
     #[account(mut)]
     pub study: Account<'info, Study>,
+    // Required when opening recruitment
+    #[account(seeds = [b"ethics_approval", study.key().as_ref()], bump = ethics_approval.bump)]
+    pub ethics_approval: Option<Account<'info, EthicsApproval>>,
     #[account(mut)]
     pub authority: Signer<'info>,
 }
 
 #[derive(Accounts)]
 pub struct ManageParticipantStatus<'info> {
-    #[account(mut)]
+    #[account(mut, seeds = [b"admin"], bump = admin.bump)]
     pub admin: Account<'info, Admin>,
     #[account(mut)]
     pub participant: Account<'info, Participant>,
-    #[account(mut)]
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TrackStudyProgress<'info> {
+    pub study: Account<'info, Study>,
+    pub researcher: Account<'info, Researcher>,
+    #[account(seeds = [b"study_team", study.key().as_ref()], bump = team.bump)]
+    pub team: Account<'info, StudyTeam>,
+    pub participant: Account<'info, Participant>,
+    #[account(
+        mut,
+        seeds = [b"enrollment", study.key().as_ref(), participant.key().as_ref()],
+        bump = enrollment.bump,
+    )]
+    pub enrollment: Account<'info, Enrollment>,
+    // Either the enrolled participant or a team member allowed to record progress
+    #[account(
+        constraint = authority.key() == participant.authority
+            || (authority.key() == researcher.authority
+                && team.allows(&researcher.key(), TeamPermission::RecordProgress)) @ RecruSearchError::Unauthorized
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitResponse<'info> {
+    #[account(mut)]
+    pub study: Account<'info, Study>,
+    #[account(has_one = authority)]
+    pub participant: Account<'info, Participant>,
+    #[account(
+        mut,
+        seeds = [b"enrollment", study.key().as_ref(), participant.key().as_ref()],
+        bump = enrollment.bump,
+    )]
+    pub enrollment: Account<'info, Enrollment>,
     pub authority: Signer<'info>,
 }
 
+// Read-only and permissionless: anyone can check an entry against the anchored root
 #[derive(Accounts)]
-pub struct TrackStudyProgress<'info> {
-    #[account(mut)]
+pub struct VerifyDatasetEntry<'info> {
     pub study: Account<'info, Study>,
-    // Add more accounts if needed
+    #[account(
+        seeds = [b"enrollment", study.key().as_ref(), enrollment.participant.as_ref()],
+        bump = enrollment.bump,
+    )]
+    pub enrollment: Account<'info, Enrollment>,
 }
 
 #[derive(Accounts)]
 pub struct SubmitStudyFeedback<'info> {
     #[account(mut)]
     pub study: Account<'info, Study>,
-    // Add more accounts if needed
+    /// Lead researcher, who receives the rating
+    #[account(mut, address = study.authority)]
+    pub researcher: Account<'info, Researcher>,
+    #[account(has_one = authority)]
+    pub participant: Account<'info, Participant>,
+    #[account(
+        seeds = [b"enrollment", study.key().as_ref(), participant.key().as_ref()],
+        bump = enrollment.bump,
+        constraint = enrollment.status == EnrollmentStatus::Completed @ RecruSearchError::StudyCompletionCriteriaNotMet
+    )]
+    pub enrollment: Account<'info, Enrollment>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Feedback::INIT_SPACE,
+        seeds = [b"feedback", study.key().as_ref(), participant.key().as_ref()],
+        bump
+    )]
+    pub feedback: Account<'info, Feedback>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
@@ -199,6 +796,17 @@ pub struct UpdateParticipantProfile<'info> {
     pub participant: Account<'info, Participant>,
 }
 
+#[derive(Accounts)]
+pub struct CommitAttributes<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = !participant.banned @ RecruSearchError::Unauthorized
+    )]
+    pub participant: Account<'info, Participant>,
+}
+
 #[derive(Accounts)]
 pub struct UpdateInterests<'info> {
     #[account(mut)]
@@ -220,10 +828,10 @@ pub struct IssueConsentNFT<'info> {
     #[account(mut)]
     pub study: Account<'info, Study>,
     
-    #[account(mut)]
+    #[account(mut, has_one = authority)]
     pub participant: Account<'info, Participant>,
     
-    #[account(mut)]
+    #[account(mut, seeds = [b"consent_nft", consent.authority.as_ref()], bump = consent.bump)]
     pub consent: Account<'info, Consent>,
     
     #[account(mut)]
@@ -250,6 +858,19 @@ pub struct IssueConsentNFT<'info> {
     
     /// CHECK: Metadata program ID
     pub token_metadata_program: UncheckedAccount<'info>,
+
+    // Re-used when a participant consents again after revoking
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + StudyConsent::INIT_SPACE,
+        seeds = [b"study_consent", study.key().as_ref(), participant.key().as_ref()],
+        bump
+    )]
+    pub study_consent: Account<'info, StudyConsent>,
+
+    // Required when the study needs guardian consent
+    pub guardian: Option<Signer<'info>>,
 }
 
 #[derive(Accounts)]
@@ -257,11 +878,46 @@ pub struct RevokeConsent<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
     
-    #[account(mut)]
+    #[account(mut, seeds = [b"consent_nft", consent.authority.as_ref()], bump = consent.bump)]
     pub consent: Account<'info, Consent>,
     
-    #[account(mut)]
+    #[account(mut, has_one = authority)]
     pub participant: Account<'info, Participant>,
+
+    #[account(
+        mut,
+        seeds = [b"study_consent", study_consent.study.as_ref(), participant.key().as_ref()],
+        bump = study_consent.bump,
+    )]
+    pub study_consent: Account<'info, StudyConsent>,
+
+    #[account(mut, address = study_consent.study)]
+    pub study: Account<'info, Study>,
+
+    /// CHECK: The participant's enrollment in the study, if any; ended when still active
+    #[account(
+        mut,
+        seeds = [b"enrollment", study.key().as_ref(), participant.key().as_ref()],
+        bump,
+    )]
+    pub enrollment: UncheckedAccount<'info>,
+
+    // The exit accounts are only needed while the participant is still enrolled
+    #[account(mut, seeds = [b"vault", study.key().as_ref()], bump = study.vault_bump)]
+    pub vault: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = study.reward_mint,
+        token::authority = participant.authority,
+    )]
+    pub reward_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut, seeds = [b"waitlist", study.key().as_ref()], bump = waitlist.bump)]
+    pub waitlist: Option<Account<'info, Waitlist>>,
+
+    /// Required when the participant has a guardian
+    pub guardian: Option<Signer<'info>>,
     
     #[account(mut)]
     pub mint: Account<'info, Mint>,
@@ -272,6 +928,167 @@ pub struct RevokeConsent<'info> {
     pub token_program: Program<'info, Token>,
 }
 
+#[derive(Accounts)]
+pub struct EraseParticipant<'info> {
+    #[account(mut, has_one = authority, close = authority)]
+    pub participant: Account<'info, Participant>,
+    #[account(
+        mut,
+        seeds = [b"wallet", authority.key().as_ref()],
+        bump = wallet.bump,
+        close = authority,
+    )]
+    pub wallet: Option<Account<'info, ParticipantWallet>>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + ErasureTombstone::INIT_SPACE,
+        seeds = [b"tombstone", participant.key().as_ref()],
+        bump
+    )]
+    pub tombstone: Account<'info, ErasureTombstone>,
+    // Required while the participant has a guardian; closed along with the profile
+    #[account(mut, seeds = [b"guardianship", participant.key().as_ref()], bump = guardianship.bump)]
+    pub guardianship: Option<Account<'info, Guardianship>>,
+    /// CHECK: Gets the guardianship's rent back, checked against `participant.guardian`
+    #[account(mut)]
+    pub guardian: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(identity_hash: [u8; 32])]
+pub struct AttestIdentity<'info> {
+    #[account(seeds = [b"admin"], bump = admin.bump)]
+    pub admin: Account<'info, Admin>,
+    #[account(mut, has_one = authority)]
+    pub participant: Account<'info, Participant>,
+    // Re-used when an erased participant re-registers under the same authority
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + IdentityRecord::INIT_SPACE,
+        seeds = [b"identity", identity_hash.as_ref()],
+        bump
+    )]
+    pub identity_record: Account<'info, IdentityRecord>,
+    /// CHECK: Instructions sysvar, read to find the attester's Ed25519 signature
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeIdentity<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump = admin.bump,
+        constraint = admin.is_authorized(&authority.key()) @ RecruSearchError::UnauthorizedAdmin
+    )]
+    pub admin: Account<'info, Admin>,
+    #[account(
+        mut,
+        seeds = [b"identity", identity_record.identity_hash.as_ref()],
+        bump = identity_record.bump,
+    )]
+    pub identity_record: Account<'info, IdentityRecord>,
+    /// CHECK: Always the participant the identity is bound to; suspended unless erasure
+    /// has already closed the account
+    #[account(mut, address = identity_record.participant)]
+    pub participant: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct EstablishGuardianship<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump = admin.bump,
+        constraint = admin.is_authorized(&approver.key()) || admin.is_identity_attester(&approver.key())
+            @ RecruSearchError::UnauthorizedGuardianshipApproval
+    )]
+    pub admin: Account<'info, Admin>,
+    #[account(mut, constraint = participant.guardian.is_none() @ RecruSearchError::GuardianAlreadySet)]
+    pub participant: Account<'info, Participant>,
+    #[account(
+        init,
+        payer = guardian,
+        space = 8 + Guardianship::INIT_SPACE,
+        seeds = [b"guardianship", participant.key().as_ref()],
+        bump
+    )]
+    pub guardianship: Account<'info, Guardianship>,
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+    pub approver: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordAssent<'info> {
+    pub study: Account<'info, Study>,
+    #[account(
+        has_one = authority,
+        constraint = participant.guardian.is_some() @ RecruSearchError::GuardianConsentRequired
+    )]
+    pub participant: Account<'info, Participant>,
+    // Created by the assent when it comes before the consent is issued
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + StudyConsent::INIT_SPACE,
+        seeds = [b"study_consent", study.key().as_ref(), participant.key().as_ref()],
+        bump
+    )]
+    pub study_consent: Account<'info, StudyConsent>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Signed by either the guardian or the participant
+#[derive(Accounts)]
+pub struct WithdrawGuardianConsent<'info> {
+    #[account(mut)]
+    pub study: Account<'info, Study>,
+    #[account(mut)]
+    pub participant: Account<'info, Participant>,
+    #[account(seeds = [b"guardianship", participant.key().as_ref()], bump = guardianship.bump)]
+    pub guardianship: Account<'info, Guardianship>,
+    #[account(
+        mut,
+        seeds = [b"enrollment", study.key().as_ref(), participant.key().as_ref()],
+        bump = enrollment.bump,
+    )]
+    pub enrollment: Account<'info, Enrollment>,
+    #[account(
+        mut,
+        seeds = [b"vault", study.key().as_ref()],
+        bump = study.vault_bump,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = study.reward_mint,
+        token::authority = participant.authority,
+    )]
+    pub participant_token_account: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [b"waitlist", study.key().as_ref()], bump = waitlist.bump)]
+    pub waitlist: Option<Account<'info, Waitlist>>,
+    #[account(
+        mut,
+        seeds = [b"study_consent", study.key().as_ref(), participant.key().as_ref()],
+        bump = study_consent.bump,
+    )]
+    pub study_consent: Account<'info, StudyConsent>,
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
 #[derive(Accounts)]
 pub struct InitializePrivacyManager<'info> {
     #[account(mut)]
@@ -295,4 +1112,212 @@ pub struct RevokeDataAccess<'info> {
     #[account(mut)]
     pub participant: Account<'info, Participant>,
     pub system_program: Program<'info, System>,
-}
\ No newline at end of file
+}
+
+#[derive(Accounts)]
+pub struct ManageTrustedVerifiers<'info> {
+    #[account(
+        mut,
+        seeds = [b"admin"],
+        bump = admin.bump,
+        constraint = admin.is_authorized(&authority.key()) @ RecruSearchError::UnauthorizedAdmin
+    )]
+    pub admin: Account<'info, Admin>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePlatformConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"admin"],
+        bump = admin.bump,
+        constraint = admin.is_authorized(&authority.key()) @ RecruSearchError::UnauthorizedAdmin
+    )]
+    pub admin: Account<'info, Admin>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GrantEthicsApproval<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump = admin.bump,
+        constraint = admin.is_review_board(&board.key()) @ RecruSearchError::UnauthorizedReviewBoard
+    )]
+    pub admin: Account<'info, Admin>,
+    pub study: Account<'info, Study>,
+    // Re-granting renews an existing approval
+    #[account(
+        init_if_needed,
+        payer = board,
+        space = 8 + EthicsApproval::INIT_SPACE,
+        seeds = [b"ethics_approval", study.key().as_ref()],
+        bump
+    )]
+    pub ethics_approval: Account<'info, EthicsApproval>,
+    #[account(mut)]
+    pub board: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeEthicsApproval<'info> {
+    #[account(
+        mut,
+        has_one = board @ RecruSearchError::UnauthorizedReviewBoard,
+        seeds = [b"ethics_approval", ethics_approval.study.as_ref()],
+        bump = ethics_approval.bump,
+    )]
+    pub ethics_approval: Account<'info, EthicsApproval>,
+    pub board: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ManageStudyTeam<'info> {
+    pub study: Account<'info, Study>,
+    #[account(has_one = authority @ RecruSearchError::UnauthorizedResearcher)]
+    pub researcher: Account<'info, Researcher>,
+    #[account(
+        mut,
+        seeds = [b"study_team", study.key().as_ref()],
+        bump = team.bump,
+        constraint = team.role_of(&researcher.key()) == Some(TeamRole::PrincipalInvestigator) @ RecruSearchError::UnauthorizedResearcher
+    )]
+    pub team: Account<'info, StudyTeam>,
+    // The researcher being added or removed
+    pub member: Account<'info, Researcher>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureRandomization<'info> {
+    #[account(mut)]
+    pub study: Account<'info, Study>,
+    #[account(has_one = authority @ RecruSearchError::UnauthorizedResearcher)]
+    pub researcher: Account<'info, Researcher>,
+    #[account(
+        seeds = [b"study_team", study.key().as_ref()],
+        bump = team.bump,
+        constraint = team.allows(&researcher.key(), TeamPermission::ManageStudy) @ RecruSearchError::UnauthorizedResearcher
+    )]
+    pub team: Account<'info, StudyTeam>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Allocation::INIT_SPACE,
+        seeds = [b"allocation", study.key().as_ref()],
+        bump
+    )]
+    pub allocation: Account<'info, Allocation>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DrawArm<'info> {
+    pub study: Account<'info, Study>,
+    #[account(mut, seeds = [b"allocation", study.key().as_ref()], bump = allocation.bump)]
+    pub allocation: Account<'info, Allocation>,
+    #[account(
+        mut,
+        seeds = [b"enrollment", study.key().as_ref(), enrollment.participant.as_ref()],
+        bump = enrollment.bump,
+    )]
+    pub enrollment: Account<'info, Enrollment>,
+    /// CHECK: SlotHashes sysvar, mixed into the arm draw
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+    pub cranker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RevealAllocationSeed<'info> {
+    pub study: Account<'info, Study>,
+    #[account(has_one = authority @ RecruSearchError::UnauthorizedResearcher)]
+    pub researcher: Account<'info, Researcher>,
+    #[account(
+        seeds = [b"study_team", study.key().as_ref()],
+        bump = team.bump,
+        constraint = team.allows(&researcher.key(), TeamPermission::ManageStudy) @ RecruSearchError::UnauthorizedResearcher
+    )]
+    pub team: Account<'info, StudyTeam>,
+    #[account(mut, seeds = [b"allocation", study.key().as_ref()], bump = allocation.bump)]
+    pub allocation: Account<'info, Allocation>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PostArmCommitments<'info> {
+    pub study: Account<'info, Study>,
+    #[account(has_one = authority @ RecruSearchError::UnauthorizedResearcher)]
+    pub researcher: Account<'info, Researcher>,
+    #[account(
+        seeds = [b"study_team", study.key().as_ref()],
+        bump = team.bump,
+        constraint = team.allows(&researcher.key(), TeamPermission::ManageStudy) @ RecruSearchError::UnauthorizedResearcher
+    )]
+    pub team: Account<'info, StudyTeam>,
+    #[account(mut, seeds = [b"allocation", study.key().as_ref()], bump = allocation.bump)]
+    pub allocation: Account<'info, Allocation>,
+    pub authority: Signer<'info>,
+}
+
+// The enrollments to unblind are passed as writable remaining accounts
+#[derive(Accounts)]
+pub struct RevealAllocations<'info> {
+    pub study: Account<'info, Study>,
+    #[account(has_one = authority @ RecruSearchError::UnauthorizedResearcher)]
+    pub researcher: Account<'info, Researcher>,
+    #[account(
+        seeds = [b"study_team", study.key().as_ref()],
+        bump = team.bump,
+        constraint = team.allows(&researcher.key(), TeamPermission::ManageStudy) @ RecruSearchError::UnauthorizedResearcher
+    )]
+    pub team: Account<'info, StudyTeam>,
+    #[account(mut, seeds = [b"allocation", study.key().as_ref()], bump = allocation.bump)]
+    pub allocation: Account<'info, Allocation>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyUnblind<'info> {
+    pub study: Account<'info, Study>,
+    #[account(has_one = authority @ RecruSearchError::UnauthorizedResearcher)]
+    pub researcher: Account<'info, Researcher>,
+    #[account(
+        seeds = [b"study_team", study.key().as_ref()],
+        bump = team.bump,
+        constraint = team.allows(&researcher.key(), TeamPermission::ManageStudy) @ RecruSearchError::UnauthorizedResearcher
+    )]
+    pub team: Account<'info, StudyTeam>,
+    #[account(mut, seeds = [b"allocation", study.key().as_ref()], bump = allocation.bump)]
+    pub allocation: Account<'info, Allocation>,
+    #[account(
+        mut,
+        seeds = [b"enrollment", study.key().as_ref(), enrollment.participant.as_ref()],
+        bump = enrollment.bump,
+    )]
+    pub enrollment: Account<'info, Enrollment>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + UnblindingRecord::INIT_SPACE,
+        seeds = [b"unblinding", enrollment.key().as_ref()],
+        bump
+    )]
+    pub unblinding_record: Account<'info, UnblindingRecord>,
+    #[account(seeds = [b"admin"], bump = admin.bump)]
+    pub admin: Account<'info, Admin>,
+    #[account(seeds = [b"ethics_approval", study.key().as_ref()], bump = ethics_approval.bump)]
+    pub ethics_approval: Account<'info, EthicsApproval>,
+    #[account(
+        constraint = admin.is_authorized(&co_signer.key())
+            || co_signer.key() == ethics_approval.board @ RecruSearchError::UnauthorizedUnblinding
+    )]
+    pub co_signer: Signer<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
@@ -120,7 +120,7 @@ pub enum RecruSearchError {
     #[msg("Feedback too long")]
     FeedbackTooLong,
     
-    /// Participant already has an active consent
+    /// Participant already has an active consent to this study
     #[msg("Participant already has an active consent")]
     DuplicateConsent,
     
@@ -141,4 +141,339 @@ pub enum RecruSearchError {
     /// Arithmetic overflow occurred
     #[msg("Arithmetic overflow")]
     ArithmeticOverflow,
-} 
\ No newline at end of file
+
+    /// Participant has no active enrollment in the study
+    #[msg("Participant is not enrolled in this study")]
+    NotEnrolled,
+
+    /// Requested study status change is not allowed from the current status
+    #[msg("Invalid study status transition")]
+    InvalidStatusTransition,
+
+    /// Progress updates may only move forward
+    #[msg("Progress cannot go backwards")]
+    ProgressRegression,
+
+    /// Study eligibility criteria exceed their bounds or contain empty values
+    #[msg("Invalid eligibility criteria")]
+    InvalidEligibilityCriteria,
+
+    /// Profile attribute is empty or too long
+    #[msg("Invalid profile attribute")]
+    InvalidProfileAttribute,
+
+    /// Study criteria need an attribute the participant did not disclose
+    #[msg("Required attribute was not disclosed")]
+    MissingAttributeDisclosure,
+
+    /// Disclosed attribute does not prove against the participant's commitment
+    #[msg("Invalid attribute inclusion proof")]
+    InvalidAttributeProof,
+
+    /// Participant's age group is not accepted by the study
+    #[msg("Participant age group not eligible")]
+    IneligibleAgeGroup,
+
+    /// Participant's gender is not accepted by the study
+    #[msg("Participant gender not eligible")]
+    IneligibleGender,
+
+    /// Participant's region is not accepted by the study
+    #[msg("Participant region not eligible")]
+    IneligibleRegion,
+
+    /// Participant lacks an interest the study requires
+    #[msg("Participant is missing a required interest")]
+    MissingRequiredInterest,
+
+    /// Participant's reputation is below the study minimum
+    #[msg("Insufficient reputation score")]
+    InsufficientReputation,
+
+    /// Participant has completed fewer studies than the study requires
+    #[msg("Insufficient completed studies")]
+    InsufficientCompletedStudies,
+
+    /// Trusted verifier list is full
+    #[msg("Too many trusted verifiers")]
+    TooManyVerifiers,
+
+    /// Attestation was signed by a key the admin has not registered
+    #[msg("Verifier is not trusted")]
+    UntrustedVerifier,
+
+    /// Study requires a verifier attestation and none was supplied
+    #[msg("Eligibility attestation required")]
+    AttestationRequired,
+
+    /// Attestation expiry has passed
+    #[msg("Eligibility attestation expired")]
+    AttestationExpired,
+
+    /// No matching Ed25519 signature over the attestation was found
+    #[msg("Invalid eligibility attestation")]
+    InvalidAttestation,
+
+    /// Instruction is not allowed in the study's current phase
+    #[msg("Action not allowed in the current study phase")]
+    InvalidStudyPhase,
+
+    /// A string or vector argument exceeds the space reserved for it
+    #[msg("Input exceeds maximum length")]
+    InputTooLong,
+
+    /// The vault cannot cover another participant's reward
+    #[msg("Insufficient escrow for another participant")]
+    InsufficientEscrow,
+
+    /// Waitlisting is only for studies with no open slot
+    #[msg("Study still has open slots")]
+    StudyNotFull,
+
+    #[msg("Waitlist is full")]
+    WaitlistFull,
+
+    #[msg("Participant is already on the waitlist")]
+    AlreadyWaitlisted,
+
+    /// The study has queued participants or held slots, so its waitlist must be passed
+    #[msg("Study waitlist account required")]
+    WaitlistRequired,
+
+    #[msg("Participant is already enrolled in this study")]
+    AlreadyEnrolled,
+
+    #[msg("Invalid study schedule")]
+    InvalidSchedule,
+
+    #[msg("Recruitment has not opened yet")]
+    RecruitmentNotOpen,
+
+    #[msg("Recruitment has closed")]
+    RecruitmentClosed,
+
+    /// The enrollment's completion deadline has passed
+    #[msg("Completion deadline has passed")]
+    DeadlinePassed,
+
+    #[msg("Completion deadline has not been reached")]
+    DeadlineNotReached,
+
+    /// Remaining accounts must come in whole groups, e.g. (enrollment, participant, study consent)
+    #[msg("Remaining accounts must come in complete groups")]
+    InvalidAccountGroups,
+
+    #[msg("Study requires an ethics approval")]
+    MissingEthicsApproval,
+
+    #[msg("Study type requires a written-consent version")]
+    MissingConsentVersion,
+
+    #[msg("Interview studies require session slots")]
+    MissingSessionSlots,
+
+    #[msg("Invalid session slot")]
+    InvalidSessionSlot,
+
+    /// The study needs the participant to hold an active consent NFT
+    #[msg("Active consent required to join this study")]
+    ConsentRequired,
+
+    #[msg("Too many review boards")]
+    TooManyReviewBoards,
+
+    /// Signer is not a registered review board, or not the approval's board
+    #[msg("Unauthorized review board")]
+    UnauthorizedReviewBoard,
+
+    #[msg("Invalid ethics approval validity period")]
+    InvalidEthicsApproval,
+
+    /// The approval has expired, is not yet valid, or was revoked
+    #[msg("Ethics approval is not in force")]
+    EthicsApprovalInactive,
+
+    /// Teams have exactly one principal investigator, set when the study is created
+    #[msg("Invalid team role")]
+    InvalidTeamRole,
+
+    #[msg("Researcher is already on the study team")]
+    AlreadyTeamMember,
+
+    #[msg("Researcher is not on the study team")]
+    NotTeamMember,
+
+    #[msg("Study team is full")]
+    TeamFull,
+
+    #[msg("Invalid demographic quota")]
+    InvalidQuota,
+
+    /// The participant's stratum is at its maximum, or the remaining slots are
+    /// held for other strata still short of their minimum
+    #[msg("Participant's stratum is full")]
+    StratumFull,
+
+    #[msg("Invalid randomization settings")]
+    InvalidRandomization,
+
+    /// Already revealed, or does not hash to the committed value
+    #[msg("Allocation seed does not match its commitment")]
+    InvalidAllocationSeed,
+
+    #[msg("Allocation seed has not been revealed")]
+    AllocationSeedNotRevealed,
+
+    /// Randomized studies must be joined with their allocation
+    #[msg("Allocation accounts required")]
+    AllocationRequired,
+
+    /// The join slot's hash is only recorded once a later slot starts
+    #[msg("Slot hashes unavailable")]
+    RandomnessUnavailable,
+
+    /// A blinded study's seed is its allocation key and stays secret until close
+    #[msg("Allocation is blinded")]
+    AllocationBlinded,
+
+    /// The PI has not queued an arm commitment for the participant's stratum
+    #[msg("No arm commitments left to allocate")]
+    AllocationExhausted,
+
+    #[msg("Arm commitment does not open")]
+    InvalidArmCommitment,
+
+    #[msg("Unblinding needs a reason of at most 200 characters")]
+    InvalidUnblindingReason,
+
+    /// Co-signer is neither the platform admin nor the study's ethics board
+    #[msg("Unauthorized unblinding")]
+    UnauthorizedUnblinding,
+
+    #[msg("No response submitted")]
+    NoResponseSubmitted,
+
+    #[msg("Dataset has not been anchored")]
+    DatasetNotAnchored,
+
+    /// The anchored tree must have one leaf for every submitted response
+    #[msg("Dataset entry count does not match submissions")]
+    DatasetIncomplete,
+
+    #[msg("Entry is not in the anchored dataset")]
+    NotInDataset,
+
+    #[msg("Completion has not been claimed")]
+    CompletionNotClaimed,
+
+    /// The researcher can still answer the claim
+    #[msg("Confirmation window is still open")]
+    ConfirmationWindowOpen,
+
+    #[msg("Confirmation window has closed")]
+    ConfirmationWindowClosed,
+
+    #[msg("Dispute is not open")]
+    DisputeNotOpen,
+
+    /// Only removals and expiries with an unpaid reward can be contested
+    #[msg("Enrollment cannot be disputed")]
+    EnrollmentNotContestable,
+
+    #[msg("Dispute filing window has closed")]
+    DisputeFilingClosed,
+
+    #[msg("Too much evidence submitted")]
+    TooMuchEvidence,
+
+    /// Signer is neither the participant nor a member of the study team
+    #[msg("Not a party to this dispute")]
+    NotDisputeParty,
+
+    #[msg("Partial payment must be between zero and the held reward")]
+    InvalidRuling,
+
+    #[msg("Too many arbitrators")]
+    TooManyArbitrators,
+
+    #[msg("Unauthorized arbitrator")]
+    UnauthorizedArbitrator,
+
+    /// Withdraw from every study before erasing the profile
+    #[msg("Participant still has active enrollments")]
+    ActiveEnrollmentsRemain,
+
+    #[msg("Participant still has open disputes")]
+    OpenDisputesRemain,
+
+    /// Every live study consent must be passed to erasure so it can be revoked
+    #[msg("Participant still has active consents")]
+    ActiveConsentsRemain,
+
+    #[msg("Too many identity attesters")]
+    TooManyIdentityAttesters,
+
+    #[msg("Untrusted identity attester")]
+    UntrustedIdentityAttester,
+
+    /// The identity already backs a different participant
+    #[msg("Identity is already bound to another participant")]
+    IdentityAlreadyBound,
+
+    #[msg("Identity attestation has been revoked")]
+    IdentityRevoked,
+
+    #[msg("Participant already has an identity attestation")]
+    IdentityAlreadyAttested,
+
+    #[msg("Participant has no identity attestation")]
+    IdentityNotAttested,
+
+    /// Approver is neither the platform admin nor an identity attester
+    #[msg("Unauthorized guardianship approval")]
+    UnauthorizedGuardianshipApproval,
+
+    #[msg("Participant already has a guardian")]
+    GuardianAlreadySet,
+
+    #[msg("Guardian consent required")]
+    GuardianConsentRequired,
+
+    #[msg("Participant assent required")]
+    AssentRequired,
+
+    #[msg("Not the participant or their guardian")]
+    NotGuardianshipParty,
+
+    /// Queued arm draws are settled strictly in join order
+    #[msg("Arm draw is not next in the queue")]
+    DrawOutOfOrder,
+
+    #[msg("Enrollment has no pending arm draw")]
+    NoPendingDraw,
+
+    #[msg("Allocation list already committed")]
+    AllocationListCommitted,
+
+    /// Blinded strata only take commitments from a list fixed while the study was a draft
+    #[msg("Allocation list not committed")]
+    AllocationListNotCommitted,
+
+    /// Posted commitments are not the next entries of the committed list
+    #[msg("Commitments do not match the allocation list")]
+    AllocationListMismatch,
+
+    /// Erasure must close the guardianship and return its rent to the guardian
+    #[msg("Guardianship accounts required")]
+    GuardianshipRequired,
+
+    #[msg("Exit reason is reserved")]
+    ReservedExitReason,
+
+    #[msg("Study does not require guardian consent")]
+    GuardianConsentNotRequired,
+
+    /// Revoking consent mid-study also ends the enrollment, which needs the vault and reward account
+    #[msg("Exit accounts required for an active enrollment")]
+    ExitAccountsRequired,
+}
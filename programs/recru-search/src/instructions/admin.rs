@@ -1,11 +1,110 @@
 use anchor_lang::prelude::*;
-use crate::contexts::{InitializeAdmin, ReviewResearcherVerification, UpdateStudyStatus, ManageParticipantStatus};
-use crate::state::admin::{StudyStatus, ParticipantAction};
+use crate::contexts::{Initialize, InitializeAdmin, ManageTrustedVerifiers, UpdatePlatformConfig, ReviewResearcherVerification, UpdateStudyStatus, ManageParticipantStatus};
+use crate::state::admin::{Admin, StatusAuthority, StudyStatus, ParticipantAction};
+use crate::instructions::study::StudyStatusChanged;
+
+pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    ctx.accounts.admin.set_inner(Admin::new(authority, ctx.bumps.admin));
+    Ok(())
+}
 
 pub fn initialize_admin(ctx: Context<InitializeAdmin>) -> Result<()> {
+    let authority = ctx.accounts.payer.key();
+    ctx.accounts.admin.set_inner(Admin::new(authority, ctx.bumps.admin));
+    Ok(())
+}
+
+pub fn add_trusted_verifier(ctx: Context<ManageTrustedVerifiers>, verifier: Pubkey) -> Result<()> {
+    ctx.accounts.admin.add_trusted_verifier(verifier)?;
+    emit!(TrustedVerifierUpdated {
+        verifier,
+        trusted: true,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub fn remove_trusted_verifier(ctx: Context<ManageTrustedVerifiers>, verifier: Pubkey) -> Result<()> {
+    ctx.accounts.admin.remove_trusted_verifier(&verifier)?;
+    emit!(TrustedVerifierUpdated {
+        verifier,
+        trusted: false,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub fn add_review_board(ctx: Context<UpdatePlatformConfig>, board: Pubkey) -> Result<()> {
+    ctx.accounts.admin.add_review_board(board)?;
+    emit!(ReviewBoardUpdated {
+        board,
+        recognised: true,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub fn remove_review_board(ctx: Context<UpdatePlatformConfig>, board: Pubkey) -> Result<()> {
+    ctx.accounts.admin.remove_review_board(&board)?;
+    emit!(ReviewBoardUpdated {
+        board,
+        recognised: false,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub fn add_arbitrator(ctx: Context<UpdatePlatformConfig>, arbitrator: Pubkey) -> Result<()> {
+    ctx.accounts.admin.add_arbitrator(arbitrator)?;
+    emit!(ArbitratorUpdated {
+        arbitrator,
+        appointed: true,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub fn remove_arbitrator(ctx: Context<UpdatePlatformConfig>, arbitrator: Pubkey) -> Result<()> {
+    ctx.accounts.admin.remove_arbitrator(&arbitrator)?;
+    emit!(ArbitratorUpdated {
+        arbitrator,
+        appointed: false,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub fn add_identity_attester(ctx: Context<UpdatePlatformConfig>, attester: Pubkey) -> Result<()> {
+    ctx.accounts.admin.add_identity_attester(attester)?;
+    emit!(IdentityAttesterUpdated {
+        attester,
+        trusted: true,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub fn remove_identity_attester(ctx: Context<UpdatePlatformConfig>, attester: Pubkey) -> Result<()> {
+    ctx.accounts.admin.remove_identity_attester(&attester)?;
+    emit!(IdentityAttesterUpdated {
+        attester,
+        trusted: false,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub fn set_survey_consent_waiver(ctx: Context<UpdatePlatformConfig>, waived: bool) -> Result<()> {
     let admin = &mut ctx.accounts.admin;
-    admin.authority = ctx.accounts.payer.key();
-    // Initialize dashboard and other fields as needed (already handled by Admin::new if used)
+    let previous = admin.survey_consent_waiver;
+    admin.survey_consent_waiver = waived;
+    emit!(SurveyConsentWaiverUpdated {
+        previous,
+        waived,
+        updated_by: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
     Ok(())
 }
 
@@ -30,7 +129,19 @@ pub fn update_study_status(ctx: Context<UpdateStudyStatus>, status: StudyStatus)
     let admin = &ctx.accounts.admin;
     let study = &mut ctx.accounts.study;
     require!(admin.authority == ctx.accounts.authority.key(), crate::error::RecruSearchError::UnauthorizedAdmin);
-    study.status = status;
+    if status == StudyStatus::Recruiting {
+        ctx.accounts.ethics_approval.as_ref()
+            .ok_or(crate::error::RecruSearchError::MissingEthicsApproval)?
+            .require_valid(admin)?;
+    }
+    let from = study.transition(status, StatusAuthority::Admin)?;
+    emit!(StudyStatusChanged {
+        study: study.key(),
+        from,
+        to: status,
+        authority: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
     Ok(())
 }
 
@@ -44,4 +155,40 @@ pub fn manage_participant(ctx: Context<ManageParticipantStatus>, action: Partici
         ParticipantAction::Ban => participant.banned = true,
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+#[event]
+pub struct TrustedVerifierUpdated {
+    pub verifier: Pubkey,
+    pub trusted: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReviewBoardUpdated {
+    pub board: Pubkey,
+    pub recognised: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ArbitratorUpdated {
+    pub arbitrator: Pubkey,
+    pub appointed: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct IdentityAttesterUpdated {
+    pub attester: Pubkey,
+    pub trusted: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SurveyConsentWaiverUpdated {
+    pub previous: bool,
+    pub waived: bool,
+    pub updated_by: Pubkey,
+    pub timestamp: i64,
+}
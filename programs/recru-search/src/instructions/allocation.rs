@@ -0,0 +1,294 @@
+use anchor_lang::prelude::*;
+use crate::contexts::{
+    ConfigureRandomization, DrawArm, EmergencyUnblind, PostArmCommitments, RevealAllocationSeed, RevealAllocations,
+};
+use crate::error::RecruSearchError;
+use crate::state::admin::StudyStatus;
+use crate::instructions::study::ArmDrawQueued;
+use crate::state::allocation::{join_slot_hash, PendingDraw, RandomizationMethod, StudyArm, MAX_UNBLINDING_REASON_LEN};
+use crate::state::enrollment::Enrollment;
+
+/// Declares the study's arms and commits to the allocation seed; fixed once set
+pub fn configure_randomization(
+    ctx: Context<ConfigureRandomization>,
+    method: RandomizationMethod,
+    blinded: bool,
+    arms: Vec<StudyArm>,
+    block_multiple: u8,
+    seed_commitment: [u8; 32],
+) -> Result<()> {
+    let study = &mut ctx.accounts.study;
+    study.require_draft()?;
+    let allocation = &mut ctx.accounts.allocation;
+    allocation.configure(
+        study.key(),
+        method,
+        blinded,
+        arms,
+        block_multiple,
+        seed_commitment,
+        ctx.bumps.allocation,
+    )?;
+    study.randomized = true;
+
+    emit!(RandomizationConfigured {
+        study: study.key(),
+        allocation: allocation.key(),
+        method,
+        blinded,
+        arms: allocation.arms.len() as u8,
+        seed_commitment,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Reveals the committed seed. Only possible once recruitment has opened, so the
+/// sequence cannot be worked out while the protocol is still being set up. Knowing the
+/// seed does not reveal an arm ahead of a join: each draw also takes the hash of the
+/// join slot, which only exists after the enrollment is committed (see `draw_arm`).
+pub fn reveal_allocation_seed(ctx: Context<RevealAllocationSeed>, seed: [u8; 32]) -> Result<()> {
+    ctx.accounts.study.require_status(&[StudyStatus::Recruiting, StudyStatus::Active])?;
+    let allocation = &mut ctx.accounts.allocation;
+    require!(!allocation.blinded, RecruSearchError::AllocationBlinded);
+    allocation.reveal(seed)?;
+
+    emit!(AllocationSeedRevealed {
+        study: allocation.study,
+        seed,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Permissionless: draws the arm of the next queued enrollment from its join slot's
+/// hash. The draw runs regardless of whether the participant has since left, so
+/// dropping out after seeing the arm cannot free up a second attempt. A draw left until
+/// its slot has aged out of the SlotHashes window is queued again at the current slot.
+pub fn draw_arm(ctx: Context<DrawArm>) -> Result<()> {
+    let study = &ctx.accounts.study;
+    let allocation = &mut ctx.accounts.allocation;
+    let enrollment = &mut ctx.accounts.enrollment;
+    let pending = enrollment.pending_draw.ok_or(RecruSearchError::NoPendingDraw)?;
+    let clock = Clock::get()?;
+    // The current slot's hash is only recorded once the slot is over
+    require!(clock.slot > pending.slot, RecruSearchError::RandomnessUnavailable);
+    let slot = pending.slot;
+    let Ok(slot_hash) = join_slot_hash(&ctx.accounts.slot_hashes, slot) else {
+        let requeued = PendingDraw { sequence: pending.sequence, slot: clock.slot };
+        enrollment.pending_draw = Some(requeued);
+        emit!(ArmDrawQueued {
+            study: study.key(),
+            participant: enrollment.participant,
+            sequence: requeued.sequence,
+            slot: requeued.slot,
+            timestamp: clock.unix_timestamp,
+        });
+        return Ok(());
+    };
+    let stratum = allocation.stratum_of(&study.quotas, &enrollment.strata);
+    let draw = allocation.draw(&study.key(), &enrollment.participant, stratum, &pending, &slot_hash)?;
+    enrollment.arm = Some(draw.arm);
+    enrollment.pending_draw = None;
+
+    emit!(ArmAssigned {
+        study: study.key(),
+        participant: enrollment.participant,
+        arm: draw.arm,
+        sequence: draw.sequence,
+        stratum,
+        slot,
+        slot_hash,
+        randomness: draw.randomness,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Fixes a blinded stratum's whole allocation list while the study is a draft, so the
+/// PI cannot pick later arms after seeing who has joined
+pub fn commit_allocation_list(
+    ctx: Context<PostArmCommitments>,
+    stratum: Option<u8>,
+    list_head: [u8; 32],
+) -> Result<()> {
+    ctx.accounts.study.require_draft()?;
+    ctx.accounts.allocation.commit_list(stratum, list_head)?;
+
+    emit!(AllocationListCommitted {
+        study: ctx.accounts.study.key(),
+        stratum,
+        list_head,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Queues the next entries of a blinded stratum's committed list for upcoming joins;
+/// `rest` is the list hash of the entries after them
+pub fn post_arm_commitments(
+    ctx: Context<PostArmCommitments>,
+    stratum: Option<u8>,
+    commitments: Vec<[u8; 32]>,
+    rest: [u8; 32],
+) -> Result<()> {
+    ctx.accounts.study.require_status(&[StudyStatus::Draft, StudyStatus::Recruiting, StudyStatus::Active])?;
+    ctx.accounts.allocation.post_commitments(stratum, commitments, rest)
+}
+
+/// Unblinds a closed study: checks the PI's allocation key against its commitment
+/// and opens the arm of every enrollment passed in the remaining accounts. Can be
+/// repeated with further batches.
+pub fn reveal_allocations<'info>(
+    ctx: Context<'_, '_, 'info, 'info, RevealAllocations<'info>>,
+    key: [u8; 32],
+) -> Result<()> {
+    let study = &ctx.accounts.study;
+    study.require_status(&[StudyStatus::Closed, StudyStatus::Archived, StudyStatus::Cancelled])?;
+    let allocation = &mut ctx.accounts.allocation;
+    require!(allocation.blinded, RecruSearchError::InvalidRandomization);
+    let first_reveal = allocation.seed.is_none();
+    allocation.unseal(key)?;
+
+    let mut revealed = 0u32;
+    for account in ctx.remaining_accounts {
+        let mut enrollment = Account::<Enrollment>::try_from(account)?;
+        require_keys_eq!(enrollment.study, study.key(), RecruSearchError::NotEnrolled);
+        // Already opened by an emergency unblinding
+        if enrollment.arm.is_some() {
+            continue;
+        }
+        let commitment = enrollment.arm_commitment.ok_or(RecruSearchError::InvalidArmCommitment)?;
+        let stratum = allocation.stratum_of(&study.quotas, &enrollment.strata);
+        let arm = allocation.open(&key, stratum, &commitment)?;
+        allocation.record_assignment(arm)?;
+        enrollment.arm = Some(arm);
+        enrollment.exit(&crate::ID)?;
+        revealed += 1;
+    }
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    if first_reveal {
+        emit!(AllocationSeedRevealed {
+            study: study.key(),
+            seed: key,
+            timestamp,
+        });
+    }
+    emit!(AllocationsRevealed {
+        study: study.key(),
+        revealed,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+/// Opens one participant's arm before close, e.g. after a serious adverse event.
+/// The PI supplies the opening; an admin or the study's ethics board co-signs, and
+/// the reason is kept in a permanent `UnblindingRecord`.
+pub fn emergency_unblind(
+    ctx: Context<EmergencyUnblind>,
+    arm: u8,
+    salt: [u8; 32],
+    reason: String,
+) -> Result<()> {
+    require!(
+        !reason.is_empty() && reason.len() <= MAX_UNBLINDING_REASON_LEN,
+        RecruSearchError::InvalidUnblindingReason
+    );
+    let enrollment = &mut ctx.accounts.enrollment;
+    require!(enrollment.arm.is_none(), RecruSearchError::InvalidArmCommitment);
+    let commitment = enrollment.arm_commitment.ok_or(RecruSearchError::InvalidArmCommitment)?;
+    require!(
+        (arm as usize) < ctx.accounts.allocation.arms.len() && commitment.opens(arm, &salt),
+        RecruSearchError::InvalidArmCommitment
+    );
+    ctx.accounts.allocation.record_assignment(arm)?;
+    enrollment.arm = Some(arm);
+
+    let now = Clock::get()?.unix_timestamp;
+    let record = &mut ctx.accounts.unblinding_record;
+    record.study = ctx.accounts.study.key();
+    record.enrollment = enrollment.key();
+    record.arm = arm;
+    record.reason.clone_from(&reason);
+    record.requested_by = ctx.accounts.researcher.key();
+    record.authorized_by = ctx.accounts.co_signer.key();
+    record.unblinded_at = now;
+    record.bump = ctx.bumps.unblinding_record;
+
+    emit!(EmergencyUnblinded {
+        study: record.study,
+        participant: enrollment.participant,
+        arm,
+        reason,
+        requested_by: record.requested_by,
+        authorized_by: record.authorized_by,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct RandomizationConfigured {
+    pub study: Pubkey,
+    pub allocation: Pubkey,
+    pub method: RandomizationMethod,
+    pub blinded: bool,
+    pub arms: u8,
+    pub seed_commitment: [u8; 32],
+    pub timestamp: i64,
+}
+
+/// One randomized assignment; with the revealed seed, enough to replay the draw
+#[event]
+pub struct ArmAssigned {
+    pub study: Pubkey,
+    pub participant: Pubkey,
+    pub arm: u8,
+    pub sequence: u32,
+    pub stratum: Option<u8>,
+    pub slot: u64,
+    pub slot_hash: [u8; 32],
+    pub randomness: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AllocationListCommitted {
+    pub study: Pubkey,
+    pub stratum: Option<u8>,
+    pub list_head: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AllocationSeedRevealed {
+    pub study: Pubkey,
+    pub seed: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AllocationsRevealed {
+    pub study: Pubkey,
+    pub revealed: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EmergencyUnblinded {
+    pub study: Pubkey,
+    pub participant: Pubkey,
+    pub arm: u8,
+    pub reason: String,
+    pub requested_by: Pubkey,
+    pub authorized_by: Pubkey,
+    pub timestamp: i64,
+}
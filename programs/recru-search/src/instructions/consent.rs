@@ -1,10 +1,14 @@
 use anchor_lang::prelude::*;
-use anchor_spl::metadata::{self, Metadata};
+use anchor_spl::metadata;
 use anchor_spl::token;
 // Use proper import path for DataV2 in version 4.1.2
 use mpl_token_metadata::types::DataV2;
 use crate::error::RecruSearchError;
+use crate::state::consent::{MAX_CONSENT_HASH_LEN, MAX_CONSENT_VERSION_LEN};
+use crate::state::eligibility::AttributeDisclosure;
+use crate::state::enrollment::{Enrollment, EnrollmentStatus, ExitReason};
 use crate::contexts::{InitializeConsentNFT, IssueConsentNFT, RevokeConsent};
+use crate::instructions::study::end_enrollment;
 
 pub fn initialize_consent_nft(ctx: Context<InitializeConsentNFT>) -> Result<()> {
     let consent = &mut ctx.accounts.consent;
@@ -30,19 +34,47 @@ pub fn issue_consent_nft(
     ctx: Context<IssueConsentNFT>,
     consent_version: String,
     consent_hash: String,
+    disclosures: Vec<AttributeDisclosure>,
 ) -> Result<()> {
     let consent = &mut ctx.accounts.consent;
     let study = &mut ctx.accounts.study;
     let participant = &mut ctx.accounts.participant;
+    let study_consent = &mut ctx.accounts.study_consent;
+
+    require!(
+        !consent_version.is_empty() && consent_version.len() <= MAX_CONSENT_VERSION_LEN,
+        RecruSearchError::InvalidConsentVersion
+    );
+    require!(consent_hash.len() <= MAX_CONSENT_HASH_LEN, RecruSearchError::InputTooLong);
+    // Studies with a written-consent document only accept its current version
+    if let Some(current) = &study.consent_version {
+        require!(current == &consent_version, RecruSearchError::InvalidConsentVersion);
+    }
     
     // Verify that the participant is eligible for the study
-    require!(participant.profile.is_eligible_for_study(study), RecruSearchError::ParticipantNotEligible);
+    study.criteria.check(participant, &disclosures)?;
     
-    // Verify that the participant does not already have an active consent
-    require!(!participant.has_active_consent, RecruSearchError::DuplicateConsent);
-
     // Create the consent NFT metadata
     let issued_at = Clock::get()?.unix_timestamp;
+
+    // Consent is per study; this fails if the participant already consented to this one
+    study_consent.issue(
+        study.key(),
+        participant.key(),
+        consent_version.clone(),
+        issued_at,
+        ctx.bumps.study_consent,
+    )?;
+
+    // Minors and dependent adults consent through their guardian, on top of their own assent
+    if study.requires_guardian_consent {
+        let guardian = ctx.accounts.guardian.as_ref().ok_or(RecruSearchError::GuardianConsentRequired)?;
+        require!(
+            participant.guardian == Some(guardian.key()),
+            RecruSearchError::GuardianConsentRequired
+        );
+        study_consent.record_guardian_consent(issued_at)?;
+    }
     
     // Store consent data in program's own format
     let metadata_uri = format!("https://recrusearch.io/consent/{}/{}/{}", 
@@ -51,7 +83,7 @@ pub fn issue_consent_nft(
                              issued_at);
     
     // Update consent status for participant
-    participant.update_consent_status(true)?;
+    participant.add_consent()?;
     
     // Increment the study's consent count
     study.increment_consent()?;
@@ -67,9 +99,9 @@ pub fn issue_consent_nft(
     // Create metadata account using direct parameters instead of DataV2
     // Create a DataV2 struct that the function expects
     let data = DataV2 {
-        name: name,
-        symbol: symbol,
-        uri: uri,
+        name,
+        symbol,
+        uri,
         seller_fee_basis_points: 0,
         creators: None,
         collection: None,
@@ -121,38 +153,71 @@ pub fn issue_consent_nft(
 }
 
 pub fn revoke_consent(ctx: Context<RevokeConsent>) -> Result<()> {
-    let consent = &mut ctx.accounts.consent;
-    let participant = &mut ctx.accounts.participant;
+    let accounts = ctx.accounts;
     let clock = Clock::get()?;
     
-    // Verify participant has active consent
-    require!(participant.has_active_consent, RecruSearchError::NoActiveConsent);
-    
-    // Update participant's consent status
-    participant.update_consent_status(false)?;
+    // Verify participant has active consent to this study
+    require!(accounts.study_consent.is_active(), RecruSearchError::NoActiveConsent);
+    if let Some(guardian) = accounts.participant.guardian {
+        require!(
+            accounts.guardian.as_ref().is_some_and(|signer| signer.key() == guardian),
+            RecruSearchError::GuardianConsentRequired
+        );
+    }
+
+    let enrollment_info = accounts.enrollment.to_account_info();
+    let mut enrollment = match enrollment_info.data_is_empty() {
+        true => None,
+        false => {
+            require_keys_eq!(*enrollment_info.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+            Some(Enrollment::try_deserialize(&mut &enrollment_info.try_borrow_data()?[..])?)
+        }
+    };
+    match enrollment.as_mut().filter(|enrollment| enrollment.status == EnrollmentStatus::Enrolled) {
+        // Withdrawing consent while still enrolled takes the participant out of the study
+        Some(enrollment) => {
+            end_enrollment(
+                &mut accounts.study,
+                &mut accounts.participant,
+                enrollment,
+                accounts.vault.as_ref().ok_or(RecruSearchError::ExitAccountsRequired)?,
+                accounts.reward_token_account.as_ref().ok_or(RecruSearchError::ExitAccountsRequired)?,
+                &accounts.token_program,
+                accounts.waitlist.as_mut(),
+                Some(&mut accounts.study_consent),
+                EnrollmentStatus::Withdrawn,
+                ExitReason::ConsentWithdrawn,
+            )?;
+            enrollment.try_serialize(&mut &mut enrollment_info.try_borrow_mut_data()?[..])?;
+        }
+        None => {
+            accounts.study_consent.revoke(clock.unix_timestamp)?;
+            accounts.participant.remove_consent()?;
+            accounts.study.decrement_consent()?;
+            emit!(ConsentRevoked {
+                participant: accounts.participant.key(),
+                study: accounts.study.key(),
+                timestamp: clock.unix_timestamp,
+            });
+        }
+    }
     
     // Update consent NFT stats
-    consent.total_revoked += 1;
+    accounts.consent.total_revoked += 1;
     
     // Burn NFT from participant's token account
     token::burn(
         CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
+            accounts.token_program.to_account_info(),
             token::Burn {
-                mint: ctx.accounts.mint.to_account_info(),
-                from: ctx.accounts.participant_token_account.to_account_info(),
-                authority: ctx.accounts.consent.to_account_info(),
+                mint: accounts.mint.to_account_info(),
+                from: accounts.participant_token_account.to_account_info(),
+                authority: accounts.consent.to_account_info(),
             },
         ),
         1, // Burn 1 token
     )?;
     
-    // Emit event
-    emit!(ConsentRevoked {
-        participant: participant.key(),
-        timestamp: clock.unix_timestamp,
-    });
-    
     Ok(())
 }
 
@@ -168,5 +233,6 @@ pub struct ConsentIssued {
 #[event]
 pub struct ConsentRevoked {
     pub participant: Pubkey,
+    pub study: Pubkey,
     pub timestamp: i64,
 }
\ No newline at end of file
@@ -0,0 +1,179 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Transfer};
+use crate::contexts::{OpenDispute, ResolveDispute, SubmitDisputeEvidence};
+use crate::instructions::study::{refill_from_waitlist, revoke_exit_consent};
+use crate::state::dispute::{DisputeParty, DisputeRuling};
+use crate::state::enrollment::EnrollmentStatus;
+use crate::state::reputation::ReputationEvent;
+use crate::state::team::TeamPermission;
+use crate::error::RecruSearchError;
+
+/// Participant contests a removal or expiry; whatever of the reward they were not paid
+/// is frozen in the vault until an arbitrator rules. Researchers dispute through
+/// `reject_completion`.
+pub fn open_dispute(ctx: Context<OpenDispute>, reason_hash: [u8; 32]) -> Result<()> {
+    let study = &mut ctx.accounts.study;
+    let enrollment = &ctx.accounts.enrollment;
+    let now = Clock::get()?.unix_timestamp;
+    enrollment.require_contestable(now)?;
+
+    let owed = study.reward_amount.saturating_sub(enrollment.reward_paid);
+    require!(owed > 0, RecruSearchError::EnrollmentNotContestable);
+    // Only what the vault holds beyond other participants' rewards can be frozen
+    let amount = owed.min(ctx.accounts.vault.amount.saturating_sub(study.outstanding_rewards()?));
+    require!(amount > 0, RecruSearchError::InsufficientEscrow);
+    study.freeze_reward(amount)?;
+    ctx.accounts.participant.open_dispute()?;
+
+    let dispute = &mut ctx.accounts.dispute;
+    dispute.open(
+        study.key(),
+        enrollment.key(),
+        enrollment.participant,
+        DisputeParty::Participant,
+        reason_hash,
+        amount,
+        now,
+        ctx.bumps.dispute,
+    );
+
+    emit!(DisputeOpened {
+        dispute: dispute.key(),
+        study: study.key(),
+        participant: enrollment.participant,
+        opened_by: DisputeParty::Participant,
+        amount,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+pub fn submit_dispute_evidence(ctx: Context<SubmitDisputeEvidence>, evidence_hash: [u8; 32]) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    let party = if authority == ctx.accounts.participant.authority {
+        DisputeParty::Participant
+    } else {
+        let researcher = ctx.accounts.researcher.as_ref().ok_or(RecruSearchError::NotDisputeParty)?;
+        require!(
+            ctx.accounts.team.allows(&researcher.key(), TeamPermission::RecordProgress),
+            RecruSearchError::NotDisputeParty
+        );
+        DisputeParty::Researcher
+    };
+    let dispute = &mut ctx.accounts.dispute;
+    dispute.add_evidence(party, evidence_hash)?;
+
+    emit!(DisputeEvidenceSubmitted {
+        dispute: dispute.key(),
+        party,
+        evidence_hash,
+        submitted_by: authority,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Arbitrator's ruling: pays the award out of the vault, settles the enrollment and
+/// moves both parties' reputation
+pub fn resolve_dispute(ctx: Context<ResolveDispute>, ruling: DisputeRuling) -> Result<()> {
+    let accounts = ctx.accounts;
+    let now = Clock::get()?.unix_timestamp;
+    let arbitrator = accounts.arbitrator.key();
+    let dispute = &mut accounts.dispute;
+    let awarded = dispute.resolve(ruling, arbitrator, now)?;
+
+    let study = &mut accounts.study;
+    let participant = &mut accounts.participant;
+    let enrollment = &mut accounts.enrollment;
+    participant.close_dispute()?;
+    if enrollment.status == EnrollmentStatus::Disputed {
+        // A rejected claim still holds its slot, so the ruling decides how it ends
+        study.settle_pending_claim()?;
+        if awarded > 0 {
+            enrollment.uphold_completion(awarded, now)?;
+            study.complete_participant()?;
+            participant.increment_completed_studies()?;
+            participant.record_reputation(ReputationEvent::Completed)?;
+        } else {
+            // Ends like any other removal: the slot goes to the waitlist and consent lapses
+            enrollment.overturn_completion(now)?;
+            study.remove_participant()?;
+            study.release_strata(&enrollment.strata)?;
+            match accounts.waitlist.as_mut() {
+                Some(waitlist) => refill_from_waitlist(study, waitlist)?,
+                None => require!(!study.waitlist_in_use(), RecruSearchError::WaitlistRequired),
+            }
+            revoke_exit_consent(study, participant, enrollment.consented, accounts.study_consent.as_mut(), now)?;
+        }
+        participant.decrement_active_studies()?;
+    } else {
+        enrollment.add_award(awarded)?;
+        study.release_frozen_reward(dispute.amount)?;
+    }
+
+    if let Some((participant_outcome, researcher_outcome)) = ruling.reputation_outcomes() {
+        participant.record_reputation(participant_outcome)?;
+        accounts.researcher.record_reputation(researcher_outcome)?;
+    }
+
+    let study_key = study.key();
+    if awarded > 0 {
+        let vault_seeds: &[&[u8]] = &[b"vault", study_key.as_ref(), &[study.vault_bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                accounts.token_program.to_account_info(),
+                Transfer {
+                    from: accounts.vault.to_account_info(),
+                    to: accounts.participant_token_account.to_account_info(),
+                    authority: accounts.vault.to_account_info(),
+                },
+                &[vault_seeds],
+            ),
+            awarded,
+        )?;
+    }
+
+    emit!(DisputeResolved {
+        dispute: dispute.key(),
+        study: study_key,
+        participant: participant.key(),
+        ruling,
+        awarded,
+        resolved_by: arbitrator,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct DisputeOpened {
+    pub dispute: Pubkey,
+    pub study: Pubkey,
+    pub participant: Pubkey,
+    pub opened_by: DisputeParty,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DisputeEvidenceSubmitted {
+    pub dispute: Pubkey,
+    pub party: DisputeParty,
+    pub evidence_hash: [u8; 32],
+    pub submitted_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DisputeResolved {
+    pub dispute: Pubkey,
+    pub study: Pubkey,
+    pub participant: Pubkey,
+    pub ruling: DisputeRuling,
+    pub awarded: u64,
+    pub resolved_by: Pubkey,
+    pub timestamp: i64,
+}
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use crate::contexts::{GrantEthicsApproval, RevokeEthicsApproval};
+
+pub fn grant_ethics_approval(
+    ctx: Context<GrantEthicsApproval>,
+    protocol_number_hash: [u8; 32],
+    approval_document_hash: [u8; 32],
+    valid_from: i64,
+    valid_until: i64,
+) -> Result<()> {
+    let study = ctx.accounts.study.key();
+    let board = ctx.accounts.board.key();
+    let approval = &mut ctx.accounts.ethics_approval;
+    approval.grant(
+        study,
+        board,
+        protocol_number_hash,
+        approval_document_hash,
+        valid_from,
+        valid_until,
+        ctx.bumps.ethics_approval,
+    )?;
+
+    emit!(EthicsApprovalGranted {
+        study,
+        board,
+        approval: approval.key(),
+        valid_from,
+        valid_until,
+        timestamp: approval.granted_at,
+    });
+
+    Ok(())
+}
+
+pub fn revoke_ethics_approval(ctx: Context<RevokeEthicsApproval>) -> Result<()> {
+    let approval = &mut ctx.accounts.ethics_approval;
+    let now = Clock::get()?.unix_timestamp;
+    approval.revoke(now)?;
+
+    emit!(EthicsApprovalRevoked {
+        study: approval.study,
+        board: approval.board,
+        approval: approval.key(),
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct EthicsApprovalGranted {
+    pub study: Pubkey,
+    pub board: Pubkey,
+    pub approval: Pubkey,
+    pub valid_from: i64,
+    pub valid_until: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EthicsApprovalRevoked {
+    pub study: Pubkey,
+    pub board: Pubkey,
+    pub approval: Pubkey,
+    pub timestamp: i64,
+}
@@ -0,0 +1,110 @@
+use anchor_lang::prelude::*;
+use crate::contexts::{EstablishGuardianship, RecordAssent, WithdrawGuardianConsent};
+use crate::state::enrollment::{EnrollmentStatus, ExitReason};
+use crate::state::guardianship::GuardianshipParty;
+use crate::instructions::study::end_enrollment;
+use crate::error::RecruSearchError;
+
+/// Links a guardian to a participant, co-signed by the admin or an identity attester
+/// who has checked the relationship off-chain
+pub fn establish_guardianship(ctx: Context<EstablishGuardianship>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let participant = &mut ctx.accounts.participant;
+    let guardian = ctx.accounts.guardian.key();
+    let approver = ctx.accounts.approver.key();
+    ctx.accounts.guardianship.establish(participant.key(), guardian, approver, now, ctx.bumps.guardianship);
+    participant.guardian = Some(guardian);
+
+    emit!(GuardianshipEstablished {
+        participant: participant.key(),
+        guardian,
+        approved_by: approver,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+/// The participant's own assent to one study, which guardian consent to it cannot be
+/// issued without
+pub fn record_assent(ctx: Context<RecordAssent>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let study = ctx.accounts.study.key();
+    let participant = ctx.accounts.participant.key();
+    ctx.accounts.study_consent.record_assent(study, participant, now, ctx.bumps.study_consent);
+
+    emit!(AssentRecorded {
+        study,
+        participant,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+/// Either the guardian withdrawing consent or the participant withdrawing assent ends
+/// their participation in the study. Consent to other studies is unaffected.
+pub fn withdraw_guardian_consent(ctx: Context<WithdrawGuardianConsent>) -> Result<()> {
+    let accounts = ctx.accounts;
+    // Only a consent that was actually given can be withdrawn, and only where it was needed;
+    // anything else leaves through the ordinary, penalised withdrawal
+    require!(accounts.study.requires_guardian_consent, RecruSearchError::GuardianConsentNotRequired);
+    require!(
+        accounts.study_consent.guardian_consented_at.is_some(),
+        RecruSearchError::GuardianConsentRequired
+    );
+    let authority = accounts.authority.key();
+    let party = if authority == accounts.guardianship.guardian {
+        GuardianshipParty::Guardian
+    } else if authority == accounts.participant.authority {
+        GuardianshipParty::Participant
+    } else {
+        return err!(RecruSearchError::NotGuardianshipParty);
+    };
+    accounts.study_consent.withdraw(party);
+
+    end_enrollment(
+        &mut accounts.study,
+        &mut accounts.participant,
+        &mut accounts.enrollment,
+        &accounts.vault,
+        &accounts.participant_token_account,
+        &accounts.token_program,
+        accounts.waitlist.as_mut(),
+        Some(&mut accounts.study_consent),
+        EnrollmentStatus::Withdrawn,
+        ExitReason::ConsentWithdrawn,
+    )?;
+
+    emit!(GuardianConsentWithdrawn {
+        study: accounts.study.key(),
+        participant: accounts.participant.key(),
+        withdrawn_by: party,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct GuardianshipEstablished {
+    pub participant: Pubkey,
+    pub guardian: Pubkey,
+    pub approved_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AssentRecorded {
+    pub study: Pubkey,
+    pub participant: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GuardianConsentWithdrawn {
+    pub study: Pubkey,
+    pub participant: Pubkey,
+    pub withdrawn_by: GuardianshipParty,
+    pub timestamp: i64,
+}
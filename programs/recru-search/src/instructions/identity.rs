@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+use crate::contexts::{AttestIdentity, RevokeIdentity};
+use crate::state::attestation::verify_preceding_signature;
+use crate::state::identity::IdentityRecord;
+use crate::state::participant::Participant;
+use crate::error::RecruSearchError;
+
+/// Records a trusted attester's statement that the participant is the only account of
+/// the person behind `identity_hash`
+pub fn attest_identity(ctx: Context<AttestIdentity>, identity_hash: [u8; 32], attester: Pubkey) -> Result<()> {
+    require!(
+        ctx.accounts.admin.is_identity_attester(&attester),
+        RecruSearchError::UntrustedIdentityAttester
+    );
+    let participant = &mut ctx.accounts.participant;
+    require!(participant.identity.is_none(), RecruSearchError::IdentityAlreadyAttested);
+    verify_preceding_signature(
+        &ctx.accounts.instructions,
+        &attester,
+        &IdentityRecord::message(&identity_hash, &participant.key()),
+    )?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let record = &mut ctx.accounts.identity_record;
+    record.bind(identity_hash, participant.key(), attester, now, ctx.bumps.identity_record)?;
+    participant.identity = Some(record.key());
+
+    emit!(IdentityAttested {
+        participant: participant.key(),
+        identity_record: record.key(),
+        attester,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+/// Revokes an attestation found to be fraudulent and suspends the participant it backed.
+/// The record stays so the identity cannot be attested to another account.
+pub fn revoke_identity(ctx: Context<RevokeIdentity>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let record = &mut ctx.accounts.identity_record;
+    record.revoke(now)?;
+    // An erased profile leaves nothing to suspend
+    let info = ctx.accounts.participant.to_account_info();
+    if !info.data_is_empty() {
+        require_keys_eq!(*info.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+        let mut data = info.try_borrow_mut_data()?;
+        let mut participant = Participant::try_deserialize(&mut &data[..])?;
+        participant.identity = None;
+        participant.suspended = true;
+        participant.try_serialize(&mut &mut data[..])?;
+    }
+
+    emit!(IdentityRevoked {
+        participant: record.participant,
+        identity_record: record.key(),
+        revoked_by: ctx.accounts.authority.key(),
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct IdentityAttested {
+    pub participant: Pubkey,
+    pub identity_record: Pubkey,
+    pub attester: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct IdentityRevoked {
+    pub participant: Pubkey,
+    pub identity_record: Pubkey,
+    pub revoked_by: Pubkey,
+    pub timestamp: i64,
+}
@@ -5,6 +5,12 @@ pub mod study;
 pub mod consent;
 pub mod wallet;
 pub mod privacy;
+pub mod ethics;
+pub mod team;
+pub mod allocation;
+pub mod dispute;
+pub mod identity;
+pub mod guardianship;
 
 pub use admin::*;
 pub use researcher::*;
@@ -13,3 +19,9 @@ pub use study::*;
 pub use consent::*;
 pub use wallet::*;
 pub use privacy::*;
+pub use ethics::*;
+pub use team::*;
+pub use allocation::*;
+pub use dispute::*;
+pub use identity::*;
+pub use guardianship::*;
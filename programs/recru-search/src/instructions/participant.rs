@@ -1,20 +1,99 @@
 use anchor_lang::prelude::*;
 use crate::contexts::*;
+use crate::state::privacy::{pseudonym, ErasureTombstone};
+use crate::state::consent::StudyConsent;
+use crate::instructions::consent::ConsentRevoked;
+use crate::error::RecruSearchError;
 
-pub fn register_participant(ctx: Context<RegisterParticipant>, eligibility_proof: String) -> Result<()> {
+pub fn register_participant(ctx: Context<RegisterParticipant>, attributes_root: [u8; 32]) -> Result<()> {
     let participant = &mut ctx.accounts.participant;
-    participant.create(ctx.accounts.authority.key(), eligibility_proof)?;
+    participant.create(ctx.accounts.authority.key(), attributes_root, ctx.bumps.participant)?;
+    // An authority registering again after erasure keeps its old profile's reputation
+    let tombstone = ctx.accounts.tombstone.to_account_info();
+    if !tombstone.data_is_empty() {
+        require_keys_eq!(*tombstone.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+        participant.reputation = ErasureTombstone::try_deserialize(&mut &tombstone.try_borrow_data()?[..])?.reputation;
+    }
+    Ok(())
+}
+
+/// Replaces the participant's attribute commitment, e.g. after moving region
+pub fn commit_attributes(ctx: Context<CommitAttributes>, attributes_root: [u8; 32]) -> Result<()> {
+    let participant = &mut ctx.accounts.participant;
+    participant.attributes_root = attributes_root;
     Ok(())
 }
 
 pub fn update_participant_profile(ctx: Context<UpdateParticipantProfile>, interests: Vec<String>) -> Result<()> {
     let participant = &mut ctx.accounts.participant;
-    participant.profile.interests = interests;
+    participant.update_interests(interests)?;
     Ok(())
 }
 
 pub fn update_interests(ctx: Context<UpdateInterests>, interests: Vec<String>) -> Result<()> {
     let participant = &mut ctx.accounts.participant;
-    participant.profile.interests = interests;
+    participant.update_interests(interests)?;
+    Ok(())
+}
+
+/// Right to erasure: revokes the participant's study consents, passed as the remaining
+/// accounts, closes those and their profile and wallet back to them, closes any
+/// guardianship back to the guardian and leaves only a tombstone pseudonymised with `salt`. Data-access grants are not
+/// held on-chain (see `PrivacyManager`), so `ParticipantErased` is also the revocation
+/// notice for their key custodians. Closing reallocates the accounts to zero length,
+/// so none of the profile survives in account data.
+pub fn erase_participant<'info>(
+    ctx: Context<'_, '_, 'info, 'info, EraseParticipant<'info>>,
+    salt: [u8; 32],
+) -> Result<()> {
+    let participant = &mut ctx.accounts.participant;
+    let now = Clock::get()?.unix_timestamp;
+
+    for account in ctx.remaining_accounts {
+        let mut study_consent = Account::<StudyConsent>::try_from(account)?;
+        require_keys_eq!(study_consent.participant, participant.key(), RecruSearchError::NoActiveConsent);
+        if study_consent.is_active() {
+            study_consent.revoke(now)?;
+            participant.remove_consent()?;
+            emit!(ConsentRevoked {
+                participant: participant.key(),
+                study: study_consent.study,
+                timestamp: now,
+            });
+        }
+        study_consent.close(ctx.accounts.authority.to_account_info())?;
+    }
+    participant.require_erasable()?;
+
+    if let Some(guardian) = participant.guardian {
+        let guardianship = ctx.accounts.guardianship.as_ref().ok_or(RecruSearchError::GuardianshipRequired)?;
+        let recipient = ctx.accounts.guardian.as_ref().ok_or(RecruSearchError::GuardianshipRequired)?;
+        require_keys_eq!(recipient.key(), guardian, RecruSearchError::NotGuardianshipParty);
+        guardianship.close(recipient.to_account_info())?;
+    }
+
+    let tombstone = &mut ctx.accounts.tombstone;
+    tombstone.pseudonym = pseudonym(&participant.key(), &salt);
+    tombstone.erased_at = now;
+    tombstone.reputation = participant.reputation;
+    tombstone.bump = ctx.bumps.tombstone;
+
+    emit!(ParticipantErased {
+        participant: participant.key(),
+        tombstone: tombstone.key(),
+        pseudonym: tombstone.pseudonym.clone(),
+        wallet_closed: ctx.accounts.wallet.is_some(),
+        timestamp: now,
+    });
+
     Ok(())
-}
\ No newline at end of file
+}
+
+#[event]
+pub struct ParticipantErased {
+    pub participant: Pubkey,
+    pub tombstone: Pubkey,
+    pub pseudonym: String,
+    pub wallet_closed: bool,
+    pub timestamp: i64,
+}
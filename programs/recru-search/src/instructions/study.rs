@@ -1,14 +1,175 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 use crate::contexts::*;
 use crate::state::study::StudyType;
-use crate::state::admin::StudyStatus;
+use crate::state::admin::{StatusAuthority, StudyStatus};
+use crate::state::eligibility::{AttributeDisclosure, EligibilityCriteria};
+use crate::state::attestation::EligibilityAttestation;
+use crate::state::enrollment::{Enrollment, EnrollmentStatus, ExitReason};
+use crate::state::participant::Participant;
+use crate::state::study::{SessionSlot, Study, StudySchedule, MAX_SESSION_SLOTS};
+use crate::state::consent::{StudyConsent, MAX_CONSENT_VERSION_LEN};
+use crate::state::waitlist::Waitlist;
+use crate::state::quota::StratumQuota;
+use crate::state::dataset::{dataset_leaf, verify_dataset_proof};
+use crate::state::dispute::DisputeParty;
+use crate::state::reputation::ReputationEvent;
+use crate::instructions::consent::ConsentRevoked;
+use crate::instructions::dispute::DisputeOpened;
+use crate::error::RecruSearchError;
 
-pub fn join_study(ctx: Context<JoinStudy>) -> Result<()> {
+pub fn join_study(
+    ctx: Context<JoinStudy>,
+    disclosures: Vec<AttributeDisclosure>,
+    attestation: Option<EligibilityAttestation>,
+) -> Result<()> {
     let study = &mut ctx.accounts.study;
     let participant = &mut ctx.accounts.participant;
-    study.can_accept_participants()?;
+    // An expired or revoked approval stops recruitment without a status change
+    ctx.accounts.ethics_approval.require_valid(&ctx.accounts.admin)?;
+    study.criteria.check(participant, &disclosures)?;
+    let consented = study.requires_consent(ctx.accounts.admin.survey_consent_waiver);
+    if consented {
+        let study_consent = ctx.accounts.study_consent.as_ref().ok_or(RecruSearchError::ConsentRequired)?;
+        study_consent.require_current(&study.key(), study.consent_version.as_ref())?;
+    }
+    if study.requires_guardian_consent {
+        let guardianship = ctx.accounts.guardianship.as_ref().ok_or(RecruSearchError::GuardianConsentRequired)?;
+        require!(
+            participant.guardian == Some(guardianship.guardian),
+            RecruSearchError::GuardianConsentRequired
+        );
+        // Assent and guardian consent are given per study
+        ctx.accounts.study_consent.as_ref()
+            .ok_or(RecruSearchError::GuardianConsentRequired)?
+            .require_guardian_consent()?;
+    }
+
+    // Lapsed offers are reclaimed first; a live offer for this participant is what
+    // lets them take a slot held back from everyone else
+    match ctx.accounts.waitlist.as_mut() {
+        Some(waitlist) => {
+            refill_from_waitlist(study, waitlist)?;
+            if waitlist.take_offer(&participant.key()) {
+                study.release_reserved_slots(1)?;
+            }
+        }
+        None => require!(!study.waitlist_in_use(), RecruSearchError::WaitlistRequired),
+    }
+
+    match (attestation, &mut ctx.accounts.attestation_nonce) {
+        (Some(attestation), Some(nonce)) => {
+            require!(
+                ctx.accounts.admin.is_trusted_verifier(&attestation.verifier),
+                RecruSearchError::UntrustedVerifier
+            );
+            attestation.verify(&ctx.accounts.instructions, &participant.key(), &study.key())?;
+            nonce.verifier = attestation.verifier;
+            nonce.nonce = attestation.nonce;
+            nonce.participant = participant.key();
+            nonce.study = study.key();
+            nonce.used_at = Clock::get()?.unix_timestamp;
+        }
+        (Some(_), None) => return err!(RecruSearchError::InvalidAttestation),
+        (None, _) => require!(!study.criteria.requires_attestation, RecruSearchError::AttestationRequired),
+    }
+
+    study.require_funded_slot(ctx.accounts.vault.amount)?;
     study.add_participant()?;
+    let strata = study.fill_strata(&participant.attributes_root, &disclosures)?;
     participant.increment_active_studies()?;
+    let joined_at = Clock::get()?.unix_timestamp;
+
+    let (arm_commitment, pending_draw) = match ctx.accounts.allocation.as_mut() {
+        _ if !study.randomized => (None, None),
+        None => return err!(RecruSearchError::AllocationRequired),
+        Some(allocation) if allocation.blinded => {
+            let stratum = allocation.stratum_of(&study.quotas, &strata);
+            let commitment = allocation.take_commitment(stratum)?;
+            emit!(ArmCommitted {
+                study: study.key(),
+                participant: participant.key(),
+                commitment: commitment.commitment,
+                sequence: commitment.sequence,
+                stratum,
+                timestamp: joined_at,
+            });
+            (Some(commitment), None)
+        }
+        Some(allocation) => {
+            // Drawn later by `draw_arm`, once this slot's hash exists
+            let draw = allocation.queue_draw(Clock::get()?.slot)?;
+            emit!(ArmDrawQueued {
+                study: study.key(),
+                participant: participant.key(),
+                sequence: draw.sequence,
+                slot: draw.slot,
+                timestamp: joined_at,
+            });
+            (None, Some(draw))
+        }
+    };
+
+    ctx.accounts.enrollment.create(
+        study.key(),
+        participant.key(),
+        joined_at,
+        study.schedule.deadline_from(joined_at),
+        strata,
+        arm_commitment,
+        pending_draw,
+        ctx.bumps.enrollment,
+    )?;
+    ctx.accounts.enrollment.consented = consented;
+    Ok(())
+}
+
+/// Queues a participant for a study with no open slot
+pub fn join_waitlist(ctx: Context<JoinWaitlist>) -> Result<()> {
+    let study = &mut ctx.accounts.study;
+    let waitlist = &mut ctx.accounts.waitlist;
+    study.require_status(&[StudyStatus::Recruiting])?;
+    study.schedule.require_recruiting(Clock::get()?.unix_timestamp)?;
+    if waitlist.study == Pubkey::default() {
+        waitlist.study = study.key();
+        waitlist.bump = ctx.bumps.waitlist;
+    }
+
+    refill_from_waitlist(study, waitlist)?;
+    require!(!study.has_open_slot(), RecruSearchError::StudyNotFull);
+
+    let now = Clock::get()?.unix_timestamp;
+    let position = waitlist.enqueue(ctx.accounts.participant.key(), now)?;
+    study.add_to_waitlist()?;
+
+    emit!(WaitlistJoined {
+        study: study.key(),
+        participant: ctx.accounts.participant.key(),
+        position,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+/// Reclaims lapsed offers, then offers every unheld slot to the head of the queue
+pub(crate) fn refill_from_waitlist(study: &mut Account<Study>, waitlist: &mut Waitlist) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let expired = waitlist.expire_offers(now);
+    study.release_reserved_slots(expired)?;
+    if study.status != StudyStatus::Recruiting || study.schedule.require_recruiting(now).is_err() {
+        return Ok(());
+    }
+    while study.has_open_slot() {
+        let Some(offer) = waitlist.promote_next(now) else { break };
+        study.reserve_slot()?;
+        emit!(WaitlistPromoted {
+            study: study.key(),
+            participant: offer.participant,
+            offer_expires_at: offer.expires_at,
+            timestamp: now,
+        });
+    }
     Ok(())
 }
 
@@ -19,9 +180,13 @@ pub fn create_study(
     criteria_hash: String,
     reward_amount: u64,
     max_participants: u32,
+    study_type: StudyType,
 ) -> Result<()> {
     let study = &mut ctx.accounts.study;
-    let researcher = &ctx.accounts.researcher;
+    let researcher = &mut ctx.accounts.researcher;
+    // The counter is part of the study's seeds, so advance it for the next one
+    let index = researcher.studies_created;
+    researcher.increment_studies_created()?;
     study.create(
         researcher.key(),
         title,
@@ -29,24 +194,576 @@ pub fn create_study(
         criteria_hash,
         reward_amount,
         max_participants,
-        StudyType::Survey, // Or pass as argument if needed
+        study_type,
+        ctx.accounts.reward_mint.key(),
+        ctx.bumps.vault,
+        index,
+        ctx.bumps.study,
     )?;
+    ctx.accounts.team.create(study.key(), researcher.key(), ctx.bumps.team)?;
+
+    // Fund the escrow with one reward per participant slot
+    let escrow = study.total_escrow()?;
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.researcher_token_account.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            },
+        ),
+        escrow,
+    )?;
+
+    emit!(StudyFunded {
+        study: study.key(),
+        vault: ctx.accounts.vault.key(),
+        amount: escrow,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
     Ok(())
 }
 
-pub fn complete_study(ctx: Context<CompleteStudy>) -> Result<()> {
+/// Participant's claim that they finished; the researcher then has the study's
+/// confirmation window to confirm or reject it
+pub fn claim_completion(ctx: Context<ClaimCompletion>) -> Result<()> {
     let study = &mut ctx.accounts.study;
-    let participant = &mut ctx.accounts.participant;
-    let researcher = &mut ctx.accounts.researcher;
+    study.require_completable()?;
+    let enrollment = &mut ctx.accounts.enrollment;
+    let now = Clock::get()?.unix_timestamp;
+    enrollment.claim_completion(now)?;
+    study.add_pending_claim()?;
+
+    emit!(CompletionClaimed {
+        study: study.key(),
+        participant: enrollment.participant,
+        confirm_by: enrollment.confirmation_deadline(study.confirmation_window).unwrap_or_default(),
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+/// Researcher's confirmation of a claimed completion; pays the reward
+pub fn complete_study(ctx: Context<CompleteStudy>) -> Result<()> {
+    ctx.accounts.lead_researcher.update_total_participants(1)?;
+    ctx.accounts.lead_researcher.record_reputation(ReputationEvent::Completed)?;
+    let accounts = ctx.accounts;
+    settle_completion(
+        &mut accounts.study,
+        &mut accounts.participant,
+        &mut accounts.enrollment,
+        &accounts.vault,
+        &accounts.participant_token_account,
+        &accounts.token_program,
+    )
+}
+
+/// Confirms a claim the researcher left unanswered past the confirmation window
+pub fn finalize_completion(ctx: Context<FinalizeCompletion>) -> Result<()> {
+    let window = ctx.accounts.study.confirmation_window;
+    ctx.accounts.enrollment.require_confirmation_lapsed(Clock::get()?.unix_timestamp, window)?;
+    ctx.accounts.researcher.update_total_participants(1)?;
+    ctx.accounts.researcher.record_reputation(ReputationEvent::Completed)?;
+    let accounts = ctx.accounts;
+    settle_completion(
+        &mut accounts.study,
+        &mut accounts.participant,
+        &mut accounts.enrollment,
+        &accounts.vault,
+        &accounts.participant_token_account,
+        &accounts.token_program,
+    )
+}
+
+/// Rejects a claim within the window, opening a dispute that holds the reward in escrow
+pub fn reject_completion(ctx: Context<RejectCompletion>, reason_hash: [u8; 32]) -> Result<()> {
+    let study = &ctx.accounts.study;
+    let enrollment = &mut ctx.accounts.enrollment;
+    let now = Clock::get()?.unix_timestamp;
+    enrollment.reject_completion(now, study.confirmation_window)?;
+    ctx.accounts.participant.open_dispute()?;
+
+    let dispute = &mut ctx.accounts.dispute;
+    dispute.open(
+        study.key(),
+        enrollment.key(),
+        enrollment.participant,
+        DisputeParty::Researcher,
+        reason_hash,
+        study.reward_amount,
+        now,
+        ctx.bumps.dispute,
+    );
+
+    emit!(DisputeOpened {
+        dispute: dispute.key(),
+        study: study.key(),
+        participant: enrollment.participant,
+        opened_by: DisputeParty::Researcher,
+        amount: dispute.amount,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+/// Marks a claimed enrollment completed and pays its reward out of the vault
+fn settle_completion<'info>(
+    study: &mut Account<'info, Study>,
+    participant: &mut Account<'info, Participant>,
+    enrollment: &mut Account<'info, Enrollment>,
+    vault: &Account<'info, TokenAccount>,
+    participant_token_account: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+) -> Result<()> {
+    // Completions stay open after close so participants already enrolled get paid, and
+    // after cancellation for claims made before it
+    study.require_status(&[
+        StudyStatus::Recruiting,
+        StudyStatus::Active,
+        StudyStatus::Closed,
+        StudyStatus::Cancelled,
+    ])?;
+    let amount = study.reward_amount;
+    enrollment.complete(amount)?;
+    study.settle_pending_claim()?;
     study.complete_participant()?;
+    participant.decrement_active_studies()?;
     participant.increment_completed_studies()?;
-    researcher.update_total_participants(1)?;
+    participant.record_reputation(ReputationEvent::Completed)?;
+
+    let study_key = study.key();
+    let vault_seeds: &[&[u8]] = &[b"vault", study_key.as_ref(), &[study.vault_bump]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: vault.to_account_info(),
+                to: participant_token_account.to_account_info(),
+                authority: vault.to_account_info(),
+            },
+            &[vault_seeds],
+        ),
+        amount,
+    )?;
+
+    emit!(RewardPaid {
+        study: study_key,
+        participant: participant.key(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn withdraw_from_study(ctx: Context<WithdrawFromStudy>, reason: ExitReason) -> Result<()> {
+    require!(!reason.is_reserved(), RecruSearchError::ReservedExitReason);
+    let accounts = ctx.accounts;
+    end_enrollment(
+        &mut accounts.study,
+        &mut accounts.participant,
+        &mut accounts.enrollment,
+        &accounts.vault,
+        &accounts.participant_token_account,
+        &accounts.token_program,
+        accounts.waitlist.as_mut(),
+        accounts.study_consent.as_mut(),
+        EnrollmentStatus::Withdrawn,
+        reason,
+    )
+}
+
+pub fn remove_participant_from_study(ctx: Context<RemoveParticipantFromStudy>, reason: ExitReason) -> Result<()> {
+    require!(!reason.is_reserved(), RecruSearchError::ReservedExitReason);
+    let accounts = ctx.accounts;
+    end_enrollment(
+        &mut accounts.study,
+        &mut accounts.participant,
+        &mut accounts.enrollment,
+        &accounts.vault,
+        &accounts.participant_token_account,
+        &accounts.token_program,
+        accounts.waitlist.as_mut(),
+        accounts.study_consent.as_mut(),
+        EnrollmentStatus::Removed,
+        reason,
+    )
+}
+
+/// Shared exit path: frees the slot, pays any prorated share from the vault and
+/// revokes the participant's consent to this study. Consents to other studies stand.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn end_enrollment<'info>(
+    study: &mut Account<'info, Study>,
+    participant: &mut Account<'info, Participant>,
+    enrollment: &mut Enrollment,
+    vault: &Account<'info, TokenAccount>,
+    participant_token_account: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    waitlist: Option<&mut Account<'info, Waitlist>>,
+    study_consent: Option<&mut Account<'info, StudyConsent>>,
+    status: EnrollmentStatus,
+    reason: ExitReason,
+) -> Result<()> {
+    // A cancelled study may already have refunded its escrow, keeping back only what
+    // pre-cancellation claims and disputes are owed
+    let available = match study.status {
+        StudyStatus::Cancelled => vault.amount.saturating_sub(study.claimed_rewards()?),
+        _ => vault.amount,
+    };
+    let payout = study.exit_payout(enrollment.progress).min(available);
+    enrollment.end(status, reason, payout)?;
+    study.remove_participant()?;
+    study.release_strata(&enrollment.strata)?;
+    participant.decrement_active_studies()?;
+    // Leaving because guardian consent or assent was withdrawn is not abandonment
+    let consent_withdrawn = reason == ExitReason::ConsentWithdrawn && study.requires_guardian_consent;
+    if status == EnrollmentStatus::Withdrawn && !consent_withdrawn {
+        participant.record_reputation(ReputationEvent::Abandoned)?;
+    }
+    match waitlist {
+        Some(waitlist) => refill_from_waitlist(study, waitlist)?,
+        None => require!(!study.waitlist_in_use(), RecruSearchError::WaitlistRequired),
+    }
+
+    let study_key = study.key();
+    if payout > 0 {
+        let vault_seeds: &[&[u8]] = &[b"vault", study_key.as_ref(), &[study.vault_bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                Transfer {
+                    from: vault.to_account_info(),
+                    to: participant_token_account.to_account_info(),
+                    authority: vault.to_account_info(),
+                },
+                &[vault_seeds],
+            ),
+            payout,
+        )?;
+    }
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    revoke_exit_consent(study, participant, enrollment.consented, study_consent, timestamp)?;
+
+    emit!(EnrollmentEnded {
+        study: study_key,
+        participant: participant.key(),
+        status,
+        reason,
+        payout,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+/// Revokes the consent a departing participant gave to `study`. Enrollments that
+/// needed consent to join must hand it in.
+pub(crate) fn revoke_exit_consent<'info>(
+    study: &mut Account<'info, Study>,
+    participant: &mut Account<'info, Participant>,
+    consented: bool,
+    study_consent: Option<&mut Account<'info, StudyConsent>>,
+    timestamp: i64,
+) -> Result<()> {
+    require!(!consented || study_consent.is_some(), RecruSearchError::ConsentRequired);
+    if let Some(study_consent) = study_consent.filter(|consent| consent.is_active()) {
+        study_consent.revoke(timestamp)?;
+        participant.remove_consent()?;
+        study.decrement_consent()?;
+        emit!(ConsentRevoked {
+            participant: participant.key(),
+            study: study.key(),
+            timestamp,
+        });
+    }
+    Ok(())
+}
+
+/// Permissionless: expires the overdue enrollments passed as `(enrollment, participant,
+/// study consent)` triples in the remaining accounts, hands their slots to the waitlist
+/// and closes the study once its end date has passed. The study consent is the
+/// participant's `[b"study_consent", study, participant]` address, which only has to
+/// exist for enrollments that needed consent.
+pub fn crank_expire<'info>(ctx: Context<'_, '_, 'info, 'info, CrankExpire<'info>>) -> Result<()> {
+    let study = &mut ctx.accounts.study;
+    let now = Clock::get()?.unix_timestamp;
+    require!(ctx.remaining_accounts.len() % 3 == 0, RecruSearchError::InvalidAccountGroups);
+
+    for group in ctx.remaining_accounts.chunks(3) {
+        let mut enrollment = Account::<Enrollment>::try_from(&group[0])?;
+        let mut participant = Account::<Participant>::try_from(&group[1])?;
+        require_keys_eq!(enrollment.study, study.key(), RecruSearchError::NotEnrolled);
+        require_keys_eq!(enrollment.participant, participant.key(), RecruSearchError::NotEnrolled);
+        let mut study_consent = match group[2].data_is_empty() {
+            true => None,
+            false => Some(Account::<StudyConsent>::try_from(&group[2])?),
+        };
+        if let Some(study_consent) = &study_consent {
+            require_keys_eq!(study_consent.study, study.key(), RecruSearchError::ConsentRequired);
+            require_keys_eq!(study_consent.participant, participant.key(), RecruSearchError::ConsentRequired);
+        }
+
+        enrollment.expire(now)?;
+        study.remove_participant()?;
+        study.release_strata(&enrollment.strata)?;
+        participant.decrement_active_studies()?;
+        participant.record_reputation(ReputationEvent::NoShow)?;
+        revoke_exit_consent(study, &mut participant, enrollment.consented, study_consent.as_mut(), now)?;
+        enrollment.exit(&crate::ID)?;
+        participant.exit(&crate::ID)?;
+        if let Some(study_consent) = &study_consent {
+            study_consent.exit(&crate::ID)?;
+        }
+
+        emit!(EnrollmentExpired {
+            study: study.key(),
+            participant: participant.key(),
+            deadline: enrollment.deadline.unwrap_or_default(),
+            timestamp: now,
+        });
+    }
+
+    if let Some(from) = study.close_if_ended(now) {
+        emit!(StudyStatusChanged {
+            study: study.key(),
+            from,
+            to: StudyStatus::Closed,
+            authority: ctx.accounts.cranker.key(),
+            timestamp: now,
+        });
+    }
+
+    match ctx.accounts.waitlist.as_mut() {
+        Some(waitlist) => refill_from_waitlist(study, waitlist)?,
+        None => require!(!study.waitlist_in_use(), RecruSearchError::WaitlistRequired),
+    }
+
+    Ok(())
+}
+
+/// Replaces the study's demographic quotas; fixed once recruitment opens
+pub fn set_quotas(ctx: Context<ConfigureStudy>, quotas: Vec<StratumQuota>) -> Result<()> {
+    let study = &mut ctx.accounts.study;
+    study.require_draft()?;
+    study.set_quotas(quotas)
+}
+
+pub fn set_study_schedule(ctx: Context<ConfigureStudy>, schedule: StudySchedule) -> Result<()> {
+    let study = &mut ctx.accounts.study;
+    study.require_draft()?;
+    schedule.validate(Clock::get()?.unix_timestamp)?;
+    study.schedule = schedule;
+    Ok(())
+}
+
+/// Publishes a new revision of the written-consent document. New consents must
+/// reference it; revisions are allowed until the study closes.
+pub fn publish_consent_version(
+    ctx: Context<ConfigureStudy>,
+    version: String,
+    document_hash: [u8; 32],
+) -> Result<()> {
+    let study = &mut ctx.accounts.study;
+    study.require_status(&[StudyStatus::Draft, StudyStatus::Recruiting, StudyStatus::Active])?;
+    require!(
+        !version.is_empty()
+            && version.len() <= MAX_CONSENT_VERSION_LEN
+            && study.consent_version.as_ref() != Some(&version),
+        RecruSearchError::InvalidConsentVersion
+    );
+    study.consent_version = Some(version.clone());
+    study.consent_document_hash = document_hash;
+
+    emit!(ConsentVersionPublished {
+        study: study.key(),
+        version,
+        document_hash,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn set_session_slots(ctx: Context<ConfigureStudy>, slots: Vec<SessionSlot>) -> Result<()> {
+    let study = &mut ctx.accounts.study;
+    study.require_draft()?;
+    let now = Clock::get()?.unix_timestamp;
+    require!(slots.len() <= MAX_SESSION_SLOTS, RecruSearchError::InputTooLong);
+    require!(
+        slots.iter().all(|s| s.starts_at > now && s.duration_minutes > 0 && s.capacity > 0),
+        RecruSearchError::InvalidSessionSlot
+    );
+    study.session_slots = slots;
+    Ok(())
+}
+
+pub fn set_exit_policy(ctx: Context<ConfigureStudy>, prorate_on_exit: bool) -> Result<()> {
+    let study = &mut ctx.accounts.study;
+    study.require_draft()?;
+    study.prorate_on_exit = prorate_on_exit;
+    Ok(())
+}
+
+pub fn set_guardian_requirement(ctx: Context<ConfigureStudy>, required: bool) -> Result<()> {
+    let study = &mut ctx.accounts.study;
+    study.require_draft()?;
+    study.requires_guardian_consent = required;
+    Ok(())
+}
+
+pub fn set_confirmation_window(ctx: Context<ConfigureStudy>, window: i64) -> Result<()> {
+    let study = &mut ctx.accounts.study;
+    study.require_draft()?;
+    require!(window > 0, RecruSearchError::InvalidSchedule);
+    study.confirmation_window = window;
+    Ok(())
+}
+
+pub fn set_eligibility_criteria(ctx: Context<ConfigureStudy>, criteria: EligibilityCriteria) -> Result<()> {
+    let study = &mut ctx.accounts.study;
+    study.require_draft()?;
+    criteria.validate()?;
+    study.criteria = criteria;
+    Ok(())
+}
+
+pub fn transition_study(ctx: Context<TransitionStudy>, status: StudyStatus) -> Result<()> {
+    let study = &mut ctx.accounts.study;
+    if status == StudyStatus::Recruiting {
+        require!(ctx.accounts.researcher.is_verified, RecruSearchError::ResearcherNotVerified);
+        study.check_type_requirements()?;
+        ctx.accounts.ethics_approval.as_ref()
+            .ok_or(RecruSearchError::MissingEthicsApproval)?
+            .require_valid(&ctx.accounts.admin)?;
+    }
+    let from = study.transition(status, StatusAuthority::Researcher)?;
+
+    emit!(StudyStatusChanged {
+        study: study.key(),
+        from,
+        to: status,
+        authority: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn refund_escrow(ctx: Context<RefundEscrow>) -> Result<()> {
+    let study = &ctx.accounts.study;
+    // A closed study keeps what it still owes enrolled participants; a cancelled one only
+    // what was claimed or disputed before the cancellation
+    let reserved = match study.status {
+        StudyStatus::Closed | StudyStatus::Archived => study.outstanding_rewards()?,
+        StudyStatus::Cancelled => study.claimed_rewards()?,
+        _ => return err!(RecruSearchError::InvalidStudyPhase),
+    };
+    let refund = ctx.accounts.vault.amount.saturating_sub(reserved);
+    if refund > 0 {
+        let study_key = study.key();
+        let vault_seeds: &[&[u8]] = &[b"vault", study_key.as_ref(), &[study.vault_bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.researcher_token_account.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                &[vault_seeds],
+            ),
+            refund,
+        )?;
+    }
+
+    emit!(EscrowRefunded {
+        study: study.key(),
+        amount: refund,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
     Ok(())
 }
 
 pub fn track_study_progress(ctx: Context<TrackStudyProgress>, progress: u8) -> Result<()> {
+    ctx.accounts.study.require_completable()?;
+    let enrollment = &mut ctx.accounts.enrollment;
+    enrollment.update_progress(progress)?;
+
+    emit!(StudyProgressUpdated {
+        study: enrollment.study,
+        participant: enrollment.participant,
+        progress,
+        updated_by: ctx.accounts.authority.key(),
+        timestamp: enrollment.last_update,
+    });
+
+    Ok(())
+}
+
+/// Commits the participant's off-chain response to their enrollment
+pub fn submit_response(ctx: Context<SubmitResponse>, response_hash: [u8; 32], uri: String) -> Result<()> {
     let study = &mut ctx.accounts.study;
-    study.update_progress(progress)?;
+    study.require_in_progress()?;
+    let enrollment = &mut ctx.accounts.enrollment;
+    if enrollment.record_response(response_hash, uri)? {
+        study.record_response()?;
+    }
+
+    emit!(ResponseSubmitted {
+        study: study.key(),
+        participant: enrollment.participant,
+        response_hash,
+        timestamp: enrollment.responded_at.unwrap_or_default(),
+    });
+
+    Ok(())
+}
+
+/// Anchors the Merkle root of all submissions once the study has closed
+pub fn anchor_dataset(ctx: Context<ConfigureStudy>, root: [u8; 32], entries: u32) -> Result<()> {
+    let study = &mut ctx.accounts.study;
+    study.anchor_dataset(root, entries)?;
+
+    emit!(DatasetAnchored {
+        study: study.key(),
+        root,
+        entries,
+        version: study.dataset_version,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Proves an enrollment's submission is in the anchored dataset, either retained
+/// or marked removed
+pub fn verify_dataset_entry(
+    ctx: Context<VerifyDatasetEntry>,
+    retained: bool,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let study = &ctx.accounts.study;
+    let enrollment = &ctx.accounts.enrollment;
+    let root = study.dataset_root.ok_or(RecruSearchError::DatasetNotAnchored)?;
+    let response_hash = enrollment.response_hash.ok_or(RecruSearchError::NoResponseSubmitted)?;
+    let leaf = dataset_leaf(&enrollment.key(), &response_hash, retained);
+    require!(verify_dataset_proof(leaf, &proof, &root), RecruSearchError::NotInDataset);
+
+    emit!(DatasetEntryVerified {
+        study: study.key(),
+        participant: enrollment.participant,
+        retained,
+        version: study.dataset_version,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
     Ok(())
 }
 
@@ -56,12 +773,178 @@ pub fn submit_study_feedback(
     feedback: Option<String>,
 ) -> Result<()> {
     let study = &mut ctx.accounts.study;
-    study.submit_feedback(rating, feedback)?;
+    study.require_status(&[StudyStatus::Recruiting, StudyStatus::Active, StudyStatus::Closed])?;
+    ctx.accounts.feedback.create(
+        study.key(),
+        ctx.accounts.participant.key(),
+        rating,
+        feedback,
+        ctx.bumps.feedback,
+    )?;
+    study.record_rating(rating)?;
+    ctx.accounts.researcher.record_reputation(ReputationEvent::RatingReceived(rating))?;
+
+    emit!(FeedbackSubmitted {
+        study: study.key(),
+        participant: ctx.accounts.participant.key(),
+        feedback: ctx.accounts.feedback.key(),
+        rating,
+        average_rating: study.analytics.average_rating,
+        rating_count: study.analytics.rating_count,
+        timestamp: ctx.accounts.feedback.submitted_at,
+    });
+
     Ok(())
 }
 
-pub fn update_study_status(ctx: Context<UpdateStudyStatus>, status: StudyStatus) -> Result<()> {
-    let study = &mut ctx.accounts.study;
-    study.status = status;
-    Ok(())
-}
\ No newline at end of file
+#[event]
+pub struct StudyFunded {
+    pub study: Pubkey,
+    pub vault: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardPaid {
+    pub study: Pubkey,
+    pub participant: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CompletionClaimed {
+    pub study: Pubkey,
+    pub participant: Pubkey,
+    /// Auto-confirms if the researcher has not answered by then
+    pub confirm_by: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EnrollmentEnded {
+    pub study: Pubkey,
+    pub participant: Pubkey,
+    pub status: EnrollmentStatus,
+    pub reason: ExitReason,
+    pub payout: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConsentVersionPublished {
+    pub study: Pubkey,
+    pub version: String,
+    pub document_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EnrollmentExpired {
+    pub study: Pubkey,
+    pub participant: Pubkey,
+    pub deadline: i64,
+    pub timestamp: i64,
+}
+
+/// A randomized join; the arm is drawn from the join slot's hash once it exists
+#[event]
+pub struct ArmDrawQueued {
+    pub study: Pubkey,
+    pub participant: Pubkey,
+    pub sequence: u32,
+    pub slot: u64,
+    pub timestamp: i64,
+}
+
+/// A blinded assignment; the arm stays hidden until the commitment is opened
+#[event]
+pub struct ArmCommitted {
+    pub study: Pubkey,
+    pub participant: Pubkey,
+    pub commitment: [u8; 32],
+    pub sequence: u32,
+    pub stratum: Option<u8>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WaitlistJoined {
+    pub study: Pubkey,
+    pub participant: Pubkey,
+    /// 1-based position in the queue
+    pub position: u32,
+    pub timestamp: i64,
+}
+
+/// A freed slot is being held for `participant` until `offer_expires_at`
+#[event]
+pub struct WaitlistPromoted {
+    pub study: Pubkey,
+    pub participant: Pubkey,
+    pub offer_expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StudyProgressUpdated {
+    pub study: Pubkey,
+    pub participant: Pubkey,
+    pub progress: u8,
+    pub updated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeedbackSubmitted {
+    pub study: Pubkey,
+    pub participant: Pubkey,
+    pub feedback: Pubkey,
+    pub rating: u8,
+    pub average_rating: u32,
+    pub rating_count: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StudyStatusChanged {
+    pub study: Pubkey,
+    pub from: StudyStatus,
+    pub to: StudyStatus,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EscrowRefunded {
+    pub study: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ResponseSubmitted {
+    pub study: Pubkey,
+    pub participant: Pubkey,
+    pub response_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DatasetAnchored {
+    pub study: Pubkey,
+    pub root: [u8; 32],
+    pub entries: u32,
+    pub version: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DatasetEntryVerified {
+    pub study: Pubkey,
+    pub participant: Pubkey,
+    pub retained: bool,
+    pub version: u32,
+    pub timestamp: i64,
+}
@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use crate::contexts::ManageStudyTeam;
+use crate::state::team::TeamRole;
+
+pub fn add_team_member(ctx: Context<ManageStudyTeam>, role: TeamRole) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let member = ctx.accounts.member.key();
+    ctx.accounts.team.add_member(member, role, now)?;
+
+    emit!(TeamMemberAdded {
+        study: ctx.accounts.study.key(),
+        researcher: member,
+        role,
+        added_by: ctx.accounts.researcher.key(),
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+pub fn remove_team_member(ctx: Context<ManageStudyTeam>) -> Result<()> {
+    let member = ctx.accounts.member.key();
+    let role = ctx.accounts.team.remove_member(&member)?;
+
+    emit!(TeamMemberRemoved {
+        study: ctx.accounts.study.key(),
+        researcher: member,
+        role,
+        removed_by: ctx.accounts.researcher.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct TeamMemberAdded {
+    pub study: Pubkey,
+    pub researcher: Pubkey,
+    pub role: TeamRole,
+    pub added_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TeamMemberRemoved {
+    pub study: Pubkey,
+    pub researcher: Pubkey,
+    pub role: TeamRole,
+    pub removed_by: Pubkey,
+    pub timestamp: i64,
+}
@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount};
-use crate::state::wallet::{ParticipantWallet, get_wallet_size};
+use crate::state::wallet::ParticipantWallet;
 use crate::error::RecruSearchError;
 
 /// Context for initializing a Phantom wallet
@@ -14,7 +14,7 @@ pub struct InitializePhantomWallet<'info> {
     #[account(
         init,
         payer = payer,
-        space = get_wallet_size(),
+        space = 8 + ParticipantWallet::INIT_SPACE,
         seeds = [b"wallet", participant.key().as_ref()],
         bump
     )]
@@ -62,22 +62,11 @@ pub struct ReceiveReward<'info> {
 /// Initializes a wallet for the participant
 pub fn initialize_phantom_wallet(ctx: Context<InitializePhantomWallet>) -> Result<()> {
     let wallet = &mut ctx.accounts.wallet;
-    // Initialize the wallet using the ParticipantWallet's new method
-    let wallet_data = ParticipantWallet::new(
+    wallet.set_inner(ParticipantWallet::new(
         ctx.accounts.participant.key(),
         ctx.accounts.phantom_public_key.key(),
         ctx.bumps.wallet,
-    )?;
-    
-    // Copy fields from the created wallet data to the account
-    wallet.participant = wallet_data.participant;
-    wallet.phantom_public_key = wallet_data.phantom_public_key;
-    wallet.bump = wallet_data.bump;
-    wallet.is_active = wallet_data.is_active;
-    wallet.total_rewards = wallet_data.total_rewards;
-    wallet.last_activity = wallet_data.last_activity;
-    wallet.last_reward_at = wallet_data.last_reward_at;
-    wallet.metadata_uri = wallet_data.metadata_uri;
+    )?);
     
     // Emit event
     emit!(WalletInitialized {
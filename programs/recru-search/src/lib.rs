@@ -12,14 +12,22 @@ use crate::contexts::*;
 use crate::instructions::*;
 //use crate::contexts::{InitializePrivacyManager, GrantDataAccess, RevokeDataAccess};
 use crate::state::admin::{StudyStatus, ParticipantAction};
+use crate::state::eligibility::{AttributeDisclosure, EligibilityCriteria};
+use crate::state::attestation::EligibilityAttestation;
+use crate::state::enrollment::ExitReason;
+use crate::state::study::{SessionSlot, StudySchedule, StudyType};
+use crate::state::team::TeamRole;
+use crate::state::quota::StratumQuota;
+use crate::state::allocation::{RandomizationMethod, StudyArm};
+use crate::state::dispute::DisputeRuling;
 
 declare_id!("BkXcFAo2TFkXRm9WsKUxikgNYvvR3Pm3yS9xLdqaeJoo");
 
 #[program]
 pub mod recru_search {
     use super::*;
-    pub fn initialize(_ctx: Context<Initialize>) -> Result<()> {
-        Ok(())
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        instructions::admin::initialize(ctx)
     }
 
     pub fn initialize_psypoints(_ctx: Context<InitializePsyPoints>) -> Result<()> {
@@ -45,25 +53,191 @@ pub mod recru_search {
         criteria_hash: String,
         reward_amount: u64,
         max_participants: u32,
+        study_type: StudyType,
     ) -> Result<()> {
-        instructions::study::create_study(ctx, title, description, criteria_hash, reward_amount, max_participants)
+        instructions::study::create_study(
+            ctx,
+            title,
+            description,
+            criteria_hash,
+            reward_amount,
+            max_participants,
+            study_type,
+        )
     }
 
     pub fn register_participant(
         ctx: Context<RegisterParticipant>,
-        eligibility_proof: String,
+        attributes_root: [u8; 32],
+    ) -> Result<()> {
+        instructions::participant::register_participant(ctx, attributes_root)
+    }
+
+    pub fn commit_attributes(ctx: Context<CommitAttributes>, attributes_root: [u8; 32]) -> Result<()> {
+        instructions::participant::commit_attributes(ctx, attributes_root)
+    }
+
+    pub fn join_study(
+        ctx: Context<JoinStudy>,
+        disclosures: Vec<AttributeDisclosure>,
+        attestation: Option<EligibilityAttestation>,
     ) -> Result<()> {
-        instructions::participant::register_participant(ctx, eligibility_proof)
+        instructions::study::join_study(ctx, disclosures, attestation)
+    }
+
+    pub fn join_waitlist(ctx: Context<JoinWaitlist>) -> Result<()> {
+        instructions::study::join_waitlist(ctx)
     }
 
-    pub fn join_study(ctx: Context<JoinStudy>) -> Result<()> {
-        instructions::study::join_study(ctx)
+    pub fn claim_completion(ctx: Context<ClaimCompletion>) -> Result<()> {
+        instructions::study::claim_completion(ctx)
     }
 
     pub fn complete_study(ctx: Context<CompleteStudy>) -> Result<()> {
         instructions::study::complete_study(ctx)
     }
 
+    pub fn finalize_completion(ctx: Context<FinalizeCompletion>) -> Result<()> {
+        instructions::study::finalize_completion(ctx)
+    }
+
+    pub fn reject_completion(ctx: Context<RejectCompletion>, reason_hash: [u8; 32]) -> Result<()> {
+        instructions::study::reject_completion(ctx, reason_hash)
+    }
+
+    pub fn open_dispute(ctx: Context<OpenDispute>, reason_hash: [u8; 32]) -> Result<()> {
+        instructions::dispute::open_dispute(ctx, reason_hash)
+    }
+
+    pub fn submit_dispute_evidence(ctx: Context<SubmitDisputeEvidence>, evidence_hash: [u8; 32]) -> Result<()> {
+        instructions::dispute::submit_dispute_evidence(ctx, evidence_hash)
+    }
+
+    pub fn resolve_dispute(ctx: Context<ResolveDispute>, ruling: DisputeRuling) -> Result<()> {
+        instructions::dispute::resolve_dispute(ctx, ruling)
+    }
+
+    pub fn withdraw_from_study(ctx: Context<WithdrawFromStudy>, reason: ExitReason) -> Result<()> {
+        instructions::study::withdraw_from_study(ctx, reason)
+    }
+
+    pub fn remove_participant_from_study(
+        ctx: Context<RemoveParticipantFromStudy>,
+        reason: ExitReason,
+    ) -> Result<()> {
+        instructions::study::remove_participant_from_study(ctx, reason)
+    }
+
+    pub fn set_study_schedule(ctx: Context<ConfigureStudy>, schedule: StudySchedule) -> Result<()> {
+        instructions::study::set_study_schedule(ctx, schedule)
+    }
+
+    pub fn configure_randomization(
+        ctx: Context<ConfigureRandomization>,
+        method: RandomizationMethod,
+        blinded: bool,
+        arms: Vec<StudyArm>,
+        block_multiple: u8,
+        seed_commitment: [u8; 32],
+    ) -> Result<()> {
+        instructions::allocation::configure_randomization(
+            ctx,
+            method,
+            blinded,
+            arms,
+            block_multiple,
+            seed_commitment,
+        )
+    }
+
+    pub fn reveal_allocation_seed(ctx: Context<RevealAllocationSeed>, seed: [u8; 32]) -> Result<()> {
+        instructions::allocation::reveal_allocation_seed(ctx, seed)
+    }
+
+    pub fn draw_arm(ctx: Context<DrawArm>) -> Result<()> {
+        instructions::allocation::draw_arm(ctx)
+    }
+
+    pub fn commit_allocation_list(
+        ctx: Context<PostArmCommitments>,
+        stratum: Option<u8>,
+        list_head: [u8; 32],
+    ) -> Result<()> {
+        instructions::allocation::commit_allocation_list(ctx, stratum, list_head)
+    }
+
+    pub fn post_arm_commitments(
+        ctx: Context<PostArmCommitments>,
+        stratum: Option<u8>,
+        commitments: Vec<[u8; 32]>,
+        rest: [u8; 32],
+    ) -> Result<()> {
+        instructions::allocation::post_arm_commitments(ctx, stratum, commitments, rest)
+    }
+
+    pub fn reveal_allocations<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RevealAllocations<'info>>,
+        key: [u8; 32],
+    ) -> Result<()> {
+        instructions::allocation::reveal_allocations(ctx, key)
+    }
+
+    pub fn emergency_unblind(
+        ctx: Context<EmergencyUnblind>,
+        arm: u8,
+        salt: [u8; 32],
+        reason: String,
+    ) -> Result<()> {
+        instructions::allocation::emergency_unblind(ctx, arm, salt, reason)
+    }
+
+    pub fn set_quotas(ctx: Context<ConfigureStudy>, quotas: Vec<StratumQuota>) -> Result<()> {
+        instructions::study::set_quotas(ctx, quotas)
+    }
+
+    pub fn crank_expire<'info>(ctx: Context<'_, '_, 'info, 'info, CrankExpire<'info>>) -> Result<()> {
+        instructions::study::crank_expire(ctx)
+    }
+
+    pub fn publish_consent_version(
+        ctx: Context<ConfigureStudy>,
+        version: String,
+        document_hash: [u8; 32],
+    ) -> Result<()> {
+        instructions::study::publish_consent_version(ctx, version, document_hash)
+    }
+
+    pub fn set_session_slots(ctx: Context<ConfigureStudy>, slots: Vec<SessionSlot>) -> Result<()> {
+        instructions::study::set_session_slots(ctx, slots)
+    }
+
+    pub fn set_exit_policy(ctx: Context<ConfigureStudy>, prorate_on_exit: bool) -> Result<()> {
+        instructions::study::set_exit_policy(ctx, prorate_on_exit)
+    }
+
+    pub fn set_guardian_requirement(ctx: Context<ConfigureStudy>, required: bool) -> Result<()> {
+        instructions::study::set_guardian_requirement(ctx, required)
+    }
+
+    pub fn set_confirmation_window(ctx: Context<ConfigureStudy>, window: i64) -> Result<()> {
+        instructions::study::set_confirmation_window(ctx, window)
+    }
+
+    pub fn transition_study(ctx: Context<TransitionStudy>, status: StudyStatus) -> Result<()> {
+        instructions::study::transition_study(ctx, status)
+    }
+
+    pub fn set_eligibility_criteria(
+        ctx: Context<ConfigureStudy>,
+        criteria: EligibilityCriteria,
+    ) -> Result<()> {
+        instructions::study::set_eligibility_criteria(ctx, criteria)
+    }
+
+    pub fn refund_escrow(ctx: Context<RefundEscrow>) -> Result<()> {
+        instructions::study::refund_escrow(ctx)
+    }
+
     pub fn verify_researcher(ctx: Context<ReviewResearcherVerification>) -> Result<()> {
         instructions::admin::verify_researcher(ctx)
     }
@@ -76,8 +250,9 @@ pub mod recru_search {
         ctx: Context<IssueConsentNFT>,
         consent_version: String,
         consent_hash: String,
+        disclosures: Vec<AttributeDisclosure>,
     ) -> Result<()> {
-        instructions::consent::issue_consent_nft(ctx, consent_version, consent_hash)
+        instructions::consent::issue_consent_nft(ctx, consent_version, consent_hash, disclosures)
     }
 
     pub fn revoke_consent(ctx: Context<RevokeConsent>) -> Result<()> {
@@ -98,6 +273,22 @@ pub mod recru_search {
         instructions::study::track_study_progress(ctx, progress)
     }
 
+    pub fn submit_response(ctx: Context<SubmitResponse>, response_hash: [u8; 32], uri: String) -> Result<()> {
+        instructions::study::submit_response(ctx, response_hash, uri)
+    }
+
+    pub fn anchor_dataset(ctx: Context<ConfigureStudy>, root: [u8; 32], entries: u32) -> Result<()> {
+        instructions::study::anchor_dataset(ctx, root, entries)
+    }
+
+    pub fn verify_dataset_entry(
+        ctx: Context<VerifyDatasetEntry>,
+        retained: bool,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::study::verify_dataset_entry(ctx, retained, proof)
+    }
+
     pub fn submit_study_feedback(
         ctx: Context<SubmitStudyFeedback>,
         rating: u8,
@@ -113,6 +304,33 @@ pub mod recru_search {
         instructions::participant::update_interests(ctx, interests)
     }
 
+    pub fn erase_participant<'info>(
+        ctx: Context<'_, '_, 'info, 'info, EraseParticipant<'info>>,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        instructions::participant::erase_participant(ctx, salt)
+    }
+
+    pub fn attest_identity(ctx: Context<AttestIdentity>, identity_hash: [u8; 32], attester: Pubkey) -> Result<()> {
+        instructions::identity::attest_identity(ctx, identity_hash, attester)
+    }
+
+    pub fn revoke_identity(ctx: Context<RevokeIdentity>) -> Result<()> {
+        instructions::identity::revoke_identity(ctx)
+    }
+
+    pub fn establish_guardianship(ctx: Context<EstablishGuardianship>) -> Result<()> {
+        instructions::guardianship::establish_guardianship(ctx)
+    }
+
+    pub fn record_assent(ctx: Context<RecordAssent>) -> Result<()> {
+        instructions::guardianship::record_assent(ctx)
+    }
+
+    pub fn withdraw_guardian_consent(ctx: Context<WithdrawGuardianConsent>) -> Result<()> {
+        instructions::guardianship::withdraw_guardian_consent(ctx)
+    }
+
     pub fn initialize_phantom_wallet(ctx: Context<InitializePhantomWallet>) -> Result<()> {
         instructions::wallet::initialize_phantom_wallet(ctx)
     }
@@ -132,6 +350,70 @@ pub mod recru_search {
         instructions::admin::initialize_admin(ctx)
     }
 
+    pub fn add_trusted_verifier(ctx: Context<ManageTrustedVerifiers>, verifier: Pubkey) -> Result<()> {
+        instructions::admin::add_trusted_verifier(ctx, verifier)
+    }
+
+    pub fn set_survey_consent_waiver(ctx: Context<UpdatePlatformConfig>, waived: bool) -> Result<()> {
+        instructions::admin::set_survey_consent_waiver(ctx, waived)
+    }
+
+    pub fn add_review_board(ctx: Context<UpdatePlatformConfig>, board: Pubkey) -> Result<()> {
+        instructions::admin::add_review_board(ctx, board)
+    }
+
+    pub fn remove_review_board(ctx: Context<UpdatePlatformConfig>, board: Pubkey) -> Result<()> {
+        instructions::admin::remove_review_board(ctx, board)
+    }
+
+    pub fn add_arbitrator(ctx: Context<UpdatePlatformConfig>, arbitrator: Pubkey) -> Result<()> {
+        instructions::admin::add_arbitrator(ctx, arbitrator)
+    }
+
+    pub fn remove_arbitrator(ctx: Context<UpdatePlatformConfig>, arbitrator: Pubkey) -> Result<()> {
+        instructions::admin::remove_arbitrator(ctx, arbitrator)
+    }
+
+    pub fn add_identity_attester(ctx: Context<UpdatePlatformConfig>, attester: Pubkey) -> Result<()> {
+        instructions::admin::add_identity_attester(ctx, attester)
+    }
+
+    pub fn remove_identity_attester(ctx: Context<UpdatePlatformConfig>, attester: Pubkey) -> Result<()> {
+        instructions::admin::remove_identity_attester(ctx, attester)
+    }
+
+    pub fn grant_ethics_approval(
+        ctx: Context<GrantEthicsApproval>,
+        protocol_number_hash: [u8; 32],
+        approval_document_hash: [u8; 32],
+        valid_from: i64,
+        valid_until: i64,
+    ) -> Result<()> {
+        instructions::ethics::grant_ethics_approval(
+            ctx,
+            protocol_number_hash,
+            approval_document_hash,
+            valid_from,
+            valid_until,
+        )
+    }
+
+    pub fn revoke_ethics_approval(ctx: Context<RevokeEthicsApproval>) -> Result<()> {
+        instructions::ethics::revoke_ethics_approval(ctx)
+    }
+
+    pub fn add_team_member(ctx: Context<ManageStudyTeam>, role: TeamRole) -> Result<()> {
+        instructions::team::add_team_member(ctx, role)
+    }
+
+    pub fn remove_team_member(ctx: Context<ManageStudyTeam>) -> Result<()> {
+        instructions::team::remove_team_member(ctx)
+    }
+
+    pub fn remove_trusted_verifier(ctx: Context<ManageTrustedVerifiers>, verifier: Pubkey) -> Result<()> {
+        instructions::admin::remove_trusted_verifier(ctx, verifier)
+    }
+
     pub fn reject_researcher(ctx: Context<ReviewResearcherVerification>) -> Result<()> {
         instructions::admin::reject_researcher(ctx)
     }
@@ -1,33 +1,47 @@
 use anchor_lang::prelude::*;
+use crate::state::study::MAX_TITLE_LEN;
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub const MAX_STUDY_CATEGORIES: usize = 10;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
 pub struct AdminDashboard {
     pub total_studies: u32,
     pub active_studies: u32,
     pub total_participants: u32,
     pub total_researchers: u32,
     pub total_rewards_distributed: u64,
+    #[max_len(MAX_STUDY_CATEGORIES)]
     pub study_categories: Vec<CategoryStats>,
     pub platform_metrics: PlatformMetrics,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
 pub struct CategoryStats {
+    #[max_len(MAX_TITLE_LEN)]
     pub category: String,
     pub study_count: u32,
     pub participant_count: u32,
-    pub average_rating: f32,
+    /// Scaled like `StudyAnalytics::average_rating`
+    pub average_rating: u32,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
 pub struct PlatformMetrics {
-    pub average_completion_rate: f32,
+    /// Basis points, like `StudyAnalytics::completion_rate`
+    pub average_completion_rate: u32,
     pub average_study_duration: i64,
     pub participant_retention_rate: f32,
     pub researcher_satisfaction_rate: f32,
 }
 
+pub const MAX_TRUSTED_VERIFIERS: usize = 10;
+pub const MAX_REVIEW_BOARDS: usize = 10;
+pub const MAX_ARBITRATORS: usize = 10;
+pub const MAX_IDENTITY_ATTESTERS: usize = 10;
+
+/// Platform configuration. Seeds: `[b"admin"]`.
 #[account]
+#[derive(InitSpace)]
 pub struct Admin {
     pub authority: Pubkey,
     pub dashboard: AdminDashboard,
@@ -36,6 +50,21 @@ pub struct Admin {
     pub last_updated: i64,
     pub study_status: StudyStatus,
     pub participant_action: ParticipantAction,
+    /// Keys of off-chain screening services whose attestations join_study accepts
+    #[max_len(MAX_TRUSTED_VERIFIERS)]
+    pub trusted_verifiers: Vec<Pubkey>,
+    /// Ethics/IRB board keys whose approvals let studies recruit
+    #[max_len(MAX_REVIEW_BOARDS)]
+    pub review_boards: Vec<Pubkey>,
+    /// Keys besides the admin authority that may resolve disputes
+    #[max_len(MAX_ARBITRATORS)]
+    pub arbitrators: Vec<Pubkey>,
+    /// Services trusted to attest that a participant is a unique person
+    #[max_len(MAX_IDENTITY_ATTESTERS)]
+    pub identity_attesters: Vec<Pubkey>,
+    /// Lets Survey studies enroll participants without a consent NFT
+    pub survey_consent_waiver: bool,
+    pub bump: u8,
 }
 
 impl Admin {
@@ -43,7 +72,96 @@ impl Admin {
         &self.authority == authority
     }
     
-    pub fn new(authority: Pubkey) -> Self {
+    pub fn is_trusted_verifier(&self, verifier: &Pubkey) -> bool {
+        self.trusted_verifiers.contains(verifier)
+    }
+
+    pub fn add_trusted_verifier(&mut self, verifier: Pubkey) -> Result<()> {
+        require!(!self.is_trusted_verifier(&verifier), AdminError::AlreadyVerified);
+        require!(
+            self.trusted_verifiers.len() < MAX_TRUSTED_VERIFIERS,
+            crate::error::RecruSearchError::TooManyVerifiers
+        );
+        self.trusted_verifiers.push(verifier);
+        Ok(())
+    }
+
+    pub fn remove_trusted_verifier(&mut self, verifier: &Pubkey) -> Result<()> {
+        let index = self.trusted_verifiers.iter()
+            .position(|v| v == verifier)
+            .ok_or(crate::error::RecruSearchError::UntrustedVerifier)?;
+        self.trusted_verifiers.swap_remove(index);
+        Ok(())
+    }
+
+    pub fn is_review_board(&self, board: &Pubkey) -> bool {
+        self.review_boards.contains(board)
+    }
+
+    pub fn add_review_board(&mut self, board: Pubkey) -> Result<()> {
+        require!(!self.is_review_board(&board), AdminError::AlreadyVerified);
+        require!(
+            self.review_boards.len() < MAX_REVIEW_BOARDS,
+            crate::error::RecruSearchError::TooManyReviewBoards
+        );
+        self.review_boards.push(board);
+        Ok(())
+    }
+
+    pub fn remove_review_board(&mut self, board: &Pubkey) -> Result<()> {
+        let index = self.review_boards.iter()
+            .position(|b| b == board)
+            .ok_or(crate::error::RecruSearchError::UnauthorizedReviewBoard)?;
+        self.review_boards.swap_remove(index);
+        Ok(())
+    }
+
+    /// The admin authority always arbitrates; others must be appointed
+    pub fn is_arbitrator(&self, arbitrator: &Pubkey) -> bool {
+        self.is_authorized(arbitrator) || self.arbitrators.contains(arbitrator)
+    }
+
+    pub fn add_arbitrator(&mut self, arbitrator: Pubkey) -> Result<()> {
+        require!(!self.is_arbitrator(&arbitrator), AdminError::AlreadyVerified);
+        require!(
+            self.arbitrators.len() < MAX_ARBITRATORS,
+            crate::error::RecruSearchError::TooManyArbitrators
+        );
+        self.arbitrators.push(arbitrator);
+        Ok(())
+    }
+
+    pub fn remove_arbitrator(&mut self, arbitrator: &Pubkey) -> Result<()> {
+        let index = self.arbitrators.iter()
+            .position(|a| a == arbitrator)
+            .ok_or(crate::error::RecruSearchError::UnauthorizedArbitrator)?;
+        self.arbitrators.swap_remove(index);
+        Ok(())
+    }
+
+    pub fn is_identity_attester(&self, attester: &Pubkey) -> bool {
+        self.identity_attesters.contains(attester)
+    }
+
+    pub fn add_identity_attester(&mut self, attester: Pubkey) -> Result<()> {
+        require!(!self.is_identity_attester(&attester), AdminError::AlreadyVerified);
+        require!(
+            self.identity_attesters.len() < MAX_IDENTITY_ATTESTERS,
+            crate::error::RecruSearchError::TooManyIdentityAttesters
+        );
+        self.identity_attesters.push(attester);
+        Ok(())
+    }
+
+    pub fn remove_identity_attester(&mut self, attester: &Pubkey) -> Result<()> {
+        let index = self.identity_attesters.iter()
+            .position(|a| a == attester)
+            .ok_or(crate::error::RecruSearchError::UntrustedIdentityAttester)?;
+        self.identity_attesters.swap_remove(index);
+        Ok(())
+    }
+
+    pub fn new(authority: Pubkey, bump: u8) -> Self {
         Self {
             authority,
             dashboard: AdminDashboard {
@@ -54,7 +172,7 @@ impl Admin {
                 total_rewards_distributed: 0,
                 study_categories: Vec::new(),
                 platform_metrics: PlatformMetrics {
-                    average_completion_rate: 0.0,
+                    average_completion_rate: 0,
                     average_study_duration: 0,
                     participant_retention_rate: 0.0,
                     researcher_satisfaction_rate: 0.0,
@@ -63,25 +181,73 @@ impl Admin {
             verified_researchers: 0,
             pending_verifications: 0,
             last_updated: Clock::get().unwrap().unix_timestamp,
-            study_status: StudyStatus::Inactive,
+            study_status: StudyStatus::Draft,
             participant_action: ParticipantAction::Unsuspend,
+            trusted_verifiers: Vec::new(),
+            review_boards: Vec::new(),
+            arbitrators: Vec::new(),
+            identity_attesters: Vec::new(),
+            survey_consent_waiver: false,
+            bump,
         }
     }
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
 pub enum ParticipantAction {
     Suspend,
     Unsuspend,
     Ban,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+/// Study lifecycle: Draft -> Recruiting -> Active -> Closed -> Archived,
+/// with Suspended and Cancelled reachable from the live phases.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
 pub enum StudyStatus {
+    Draft,
+    Recruiting,
     Active,
-    Inactive,
-    Completed,
+    Closed,
+    Archived,
     Suspended,
+    Cancelled,
+}
+
+/// Role requesting a study status transition
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum StatusAuthority {
+    Researcher,
+    Admin,
+}
+
+impl StudyStatus {
+    /// Whitelist of legal lifecycle transitions for each role
+    pub fn can_transition(&self, to: StudyStatus, by: StatusAuthority) -> bool {
+        use StudyStatus::*;
+        match by {
+            StatusAuthority::Researcher => matches!(
+                (*self, to),
+                (Draft, Recruiting)
+                    | (Recruiting, Active)
+                    | (Recruiting, Closed)
+                    | (Active, Closed)
+                    | (Closed, Archived)
+                    | (Draft, Cancelled)
+                    | (Recruiting, Cancelled)
+                    | (Active, Cancelled)
+            ),
+            StatusAuthority::Admin => matches!(
+                (*self, to),
+                (Recruiting, Suspended)
+                    | (Active, Suspended)
+                    | (Suspended, Recruiting)
+                    | (Suspended, Active)
+                    | (Suspended, Closed)
+                    | (Suspended, Cancelled)
+                    | (Closed, Archived)
+            ),
+        }
+    }
 }
 
 impl AdminDashboard {
@@ -89,12 +255,12 @@ impl AdminDashboard {
         if let Some(category_stats) = self.study_categories
             .iter_mut()
             .find(|stats| stats.category == study.title) {
+            let rating_total = category_stats.average_rating as u64 * category_stats.study_count as u64
+                + study.analytics.average_rating as u64;
             category_stats.study_count += 1;
             category_stats.participant_count += study.current_participants;
-            category_stats.average_rating = (category_stats.average_rating * 
-                category_stats.study_count as f32 + study.analytics.average_rating) / 
-                (category_stats.study_count + 1) as f32;
-        } else {
+            category_stats.average_rating = (rating_total / category_stats.study_count as u64) as u32;
+        } else if self.study_categories.len() < MAX_STUDY_CATEGORIES {
             self.study_categories.push(CategoryStats {
                 category: study.title.clone(),
                 study_count: 1,
@@ -102,17 +268,16 @@ impl AdminDashboard {
                 average_rating: study.analytics.average_rating,
             });
         }
-        self.platform_metrics.average_completion_rate = 
-            (self.platform_metrics.average_completion_rate * self.total_studies as f32 + 
-            study.analytics.completion_rate) / (self.total_studies + 1) as f32;
+        self.platform_metrics.average_completion_rate =
+            ((self.platform_metrics.average_completion_rate as u64 * self.total_studies as u64 +
+            study.analytics.completion_rate as u64) / (self.total_studies + 1) as u64) as u32;
         self.platform_metrics.average_study_duration = 
-            (self.platform_metrics.average_study_duration * self.total_studies as i64 + 
-            0) / (self.total_studies + 1) as i64;
+            (self.platform_metrics.average_study_duration * self.total_studies as i64) /
+            (self.total_studies + 1) as i64;
     }
     pub fn calculate_participant_retention(&self, participant: &crate::state::participant::Participant) -> f32 {
-        let completed_studies = participant.profile.completed_studies.len() as f32;
-        let total_studies = (participant.profile.completed_studies.len() + 
-            participant.profile.active_studies.len()) as f32;
+        let completed_studies = participant.completed_studies as f32;
+        let total_studies = (participant.completed_studies + participant.active_studies) as f32;
         if total_studies == 0.0 {
             0.0
         } else {
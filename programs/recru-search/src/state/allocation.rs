@@ -0,0 +1,362 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use crate::error::RecruSearchError;
+use crate::state::eligibility::AttributeKind;
+use crate::state::quota::{StratumQuota, MAX_QUOTAS};
+
+pub const MAX_ARMS: usize = 4;
+pub const MAX_ARM_NAME_LEN: usize = 32;
+/// One block per quota stratum plus one for participants matching none
+pub const MAX_ALLOCATION_BLOCKS: usize = MAX_QUOTAS + 1;
+/// Arm commitments a blinded stratum can have queued ahead of joins
+pub const MAX_PENDING_COMMITMENTS: usize = 16;
+pub const MAX_UNBLINDING_REASON_LEN: usize = 200;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct StudyArm {
+    #[max_len(MAX_ARM_NAME_LEN)]
+    pub name: String,
+    /// Relative share of each block, e.g. 1:1 or 2:1 treatment to control
+    pub ratio: u8,
+    pub assigned: u32,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum RandomizationMethod {
+    /// Permuted blocks across the whole study
+    Block,
+    /// Separate permuted blocks for each quota stratum of `kind`
+    Stratified { kind: AttributeKind },
+}
+
+/// Allocation state of one stratum: slots left per arm in the current block, or
+/// for blinded studies the queue of arm commitments the PI has posted
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct AllocationBlock {
+    /// Index into `Study::quotas`, or `None` for the unstratified block
+    pub stratum: Option<u8>,
+    #[max_len(MAX_ARMS)]
+    pub remaining: Vec<u16>,
+    #[max_len(MAX_PENDING_COMMITMENTS)]
+    pub pending: Vec<[u8; 32]>,
+    /// Position in the stratum's allocation list of `pending[0]`
+    pub next_sequence: u32,
+    /// Blinded strata only: hash of the part of the allocation list not yet posted, see
+    /// `allocation_list_hash`. Fixed while the study is a draft; all zeros once used up.
+    pub list_head: Option<[u8; 32]>,
+}
+
+/// A blinded enrollment's arm: `sha256(arm || salt)` where
+/// `salt = sha256(key || study || stratum || sequence)`, see `Allocation::salt`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct ArmCommitment {
+    pub commitment: [u8; 32],
+    pub sequence: u32,
+}
+
+impl ArmCommitment {
+    /// Whether `arm` with `salt` opens this commitment
+    pub fn opens(&self, arm: u8, salt: &[u8; 32]) -> bool {
+        hashv(&[&[arm], salt]).to_bytes() == self.commitment
+    }
+}
+
+/// An unblinded enrollment's place in the draw queue. The arm is drawn from the hash
+/// of the slot the participant joined in, which does not exist until that slot ends.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct PendingDraw {
+    pub sequence: u32,
+    pub slot: u64,
+}
+
+/// Hash chain over a blinded stratum's arm commitments: `sha256(c[i] || rest)` where
+/// `rest` is the hash of the commitments after `c[i]`, and all zeros past the last one.
+/// Committing to the head fixes the whole list, yet each batch can be posted with just
+/// the hash of what follows it.
+pub fn allocation_list_hash(commitments: &[[u8; 32]], rest: [u8; 32]) -> [u8; 32] {
+    commitments.iter().rev().fold(rest, |tail, commitment| hashv(&[commitment, &tail]).to_bytes())
+}
+
+/// Result of one arm draw, everything an auditor needs to replay it
+pub struct ArmDraw {
+    pub arm: u8,
+    pub sequence: u32,
+    pub randomness: [u8; 32],
+}
+
+/// Arm assignment for a randomized study.
+///
+/// Seeds: `[b"allocation", study]`. The researcher commits to `sha256(seed)` while the
+/// study is a draft and reveals the seed once recruitment opens. The seed is public from
+/// then on, so a join only queues a draw; the arm is drawn afterwards, in join order,
+/// from the seed, the study, the draw's sequence number and the hash of the join slot.
+/// That hash is produced after the join lands, so a participant cannot simulate the
+/// outcome before committing to the enrollment. `sequence_hash` chains every draw so
+/// the full sequence can be replayed and checked once the study ends.
+///
+/// Blinded studies never draw on-chain: the seed is the PI's allocation key, kept
+/// secret until the study closes, and each join takes the next arm commitment the
+/// PI queued for the participant's stratum, out of a list fixed while it was a draft.
+#[account]
+#[derive(InitSpace)]
+pub struct Allocation {
+    pub study: Pubkey,
+    pub method: RandomizationMethod,
+    pub blinded: bool,
+    #[max_len(MAX_ARMS)]
+    pub arms: Vec<StudyArm>,
+    /// Each block holds `ratio * block_multiple` slots of every arm
+    pub block_multiple: u8,
+    pub seed_commitment: [u8; 32],
+    pub seed: Option<[u8; 32]>,
+    /// Arms drawn or commitments handed out so far
+    pub allocations: u32,
+    /// Unblinded draws queued by joins; `queued - allocations` are still pending
+    pub queued: u32,
+    /// `sha256(prev || participant || arm || randomness)` over every draw so far
+    pub sequence_hash: [u8; 32],
+    #[max_len(MAX_ALLOCATION_BLOCKS)]
+    pub blocks: Vec<AllocationBlock>,
+    pub bump: u8,
+}
+
+impl Allocation {
+    #[allow(clippy::too_many_arguments)]
+    pub fn configure(
+        &mut self,
+        study: Pubkey,
+        method: RandomizationMethod,
+        blinded: bool,
+        arms: Vec<StudyArm>,
+        block_multiple: u8,
+        seed_commitment: [u8; 32],
+        bump: u8,
+    ) -> Result<()> {
+        require!(
+            (2..=MAX_ARMS).contains(&arms.len())
+                && block_multiple > 0
+                && arms.iter().all(|a| {
+                    !a.name.is_empty() && a.name.len() <= MAX_ARM_NAME_LEN && a.ratio > 0
+                }),
+            RecruSearchError::InvalidRandomization
+        );
+        self.study = study;
+        self.method = method;
+        self.blinded = blinded;
+        self.arms = arms;
+        for arm in self.arms.iter_mut() {
+            arm.assigned = 0;
+        }
+        self.block_multiple = block_multiple;
+        self.seed_commitment = seed_commitment;
+        self.seed = None;
+        self.allocations = 0;
+        self.queued = 0;
+        self.sequence_hash = [0; 32];
+        self.blocks = Vec::new();
+        self.bump = bump;
+        Ok(())
+    }
+    pub fn reveal(&mut self, seed: [u8; 32]) -> Result<()> {
+        require!(
+            self.seed.is_none() && hashv(&[&seed]).to_bytes() == self.seed_commitment,
+            RecruSearchError::InvalidAllocationSeed
+        );
+        self.seed = Some(seed);
+        Ok(())
+    }
+    /// Checks the allocation key against the commitment, revealing it the first time
+    pub fn unseal(&mut self, key: [u8; 32]) -> Result<()> {
+        match self.seed {
+            Some(seed) => require!(seed == key, RecruSearchError::InvalidAllocationSeed),
+            None => self.reveal(key)?,
+        }
+        Ok(())
+    }
+    /// Counts an arm once its assignment becomes known
+    pub fn record_assignment(&mut self, arm: u8) -> Result<()> {
+        let arm = self.arms.get_mut(arm as usize).ok_or(RecruSearchError::InvalidArmCommitment)?;
+        arm.assigned = arm.assigned.checked_add(1).ok_or(RecruSearchError::ArithmeticOverflow)?;
+        Ok(())
+    }
+    /// Salt of the `sequence`th arm commitment in `stratum`'s list
+    pub fn salt(&self, key: &[u8; 32], stratum: Option<u8>, sequence: u32) -> [u8; 32] {
+        hashv(&[
+            key,
+            self.study.as_ref(),
+            &[stratum.unwrap_or(u8::MAX)],
+            &sequence.to_le_bytes(),
+        ]).to_bytes()
+    }
+    fn block_mut(&mut self, stratum: Option<u8>) -> &mut AllocationBlock {
+        let index = match self.blocks.iter().position(|b| b.stratum == stratum) {
+            Some(index) => index,
+            None => {
+                self.blocks.push(AllocationBlock {
+                    stratum,
+                    remaining: Vec::new(),
+                    pending: Vec::new(),
+                    next_sequence: 0,
+                    list_head: None,
+                });
+                self.blocks.len() - 1
+            }
+        };
+        &mut self.blocks[index]
+    }
+    /// Fixes a blinded stratum's full allocation list by its `allocation_list_hash`
+    pub fn commit_list(&mut self, stratum: Option<u8>, list_head: [u8; 32]) -> Result<()> {
+        require!(self.blinded, RecruSearchError::InvalidRandomization);
+        require!(
+            stratum.map_or(true, |s| (s as usize) < MAX_QUOTAS),
+            RecruSearchError::InvalidRandomization
+        );
+        let block = self.block_mut(stratum);
+        require!(block.list_head.is_none(), RecruSearchError::AllocationListCommitted);
+        block.list_head = Some(list_head);
+        Ok(())
+    }
+    /// Queues the PI's next arm commitments for a blinded stratum. They must be the next
+    /// entries of the committed list, proven by the hash of the entries after them.
+    pub fn post_commitments(
+        &mut self,
+        stratum: Option<u8>,
+        commitments: Vec<[u8; 32]>,
+        rest: [u8; 32],
+    ) -> Result<()> {
+        require!(self.blinded, RecruSearchError::InvalidRandomization);
+        let block = self.block_mut(stratum);
+        let head = block.list_head.ok_or(RecruSearchError::AllocationListNotCommitted)?;
+        require!(
+            block.pending.len() + commitments.len() <= MAX_PENDING_COMMITMENTS,
+            RecruSearchError::InputTooLong
+        );
+        require!(
+            !commitments.is_empty() && allocation_list_hash(&commitments, rest) == head,
+            RecruSearchError::AllocationListMismatch
+        );
+        block.list_head = Some(rest);
+        block.pending.extend(commitments);
+        Ok(())
+    }
+    /// Hands the next queued commitment in `stratum` to a joining participant
+    pub fn take_commitment(&mut self, stratum: Option<u8>) -> Result<ArmCommitment> {
+        let block = self.block_mut(stratum);
+        require!(!block.pending.is_empty(), RecruSearchError::AllocationExhausted);
+        let commitment = ArmCommitment {
+            commitment: block.pending.remove(0),
+            sequence: block.next_sequence,
+        };
+        block.next_sequence = block.next_sequence.checked_add(1)
+            .ok_or(RecruSearchError::ArithmeticOverflow)?;
+        self.allocations = self.allocations.checked_add(1)
+            .ok_or(RecruSearchError::ArithmeticOverflow)?;
+        Ok(commitment)
+    }
+    /// Opens a blinded enrollment's commitment with the allocation key
+    pub fn open(&self, key: &[u8; 32], stratum: Option<u8>, commitment: &ArmCommitment) -> Result<u8> {
+        let salt = self.salt(key, stratum, commitment.sequence);
+        (0..self.arms.len() as u8)
+            .find(|&arm| commitment.opens(arm, &salt))
+            .ok_or(RecruSearchError::InvalidArmCommitment.into())
+    }
+    /// The block a participant counted in `strata` draws from
+    pub fn stratum_of(&self, quotas: &[StratumQuota], strata: &[u8]) -> Option<u8> {
+        match self.method {
+            RandomizationMethod::Block => None,
+            RandomizationMethod::Stratified { kind } => strata
+                .iter()
+                .copied()
+                .find(|&i| quotas.get(i as usize).is_some_and(|q| q.kind == kind)),
+        }
+    }
+    /// Queues a joining participant's draw against the slot they joined in
+    pub fn queue_draw(&mut self, slot: u64) -> Result<PendingDraw> {
+        require!(self.seed.is_some(), RecruSearchError::AllocationSeedNotRevealed);
+        let draw = PendingDraw { sequence: self.queued, slot };
+        self.queued = self.queued.checked_add(1).ok_or(RecruSearchError::ArithmeticOverflow)?;
+        Ok(draw)
+    }
+    /// Assigns the queued draw's arm from the participant's block, starting a fresh block
+    /// when it runs out. Draws run strictly in join order so the block state cannot be
+    /// steered by choosing which pending draw to settle first.
+    pub fn draw(
+        &mut self,
+        study: &Pubkey,
+        participant: &Pubkey,
+        stratum: Option<u8>,
+        pending: &PendingDraw,
+        slot_hash: &[u8; 32],
+    ) -> Result<ArmDraw> {
+        let seed = self.seed.ok_or(RecruSearchError::AllocationSeedNotRevealed)?;
+        let sequence = self.allocations;
+        require!(pending.sequence == sequence, RecruSearchError::DrawOutOfOrder);
+        let randomness = hashv(&[&seed, study.as_ref(), &sequence.to_le_bytes(), slot_hash]).to_bytes();
+
+        let fresh: Vec<u16> = self.arms.iter()
+            .map(|a| a.ratio as u16 * self.block_multiple as u16)
+            .collect();
+        let block = self.block_mut(stratum);
+        if block.remaining.iter().all(|&r| r == 0) {
+            block.remaining = fresh;
+        }
+
+        // Uniform over the slots left in the block
+        let left: u64 = block.remaining.iter().map(|&r| r as u64).sum();
+        let mut pick = u64::from_le_bytes(randomness[..8].try_into().unwrap()) % left;
+        let mut arm = 0;
+        for (i, &r) in block.remaining.iter().enumerate() {
+            if pick < r as u64 {
+                arm = i;
+                break;
+            }
+            pick -= r as u64;
+        }
+        block.remaining[arm] -= 1;
+
+        let assigned = &mut self.arms[arm].assigned;
+        *assigned = assigned.checked_add(1).ok_or(RecruSearchError::ArithmeticOverflow)?;
+        self.allocations = sequence.checked_add(1).ok_or(RecruSearchError::ArithmeticOverflow)?;
+        self.sequence_hash = hashv(&[
+            &self.sequence_hash,
+            participant.as_ref(),
+            &[arm as u8],
+            &randomness,
+        ]).to_bytes();
+        Ok(ArmDraw { arm: arm as u8, sequence, randomness })
+    }
+}
+
+/// Permanent record of one participant unblinded before the study closed.
+///
+/// Seeds: `[b"unblinding", enrollment]`. Never closed.
+#[account]
+#[derive(InitSpace)]
+pub struct UnblindingRecord {
+    pub study: Pubkey,
+    pub enrollment: Pubkey,
+    pub arm: u8,
+    #[max_len(MAX_UNBLINDING_REASON_LEN)]
+    pub reason: String,
+    /// The PI's `Researcher` account, which supplied the opening
+    pub requested_by: Pubkey,
+    /// Admin or the study's ethics board
+    pub authorized_by: Pubkey,
+    pub unblinded_at: i64,
+    pub bump: u8,
+}
+
+/// Hash of slot `joined`, read from the SlotHashes sysvar without deserializing the
+/// whole list. Only that exact slot will do: any substitute is one a cranker could pick
+/// by choosing when to draw.
+pub fn join_slot_hash(slot_hashes: &AccountInfo, joined: u64) -> Result<[u8; 32]> {
+    let data = slot_hashes.try_borrow_data()?;
+    // u64 entry count, then (u64 slot, [u8; 32] hash) entries, newest first
+    require!(data.len() >= 8, RecruSearchError::RandomnessUnavailable);
+    let count = u64::from_le_bytes(data[..8].try_into().unwrap()) as usize;
+    data[8..].chunks_exact(40)
+        .take(count)
+        .find(|entry| u64::from_le_bytes(entry[..8].try_into().unwrap()) == joined)
+        .map(|entry| entry[8..].try_into().unwrap())
+        .ok_or(RecruSearchError::RandomnessUnavailable.into())
+}
@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+use crate::error::RecruSearchError;
+
+/// Ed25519 program instruction layout: 2-byte header, then one 14-byte offsets record per signature
+const ED25519_HEADER_LEN: usize = 2;
+const ED25519_OFFSETS_LEN: usize = 14;
+/// Instruction index meaning "this same Ed25519 instruction"
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// A trusted verifier's statement that `participant` may join `study` until `expiry`.
+///
+/// The verifier signs `participant || study || expiry (i64 LE) || nonce (u64 LE)`
+/// in an Ed25519 program instruction placed immediately before `join_study`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct EligibilityAttestation {
+    pub verifier: Pubkey,
+    pub expiry: i64,
+    pub nonce: u64,
+}
+
+impl EligibilityAttestation {
+    pub fn message(&self, participant: &Pubkey, study: &Pubkey) -> Vec<u8> {
+        let mut message = Vec::with_capacity(32 + 32 + 8 + 8);
+        message.extend_from_slice(participant.as_ref());
+        message.extend_from_slice(study.as_ref());
+        message.extend_from_slice(&self.expiry.to_le_bytes());
+        message.extend_from_slice(&self.nonce.to_le_bytes());
+        message
+    }
+
+    /// Checks expiry and that the preceding instruction verified the verifier's signature over this attestation
+    pub fn verify(&self, instructions_sysvar: &AccountInfo, participant: &Pubkey, study: &Pubkey) -> Result<()> {
+        require!(
+            Clock::get()?.unix_timestamp < self.expiry,
+            RecruSearchError::AttestationExpired
+        );
+        verify_preceding_signature(instructions_sysvar, &self.verifier, &self.message(participant, study))
+    }
+}
+
+/// Checks the instruction immediately before the current one is an Ed25519 program
+/// instruction verifying `signer`'s signature over exactly `expected`
+pub fn verify_preceding_signature(instructions_sysvar: &AccountInfo, signer: &Pubkey, expected: &[u8]) -> Result<()> {
+    let current = load_current_index_checked(instructions_sysvar)?;
+    require!(current > 0, RecruSearchError::InvalidAttestation);
+    let ix = load_instruction_at_checked(current as usize - 1, instructions_sysvar)?;
+    require!(ix.program_id == ed25519_program::ID, RecruSearchError::InvalidAttestation);
+    let (pubkey, message) = parse_ed25519_instruction(&ix.data)?;
+    require!(pubkey == signer.as_ref(), RecruSearchError::InvalidAttestation);
+    require!(message == expected, RecruSearchError::InvalidAttestation);
+    Ok(())
+}
+
+/// Extracts the public key and message from a single-signature Ed25519 instruction
+/// whose offsets all point into its own data.
+fn parse_ed25519_instruction(data: &[u8]) -> Result<(&[u8], &[u8])> {
+    require!(
+        data.len() >= ED25519_HEADER_LEN + ED25519_OFFSETS_LEN && data[0] == 1,
+        RecruSearchError::InvalidAttestation
+    );
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let offsets = ED25519_HEADER_LEN;
+    let signature_ix = read_u16(offsets + 2);
+    let pubkey_offset = read_u16(offsets + 4) as usize;
+    let pubkey_ix = read_u16(offsets + 6);
+    let message_offset = read_u16(offsets + 8) as usize;
+    let message_len = read_u16(offsets + 10) as usize;
+    let message_ix = read_u16(offsets + 12);
+    require!(
+        signature_ix == CURRENT_INSTRUCTION
+            && pubkey_ix == CURRENT_INSTRUCTION
+            && message_ix == CURRENT_INSTRUCTION,
+        RecruSearchError::InvalidAttestation
+    );
+    let pubkey = data
+        .get(pubkey_offset..pubkey_offset + 32)
+        .ok_or(RecruSearchError::InvalidAttestation)?;
+    let message = data
+        .get(message_offset..message_offset + message_len)
+        .ok_or(RecruSearchError::InvalidAttestation)?;
+    Ok((pubkey, message))
+}
+
+/// Marks a verifier nonce as spent.
+///
+/// Seeds: `[b"attestation_nonce", verifier, nonce (u64 LE)]`; initialising it twice fails,
+/// which is what rejects replayed attestations.
+#[account]
+#[derive(InitSpace)]
+pub struct AttestationNonce {
+    pub verifier: Pubkey,
+    pub nonce: u64,
+    pub participant: Pubkey,
+    pub study: Pubkey,
+    pub used_at: i64,
+}
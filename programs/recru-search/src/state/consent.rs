@@ -1,15 +1,23 @@
 use anchor_lang::prelude::*;
+use crate::state::guardianship::GuardianshipParty;
 use mpl_token_metadata::types::DataV2;
 
 // Define constants
 pub const CONSENT_NFT_SYMBOL: &str = "CONSENT";
 pub const CONSENT_NFT_URI: &str = "https://recru-search.app/metadata/consent.json";
+pub const MAX_CONSENT_VERSION_LEN: usize = 32;
+pub const MAX_CONSENT_HASH_LEN: usize = 64;
+pub const MAX_CONSENT_VERSIONS: usize = 10;
 
+/// Seeds: `[b"consent_nft", admin]`
 #[account]
+#[derive(InitSpace)]
 pub struct Consent {
     pub authority: Pubkey,
     pub study_id: Pubkey,
+    #[max_len(MAX_CONSENT_VERSION_LEN)]
     pub version: String,
+    #[max_len(MAX_CONSENT_HASH_LEN)]
     pub consent_hash: String,
     pub issued_at: i64,
     pub revoked_at: Option<i64>,
@@ -19,9 +27,97 @@ pub struct Consent {
     pub total_issued: u64,
     pub total_revoked: u64,
     // Tracking valid consent versions
+    #[max_len(MAX_CONSENT_VERSIONS, MAX_CONSENT_VERSION_LEN)]
     pub consent_versions: Vec<String>,
 }
 
+/// A participant's consent to a single study, against the written-consent version
+/// current when it was issued.
+///
+/// Seeds: `[b"study_consent", study, participant]`
+#[account]
+#[derive(InitSpace)]
+pub struct StudyConsent {
+    pub study: Pubkey,
+    pub participant: Pubkey,
+    #[max_len(MAX_CONSENT_VERSION_LEN)]
+    pub version: String,
+    pub issued_at: i64,
+    pub revoked_at: Option<i64>,
+    /// Guardian-gated studies only: the participant's own assent, which can be recorded
+    /// before consent is issued
+    pub assented_at: Option<i64>,
+    /// Guardian-gated studies only: the guardian's consent, given with the issuance
+    pub guardian_consented_at: Option<i64>,
+    pub bump: u8,
+}
+
+impl StudyConsent {
+    /// A record created by an assent alone has not been issued yet
+    pub fn is_active(&self) -> bool {
+        self.issued_at != 0 && self.revoked_at.is_none()
+    }
+    /// Issues (or re-issues after a revocation) the participant's consent to the study
+    pub fn issue(&mut self, study: Pubkey, participant: Pubkey, version: String, now: i64, bump: u8) -> Result<()> {
+        require!(!self.is_active(), crate::error::RecruSearchError::DuplicateConsent);
+        self.study = study;
+        self.participant = participant;
+        self.version = version;
+        self.issued_at = now;
+        self.revoked_at = None;
+        self.bump = bump;
+        Ok(())
+    }
+    /// Consent only counts for the version the study currently asks participants to sign
+    pub fn require_current(&self, study: &Pubkey, version: Option<&String>) -> Result<()> {
+        require!(
+            self.is_active() && self.study == *study,
+            crate::error::RecruSearchError::ConsentRequired
+        );
+        require!(
+            version == Some(&self.version),
+            crate::error::RecruSearchError::InvalidConsentVersion
+        );
+        Ok(())
+    }
+    /// Revoking also drops assent and guardian consent; a new issuance needs both again
+    pub fn revoke(&mut self, now: i64) -> Result<()> {
+        require!(self.is_active(), crate::error::RecruSearchError::NoActiveConsent);
+        self.revoked_at = Some(now);
+        self.assented_at = None;
+        self.guardian_consented_at = None;
+        Ok(())
+    }
+    pub fn record_assent(&mut self, study: Pubkey, participant: Pubkey, now: i64, bump: u8) {
+        self.study = study;
+        self.participant = participant;
+        self.assented_at = Some(now);
+        self.bump = bump;
+    }
+    /// Guardian's consent to this study, only once the participant has assented to it
+    pub fn record_guardian_consent(&mut self, now: i64) -> Result<()> {
+        require!(self.assented_at.is_some(), crate::error::RecruSearchError::AssentRequired);
+        self.guardian_consented_at = Some(now);
+        Ok(())
+    }
+    /// Both the participant's assent and the guardian's consent must still stand
+    pub fn require_guardian_consent(&self) -> Result<()> {
+        require!(self.assented_at.is_some(), crate::error::RecruSearchError::AssentRequired);
+        require!(
+            self.is_active() && self.guardian_consented_at.is_some(),
+            crate::error::RecruSearchError::GuardianConsentRequired
+        );
+        Ok(())
+    }
+    /// Withdraws whichever side `party` gave
+    pub fn withdraw(&mut self, party: GuardianshipParty) {
+        match party {
+            GuardianshipParty::Guardian => self.guardian_consented_at = None,
+            GuardianshipParty::Participant => self.assented_at = None,
+        }
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct ConsentMetadata {
     pub study_id: Pubkey,
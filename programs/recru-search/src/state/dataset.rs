@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use crate::state::eligibility::hash_pair;
+
+pub const MAX_RESPONSE_URI_LEN: usize = 200;
+/// Deepest accepted dataset proof (up to 65,536 submissions)
+pub const MAX_DATASET_PROOF_DEPTH: usize = 16;
+
+/// Leaf of an anchored dataset: `sha256(0x00 || enrollment || response_hash || retained)`.
+///
+/// Every submission gets exactly one leaf; `retained` is false when the data was
+/// dropped from the published dataset, e.g. because the participant withdrew.
+pub fn dataset_leaf(enrollment: &Pubkey, response_hash: &[u8; 32], retained: bool) -> [u8; 32] {
+    hashv(&[&[0], enrollment.as_ref(), response_hash, &[retained as u8]]).to_bytes()
+}
+
+/// Checks a sorted-pair inclusion proof, the same tree shape as attribute commitments
+pub fn verify_dataset_proof(leaf: [u8; 32], proof: &[[u8; 32]], root: &[u8; 32]) -> bool {
+    proof.len() <= MAX_DATASET_PROOF_DEPTH
+        && proof.iter().fold(leaf, |node, sibling| hash_pair(&node, sibling)) == *root
+}
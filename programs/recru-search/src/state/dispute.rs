@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+use crate::state::reputation::ReputationEvent;
+
+pub const MAX_DISPUTE_EVIDENCE: usize = 8;
+/// How long after leaving a study a participant may contest the exit
+pub const DISPUTE_FILING_WINDOW: i64 = 14 * 24 * 60 * 60;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum DisputeParty {
+    Participant,
+    Researcher,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum DisputeStatus {
+    Open,
+    Resolved,
+}
+
+/// Arbitrator's decision on the held reward
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum DisputeRuling {
+    FullPayment,
+    /// Pays `amount`, which must be strictly between zero and the held reward
+    PartialPayment { amount: u64 },
+    NoPayment,
+}
+
+impl DisputeRuling {
+    /// Amount the participant receives out of `held`
+    pub fn award(&self, held: u64) -> Result<u64> {
+        match *self {
+            DisputeRuling::FullPayment => Ok(held),
+            DisputeRuling::PartialPayment { amount } => {
+                require!(amount > 0 && amount < held, crate::error::RecruSearchError::InvalidRuling);
+                Ok(amount)
+            }
+            DisputeRuling::NoPayment => Ok(0),
+        }
+    }
+    /// Reputation outcomes for the participant and the researcher, in that order;
+    /// a split decision moves neither
+    pub fn reputation_outcomes(&self) -> Option<(ReputationEvent, ReputationEvent)> {
+        match self {
+            DisputeRuling::FullPayment => Some((ReputationEvent::DisputeWon, ReputationEvent::DisputeLost)),
+            DisputeRuling::PartialPayment { .. } => None,
+            DisputeRuling::NoPayment => Some((ReputationEvent::DisputeLost, ReputationEvent::DisputeWon)),
+        }
+    }
+}
+
+/// A contested enrollment whose reward stays in escrow until the dispute is settled.
+///
+/// Seeds: `[b"dispute", enrollment]`
+#[account]
+#[derive(InitSpace)]
+pub struct Dispute {
+    pub study: Pubkey,
+    pub enrollment: Pubkey,
+    pub participant: Pubkey,
+    pub opened_by: DisputeParty,
+    /// Hash of the opening party's written grounds, kept off-chain
+    pub reason_hash: [u8; 32],
+    /// Reward held back while the dispute is open
+    pub amount: u64,
+    pub status: DisputeStatus,
+    pub opened_at: i64,
+    #[max_len(MAX_DISPUTE_EVIDENCE)]
+    pub participant_evidence: Vec<[u8; 32]>,
+    #[max_len(MAX_DISPUTE_EVIDENCE)]
+    pub researcher_evidence: Vec<[u8; 32]>,
+    pub ruling: Option<DisputeRuling>,
+    /// Part of `amount` paid to the participant on resolution
+    pub awarded: u64,
+    pub resolved_by: Option<Pubkey>,
+    pub resolved_at: Option<i64>,
+    pub bump: u8,
+}
+
+impl Dispute {
+    #[allow(clippy::too_many_arguments)]
+    pub fn open(
+        &mut self,
+        study: Pubkey,
+        enrollment: Pubkey,
+        participant: Pubkey,
+        opened_by: DisputeParty,
+        reason_hash: [u8; 32],
+        amount: u64,
+        now: i64,
+        bump: u8,
+    ) {
+        self.study = study;
+        self.enrollment = enrollment;
+        self.participant = participant;
+        self.opened_by = opened_by;
+        self.reason_hash = reason_hash;
+        self.amount = amount;
+        self.status = DisputeStatus::Open;
+        self.opened_at = now;
+        self.participant_evidence = Vec::new();
+        self.researcher_evidence = Vec::new();
+        self.ruling = None;
+        self.awarded = 0;
+        self.resolved_by = None;
+        self.resolved_at = None;
+        self.bump = bump;
+    }
+    pub fn require_open(&self) -> Result<()> {
+        require!(
+            self.status == DisputeStatus::Open,
+            crate::error::RecruSearchError::DisputeNotOpen
+        );
+        Ok(())
+    }
+    pub fn add_evidence(&mut self, party: DisputeParty, evidence_hash: [u8; 32]) -> Result<()> {
+        self.require_open()?;
+        let evidence = match party {
+            DisputeParty::Participant => &mut self.participant_evidence,
+            DisputeParty::Researcher => &mut self.researcher_evidence,
+        };
+        require!(
+            evidence.len() < MAX_DISPUTE_EVIDENCE,
+            crate::error::RecruSearchError::TooMuchEvidence
+        );
+        evidence.push(evidence_hash);
+        Ok(())
+    }
+    /// Records the ruling, returning the amount awarded to the participant
+    pub fn resolve(&mut self, ruling: DisputeRuling, arbitrator: Pubkey, now: i64) -> Result<u64> {
+        self.require_open()?;
+        let awarded = ruling.award(self.amount)?;
+        self.status = DisputeStatus::Resolved;
+        self.ruling = Some(ruling);
+        self.awarded = awarded;
+        self.resolved_by = Some(arbitrator);
+        self.resolved_at = Some(now);
+        Ok(awarded)
+    }
+}
@@ -0,0 +1,186 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use crate::error::RecruSearchError;
+use crate::state::participant::Participant;
+
+pub const MAX_CRITERIA_VALUES: usize = 8;
+pub const MAX_ATTRIBUTE_LEN: usize = 32;
+/// Deepest accepted inclusion proof (up to 256 leaves), for sorted-pair trees
+pub const MAX_PROOF_DEPTH: usize = 8;
+/// The attribute tree has one leaf per `AttributeKind`, at index `kind as u8`, padded
+/// out to a power of two; leaves for attributes not committed to can hold anything
+pub const ATTRIBUTE_TREE_DEPTH: usize = 2;
+/// One disclosure per `AttributeKind`
+pub const MAX_DISCLOSURES: usize = 3;
+
+// Domain separation between leaves and interior nodes
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+/// Screening attributes participants commit to instead of storing in plaintext
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum AttributeKind {
+    AgeGroup,
+    Gender,
+    Region,
+}
+
+/// One revealed attribute plus its Merkle path to `Participant::attributes_root`.
+///
+/// Leaf: `sha256(0x00 || kind || value || salt)`; node: `sha256(0x01 || left || right)`.
+/// The path is walked from the kind's fixed leaf index, so a participant cannot commit
+/// two values for one kind and disclose whichever suits the study.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AttributeDisclosure {
+    pub kind: AttributeKind,
+    pub value: String,
+    pub salt: [u8; 32],
+    pub proof: Vec<[u8; 32]>,
+}
+
+impl AttributeDisclosure {
+    pub fn leaf(&self) -> [u8; 32] {
+        hashv(&[LEAF_PREFIX, &[self.kind as u8], self.value.as_bytes(), &self.salt]).to_bytes()
+    }
+
+    pub fn verify(&self, root: &[u8; 32]) -> bool {
+        if self.proof.len() != ATTRIBUTE_TREE_DEPTH {
+            return false;
+        }
+        let (node, _) = self.proof.iter().fold((self.leaf(), self.kind as usize), |(node, index), sibling| {
+            let parent = if index % 2 == 0 { hash_children(&node, sibling) } else { hash_children(sibling, &node) };
+            (parent, index / 2)
+        });
+        node == *root
+    }
+}
+
+/// Interior node of a positional tree
+pub fn hash_children(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    hashv(&[NODE_PREFIX, left, right]).to_bytes()
+}
+
+pub fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_PREFIX, lo, hi]).to_bytes()
+}
+
+/// Structured eligibility rules a study applies to joining participants.
+/// An empty list places no restriction on that attribute.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct EligibilityCriteria {
+    #[max_len(MAX_CRITERIA_VALUES, MAX_ATTRIBUTE_LEN)]
+    pub allowed_age_groups: Vec<String>,
+    #[max_len(MAX_CRITERIA_VALUES, MAX_ATTRIBUTE_LEN)]
+    pub allowed_genders: Vec<String>,
+    #[max_len(MAX_CRITERIA_VALUES, MAX_ATTRIBUTE_LEN)]
+    pub allowed_regions: Vec<String>,
+    /// Every listed interest must appear in the participant's profile
+    #[max_len(MAX_CRITERIA_VALUES, MAX_ATTRIBUTE_LEN)]
+    pub required_interests: Vec<String>,
+    /// In `REPUTATION_SCALE` units, compared against the decayed score
+    pub min_reputation: u32,
+    pub min_completed_studies: u32,
+    /// Joins must carry a trusted verifier's `EligibilityAttestation`
+    pub requires_attestation: bool,
+    /// Only participants with a live `IdentityRecord` may join
+    pub requires_identity: bool,
+}
+
+impl EligibilityCriteria {
+    pub fn validate(&self) -> Result<()> {
+        for values in [
+            &self.allowed_age_groups,
+            &self.allowed_genders,
+            &self.allowed_regions,
+            &self.required_interests,
+        ] {
+            require!(values.len() <= MAX_CRITERIA_VALUES, RecruSearchError::InvalidEligibilityCriteria);
+            require!(
+                values.iter().all(|v| !v.is_empty() && v.len() <= MAX_ATTRIBUTE_LEN),
+                RecruSearchError::InvalidEligibilityCriteria
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks every criterion against the participant, failing on the first one not met.
+    /// Committed attributes are only read from `disclosures` the study actually needs.
+    pub fn check(&self, participant: &Participant, disclosures: &[AttributeDisclosure]) -> Result<()> {
+        require!(
+            disclosures.len() <= MAX_DISCLOSURES
+                && disclosures.iter().all(|d| d.value.len() <= MAX_ATTRIBUTE_LEN),
+            RecruSearchError::InputTooLong
+        );
+        let root = &participant.attributes_root;
+        check_disclosed(
+            &self.allowed_age_groups,
+            AttributeKind::AgeGroup,
+            root,
+            disclosures,
+            RecruSearchError::IneligibleAgeGroup,
+        )?;
+        check_disclosed(
+            &self.allowed_genders,
+            AttributeKind::Gender,
+            root,
+            disclosures,
+            RecruSearchError::IneligibleGender,
+        )?;
+        check_disclosed(
+            &self.allowed_regions,
+            AttributeKind::Region,
+            root,
+            disclosures,
+            RecruSearchError::IneligibleRegion,
+        )?;
+        require!(
+            self.required_interests.iter().all(|i| participant.profile.interests.contains(i)),
+            RecruSearchError::MissingRequiredInterest
+        );
+        require!(
+            participant.reputation.current(Clock::get()?.unix_timestamp) >= self.min_reputation,
+            RecruSearchError::InsufficientReputation
+        );
+        require!(
+            !self.requires_identity || participant.identity.is_some(),
+            RecruSearchError::IdentityNotAttested
+        );
+        require!(
+            participant.completed_studies >= self.min_completed_studies,
+            RecruSearchError::InsufficientCompletedStudies
+        );
+        Ok(())
+    }
+}
+
+fn check_disclosed(
+    allowed: &[String],
+    kind: AttributeKind,
+    root: &[u8; 32],
+    disclosures: &[AttributeDisclosure],
+    not_allowed: RecruSearchError,
+) -> Result<()> {
+    if allowed.is_empty() {
+        return Ok(());
+    }
+    let value = disclosed_value(kind, root, disclosures)?;
+    if !allowed.iter().any(|v| v == value) {
+        return Err(not_allowed.into());
+    }
+    Ok(())
+}
+
+/// The participant's proven value for `kind`, failing if it was not disclosed or does not verify
+pub fn disclosed_value<'a>(
+    kind: AttributeKind,
+    root: &[u8; 32],
+    disclosures: &'a [AttributeDisclosure],
+) -> Result<&'a str> {
+    let disclosure = disclosures
+        .iter()
+        .find(|d| d.kind == kind)
+        .ok_or(RecruSearchError::MissingAttributeDisclosure)?;
+    require!(disclosure.verify(root), RecruSearchError::InvalidAttributeProof);
+    Ok(&disclosure.value)
+}
@@ -0,0 +1,274 @@
+use anchor_lang::prelude::*;
+use crate::state::allocation::{ArmCommitment, PendingDraw};
+use crate::state::dataset::MAX_RESPONSE_URI_LEN;
+use crate::state::dispute::DISPUTE_FILING_WINDOW;
+use crate::state::eligibility::MAX_DISCLOSURES;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum EnrollmentStatus {
+    Enrolled,
+    Completed,
+    Withdrawn,
+    Removed,
+    /// Missed the completion deadline
+    Expired,
+    /// Participant claimed completion; awaiting the researcher or the confirmation window
+    CompletionClaimed,
+    /// Researcher rejected the claim; the reward is held until the dispute is settled
+    Disputed,
+}
+
+/// Why an enrollment ended before completion
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ExitReason {
+    ParticipantRequest,
+    AdverseEvent,
+    ScheduleConflict,
+    LostToFollowUp,
+    NonCompliance,
+    NoLongerEligible,
+    Other,
+    DeadlinePassed,
+    /// The guardian withdrew consent or the participant withdrew assent
+    ConsentWithdrawn,
+}
+
+impl ExitReason {
+    /// Set by the program itself, never chosen by whoever ends the enrollment
+    pub fn is_reserved(&self) -> bool {
+        matches!(self, ExitReason::DeadlinePassed | ExitReason::ConsentWithdrawn)
+    }
+}
+
+/// A participant's membership in a single study.
+///
+/// Seeds: `[b"enrollment", study, participant]`, so a participant can only
+/// ever hold one enrollment per study.
+#[account]
+#[derive(InitSpace)]
+pub struct Enrollment {
+    pub study: Pubkey,
+    pub participant: Pubkey,
+    pub status: EnrollmentStatus,
+    pub joined_at: i64,
+    pub progress: u8,
+    pub last_update: i64,
+    pub completed_at: Option<i64>,
+    pub claimed_at: Option<i64>,
+    /// Must complete before this, see `StudySchedule::deadline_from`
+    pub deadline: Option<i64>,
+    pub reward_paid: u64,
+    /// Set when the participant withdrew or was removed
+    pub exit_reason: Option<ExitReason>,
+    pub exited_at: Option<i64>,
+    /// Indices into `Study::quotas` this participant is counted in, at most one per attribute kind
+    #[max_len(MAX_DISCLOSURES)]
+    pub strata: Vec<u8>,
+    /// Index into `Allocation::arms` for randomized studies; unset while blinded
+    pub arm: Option<u8>,
+    /// Blinded studies store only this until unblinding
+    pub arm_commitment: Option<ArmCommitment>,
+    /// Unblinded studies hold this until `draw_arm` settles it into `arm`
+    pub pending_draw: Option<PendingDraw>,
+    /// Hash of the participant's off-chain response payload, see `dataset_leaf`
+    pub response_hash: Option<[u8; 32]>,
+    /// Where the payload is stored
+    #[max_len(MAX_RESPONSE_URI_LEN)]
+    pub response_uri: Option<String>,
+    pub responded_at: Option<i64>,
+    /// Joined under a `StudyConsent`, which must then be passed whenever the enrollment ends
+    pub consented: bool,
+    pub bump: u8,
+}
+
+impl Enrollment {
+    #[allow(clippy::too_many_arguments)]
+    pub fn create(
+        &mut self,
+        study: Pubkey,
+        participant: Pubkey,
+        joined_at: i64,
+        deadline: Option<i64>,
+        strata: Vec<u8>,
+        arm_commitment: Option<ArmCommitment>,
+        pending_draw: Option<PendingDraw>,
+        bump: u8,
+    ) -> Result<()> {
+        self.study = study;
+        self.participant = participant;
+        self.status = EnrollmentStatus::Enrolled;
+        self.joined_at = joined_at;
+        self.deadline = deadline;
+        self.progress = 0;
+        self.last_update = self.joined_at;
+        self.completed_at = None;
+        self.claimed_at = None;
+        self.reward_paid = 0;
+        self.exit_reason = None;
+        self.exited_at = None;
+        self.strata = strata;
+        self.arm = None;
+        self.arm_commitment = arm_commitment;
+        self.pending_draw = pending_draw;
+        self.response_hash = None;
+        self.response_uri = None;
+        self.responded_at = None;
+        self.consented = false;
+        self.bump = bump;
+        Ok(())
+    }
+    pub fn require_enrolled(&self) -> Result<()> {
+        require!(
+            self.status == EnrollmentStatus::Enrolled,
+            crate::error::RecruSearchError::NotEnrolled
+        );
+        Ok(())
+    }
+    pub fn is_overdue(&self, now: i64) -> bool {
+        self.deadline.is_some_and(|deadline| now >= deadline)
+    }
+    /// Enrolled and still inside the completion deadline
+    pub fn require_active(&self) -> Result<()> {
+        self.require_enrolled()?;
+        require!(
+            !self.is_overdue(Clock::get()?.unix_timestamp),
+            crate::error::RecruSearchError::DeadlinePassed
+        );
+        Ok(())
+    }
+    pub fn update_progress(&mut self, progress: u8) -> Result<()> {
+        self.require_active()?;
+        require!(progress <= 100, crate::error::RecruSearchError::InvalidProgress);
+        require!(progress >= self.progress, crate::error::RecruSearchError::ProgressRegression);
+        self.progress = progress;
+        self.last_update = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+    /// Records (or replaces) the response commitment, returning whether it is the first
+    pub fn record_response(&mut self, response_hash: [u8; 32], uri: String) -> Result<bool> {
+        self.require_active()?;
+        require!(uri.len() <= MAX_RESPONSE_URI_LEN, crate::error::RecruSearchError::InputTooLong);
+        let first = self.response_hash.is_none();
+        self.response_hash = Some(response_hash);
+        self.response_uri = Some(uri);
+        self.responded_at = Some(Clock::get()?.unix_timestamp);
+        Ok(first)
+    }
+    pub fn is_finished(&self) -> bool {
+        self.progress == 100
+    }
+    /// Participant's claim that they finished, starting the confirmation window
+    pub fn claim_completion(&mut self, now: i64) -> Result<()> {
+        self.require_active()?;
+        require!(self.is_finished(), crate::error::RecruSearchError::StudyCompletionCriteriaNotMet);
+        self.status = EnrollmentStatus::CompletionClaimed;
+        self.claimed_at = Some(now);
+        self.last_update = now;
+        Ok(())
+    }
+    /// When an unanswered claim confirms itself
+    pub fn confirmation_deadline(&self, window: i64) -> Option<i64> {
+        self.claimed_at.map(|claimed| claimed.saturating_add(window))
+    }
+    pub fn require_claimed(&self) -> Result<()> {
+        require!(
+            self.status == EnrollmentStatus::CompletionClaimed,
+            crate::error::RecruSearchError::CompletionNotClaimed
+        );
+        Ok(())
+    }
+    /// Researcher's rejection, only possible while the window is still open
+    pub fn reject_completion(&mut self, now: i64, window: i64) -> Result<()> {
+        self.require_claimed()?;
+        require!(
+            self.confirmation_deadline(window).is_some_and(|deadline| now < deadline),
+            crate::error::RecruSearchError::ConfirmationWindowClosed
+        );
+        self.status = EnrollmentStatus::Disputed;
+        self.last_update = now;
+        Ok(())
+    }
+    /// Checks an unanswered claim has outlived the window
+    pub fn require_confirmation_lapsed(&self, now: i64, window: i64) -> Result<()> {
+        self.require_claimed()?;
+        require!(
+            self.confirmation_deadline(window).is_some_and(|deadline| now >= deadline),
+            crate::error::RecruSearchError::ConfirmationWindowOpen
+        );
+        Ok(())
+    }
+    pub fn complete(&mut self, reward_paid: u64) -> Result<()> {
+        self.require_claimed()?;
+        self.status = EnrollmentStatus::Completed;
+        self.completed_at = Some(Clock::get()?.unix_timestamp);
+        self.reward_paid = reward_paid;
+        Ok(())
+    }
+    /// Checks a removal or expiry is recent enough for the participant to contest
+    pub fn require_contestable(&self, now: i64) -> Result<()> {
+        require!(
+            matches!(self.status, EnrollmentStatus::Removed | EnrollmentStatus::Expired),
+            crate::error::RecruSearchError::EnrollmentNotContestable
+        );
+        require!(
+            self.exited_at.is_some_and(|exited| now < exited.saturating_add(DISPUTE_FILING_WINDOW)),
+            crate::error::RecruSearchError::DisputeFilingClosed
+        );
+        Ok(())
+    }
+    /// Settles a disputed claim in the participant's favour, with `reward_paid` set by the ruling
+    pub fn uphold_completion(&mut self, reward_paid: u64, now: i64) -> Result<()> {
+        require!(
+            self.status == EnrollmentStatus::Disputed,
+            crate::error::RecruSearchError::DisputeNotOpen
+        );
+        self.status = EnrollmentStatus::Completed;
+        self.completed_at = Some(now);
+        self.last_update = now;
+        self.reward_paid = reward_paid;
+        Ok(())
+    }
+    /// Settles a disputed claim against the participant, ending the enrollment unpaid
+    pub fn overturn_completion(&mut self, now: i64) -> Result<()> {
+        require!(
+            self.status == EnrollmentStatus::Disputed,
+            crate::error::RecruSearchError::DisputeNotOpen
+        );
+        self.status = EnrollmentStatus::Removed;
+        self.exit_reason = Some(ExitReason::NonCompliance);
+        self.exited_at = Some(now);
+        self.last_update = now;
+        Ok(())
+    }
+    /// Adds an arbitrated award on top of what the participant was paid on exit
+    pub fn add_award(&mut self, award: u64) -> Result<()> {
+        self.reward_paid = self.reward_paid.checked_add(award)
+            .ok_or(crate::error::RecruSearchError::RewardOverflow)?;
+        Ok(())
+    }
+    /// Ends an active enrollment early as `Withdrawn` or `Removed`
+    pub fn end(&mut self, status: EnrollmentStatus, reason: ExitReason, reward_paid: u64) -> Result<()> {
+        self.require_enrolled()?;
+        require!(
+            matches!(status, EnrollmentStatus::Withdrawn | EnrollmentStatus::Removed),
+            crate::error::RecruSearchError::InvalidParticipantStatus
+        );
+        let now = Clock::get()?.unix_timestamp;
+        self.status = status;
+        self.exit_reason = Some(reason);
+        self.exited_at = Some(now);
+        self.last_update = now;
+        self.reward_paid = reward_paid;
+        Ok(())
+    }
+    /// Marks an overdue enrollment as `Expired`
+    pub fn expire(&mut self, now: i64) -> Result<()> {
+        self.require_enrolled()?;
+        require!(self.is_overdue(now), crate::error::RecruSearchError::DeadlineNotReached);
+        self.status = EnrollmentStatus::Expired;
+        self.exit_reason = Some(ExitReason::DeadlinePassed);
+        self.exited_at = Some(now);
+        self.last_update = now;
+        Ok(())
+    }
+}
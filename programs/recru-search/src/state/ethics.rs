@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+use crate::error::RecruSearchError;
+use crate::state::admin::Admin;
+
+/// A review board's approval of a study protocol.
+///
+/// Seeds: `[b"ethics_approval", study]`. Signed by a board key registered in
+/// `Admin::review_boards`; only that board may renew or revoke it.
+#[account]
+#[derive(InitSpace)]
+pub struct EthicsApproval {
+    pub study: Pubkey,
+    pub board: Pubkey,
+    pub protocol_number_hash: [u8; 32],
+    pub approval_document_hash: [u8; 32],
+    pub valid_from: i64,
+    pub valid_until: i64,
+    pub granted_at: i64,
+    pub revoked_at: Option<i64>,
+    pub bump: u8,
+}
+
+impl EthicsApproval {
+    #[allow(clippy::too_many_arguments)]
+    pub fn grant(
+        &mut self,
+        study: Pubkey,
+        board: Pubkey,
+        protocol_number_hash: [u8; 32],
+        approval_document_hash: [u8; 32],
+        valid_from: i64,
+        valid_until: i64,
+        bump: u8,
+    ) -> Result<()> {
+        // A fresh account has no board yet; an existing one can only be renewed by its board
+        require!(
+            self.board == Pubkey::default() || self.board == board,
+            RecruSearchError::UnauthorizedReviewBoard
+        );
+        let now = Clock::get()?.unix_timestamp;
+        require!(valid_from < valid_until && valid_until > now, RecruSearchError::InvalidEthicsApproval);
+        self.study = study;
+        self.board = board;
+        self.protocol_number_hash = protocol_number_hash;
+        self.approval_document_hash = approval_document_hash;
+        self.valid_from = valid_from;
+        self.valid_until = valid_until;
+        self.granted_at = now;
+        self.revoked_at = None;
+        self.bump = bump;
+        Ok(())
+    }
+    pub fn revoke(&mut self, now: i64) -> Result<()> {
+        require!(self.revoked_at.is_none(), RecruSearchError::EthicsApprovalInactive);
+        self.revoked_at = Some(now);
+        Ok(())
+    }
+    /// Deregistering a board lapses every approval it issued
+    pub fn is_valid(&self, admin: &Admin, now: i64) -> bool {
+        self.revoked_at.is_none()
+            && self.valid_from <= now
+            && now < self.valid_until
+            && admin.is_review_board(&self.board)
+    }
+    pub fn require_valid(&self, admin: &Admin) -> Result<()> {
+        require!(
+            self.is_valid(admin, Clock::get()?.unix_timestamp),
+            RecruSearchError::EthicsApprovalInactive
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_FEEDBACK_LEN: usize = 500;
+
+/// A completed participant's rating of a study.
+///
+/// Seeds: `[b"feedback", study, participant]`, so each participant can rate
+/// a study once.
+#[account]
+#[derive(InitSpace)]
+pub struct Feedback {
+    pub study: Pubkey,
+    pub participant: Pubkey,
+    pub rating: u8,
+    #[max_len(MAX_FEEDBACK_LEN)]
+    pub comment: Option<String>,
+    pub submitted_at: i64,
+    pub bump: u8,
+}
+
+impl Feedback {
+    pub fn create(
+        &mut self,
+        study: Pubkey,
+        participant: Pubkey,
+        rating: u8,
+        comment: Option<String>,
+        bump: u8,
+    ) -> Result<()> {
+        require!((1..=5).contains(&rating), crate::error::RecruSearchError::InvalidRating);
+        if let Some(comment) = &comment {
+            require!(comment.len() <= MAX_FEEDBACK_LEN, crate::error::RecruSearchError::FeedbackTooLong);
+        }
+        self.study = study;
+        self.participant = participant;
+        self.rating = rating;
+        self.comment = comment;
+        self.submitted_at = Clock::get()?.unix_timestamp;
+        self.bump = bump;
+        Ok(())
+    }
+}
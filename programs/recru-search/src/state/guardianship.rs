@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+/// Who ended a guardian-consented participation
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum GuardianshipParty {
+    Guardian,
+    Participant,
+}
+
+/// Links a minor or dependent adult to the legally authorised representative who
+/// consents on their behalf. Consent for studies flagged `requires_guardian_consent`
+/// needs the guardian's signature plus the participant's own assent, both recorded on
+/// that study's `StudyConsent`.
+///
+/// Seeds: `[b"guardianship", participant]`
+#[account]
+#[derive(InitSpace)]
+pub struct Guardianship {
+    pub participant: Pubkey,
+    pub guardian: Pubkey,
+    /// Admin or identity attester who vouched for the relationship
+    pub approved_by: Pubkey,
+    pub established_at: i64,
+    pub bump: u8,
+}
+
+impl Guardianship {
+    pub fn establish(&mut self, participant: Pubkey, guardian: Pubkey, approved_by: Pubkey, now: i64, bump: u8) {
+        self.participant = participant;
+        self.guardian = guardian;
+        self.approved_by = approved_by;
+        self.established_at = now;
+        self.bump = bump;
+    }
+}
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+
+/// Binds one deduplicated real-world identity to one `Participant`.
+///
+/// Seeds: `[b"identity", identity_hash]`. The attester derives `identity_hash` from
+/// the person's documents off-chain and signs `identity_hash || participant` in an
+/// Ed25519 program instruction placed immediately before `attest_identity`. Because
+/// the seeds are the hash alone, a second keypair presenting the same identity lands
+/// on the existing record and is refused.
+#[account]
+#[derive(InitSpace)]
+pub struct IdentityRecord {
+    pub identity_hash: [u8; 32],
+    pub participant: Pubkey,
+    pub attester: Pubkey,
+    pub attested_at: i64,
+    /// Revoked records stay, so the identity cannot be re-attested to a new account
+    pub revoked_at: Option<i64>,
+    pub bump: u8,
+}
+
+impl IdentityRecord {
+    pub fn message(identity_hash: &[u8; 32], participant: &Pubkey) -> Vec<u8> {
+        let mut message = Vec::with_capacity(32 + 32);
+        message.extend_from_slice(identity_hash);
+        message.extend_from_slice(participant.as_ref());
+        message
+    }
+
+    /// Fills a new record, or re-attests the same participant after they erased and
+    /// re-registered under the same authority
+    pub fn bind(
+        &mut self,
+        identity_hash: [u8; 32],
+        participant: Pubkey,
+        attester: Pubkey,
+        now: i64,
+        bump: u8,
+    ) -> Result<()> {
+        require!(
+            self.participant == Pubkey::default() || self.participant == participant,
+            crate::error::RecruSearchError::IdentityAlreadyBound
+        );
+        require!(self.revoked_at.is_none(), crate::error::RecruSearchError::IdentityRevoked);
+        self.identity_hash = identity_hash;
+        self.participant = participant;
+        self.attester = attester;
+        self.attested_at = now;
+        self.bump = bump;
+        Ok(())
+    }
+
+    pub fn revoke(&mut self, now: i64) -> Result<()> {
+        require!(self.revoked_at.is_none(), crate::error::RecruSearchError::IdentityRevoked);
+        self.revoked_at = Some(now);
+        Ok(())
+    }
+}
@@ -4,4 +4,18 @@ pub mod wallet;
 pub mod admin;
 pub mod researcher;
 pub mod consent;
-pub mod privacy; 
\ No newline at end of file
+pub mod privacy;
+pub mod enrollment;
+pub mod feedback;
+pub mod eligibility;
+pub mod attestation;
+pub mod waitlist;
+pub mod ethics;
+pub mod team;
+pub mod quota;
+pub mod allocation;
+pub mod dataset;
+pub mod dispute;
+pub mod reputation;
+pub mod identity;
+pub mod guardianship;
@@ -1,79 +1,74 @@
 use anchor_lang::prelude::*;
+use crate::state::eligibility::MAX_ATTRIBUTE_LEN;
+use crate::state::reputation::{Reputation, ReputationEvent};
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub const MAX_INTERESTS: usize = 8;
+
+// Per-study history lives in the Enrollment and Feedback PDAs rather than in the profile
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
 pub struct ParticipantProfile {
-    pub age_group: String,
-    pub gender: String,
-    pub region: String,
+    #[max_len(MAX_INTERESTS, MAX_ATTRIBUTE_LEN)]
     pub interests: Vec<String>,
     pub is_anonymous: bool,
-    pub completed_studies: Vec<CompletedStudy>,
-    pub active_studies: Vec<ActiveStudy>,
-    pub reward_history: Vec<RewardRecord>,
-    pub reputation_score: u32,
 }
 
+/// Seeds: `[b"participant", authority]`
 #[account]
+#[derive(InitSpace)]
 pub struct Participant {
     pub authority: Pubkey,
     pub profile: ParticipantProfile,
-    pub eligibility_proof: String,
+    /// Merkle root over salted screening attributes (see `AttributeDisclosure`)
+    pub attributes_root: [u8; 32],
     pub registered_at: i64,
     pub suspended: bool,
     pub banned: bool,
     pub active_studies: u32,
     pub completed_studies: u32,
-    pub has_active_consent: bool,
+    /// Live `StudyConsent` records across every study
+    pub active_consents: u32,
     pub consent_issued_at: i64,
     pub consent_revoked_at: Option<i64>,
     pub wallet: Option<Pubkey>,
-    pub reputation_score: u32,
+    pub reputation: Reputation,
+    /// `IdentityRecord` vouching this is the only account of a unique person
+    pub identity: Option<Pubkey>,
+    /// Representative whose signature consent issuance and revocation need, see `Guardianship`
+    pub guardian: Option<Pubkey>,
+    /// Disputes over this participant's enrollments awaiting a ruling
+    pub open_disputes: u32,
     pub last_activity: i64,
-}
-
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct CompletedStudy {
-    pub study_id: Pubkey,
-    pub completion_date: i64,
-    pub reward_amount: u64,
-    pub rating: Option<u8>,
-    pub feedback: Option<String>,
-}
-
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct ActiveStudy {
-    pub study_id: Pubkey,
-    pub start_date: i64,
-    pub progress: u8,
-    pub last_update: i64,
-}
-
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct RewardRecord {
-    pub study_id: Pubkey,
-    pub amount: u64,
-    pub timestamp: i64,
-    pub transaction_id: String,
+    pub bump: u8,
 }
 
 impl Participant {
     pub fn create(
         &mut self,
         authority: Pubkey,
-        eligibility_proof: String,
+        attributes_root: [u8; 32],
+        bump: u8,
     ) -> Result<()> {
         self.authority = authority;
-        self.eligibility_proof = eligibility_proof;
+        self.bump = bump;
+        self.attributes_root = attributes_root;
         self.registered_at = Clock::get()?.unix_timestamp;
+        self.reputation = Reputation::new(self.registered_at);
+        self.identity = None;
+        self.guardian = None;
         self.active_studies = 0;
         self.completed_studies = 0;
-        self.has_active_consent = false;
+        self.active_consents = 0;
         self.consent_issued_at = 0;
         self.consent_revoked_at = None;
         Ok(())
     }
-    pub fn update_profile(&mut self, profile: ParticipantProfile) -> Result<()> {
-        self.profile = profile;
+    pub fn update_interests(&mut self, interests: Vec<String>) -> Result<()> {
+        require!(
+            interests.len() <= MAX_INTERESTS
+                && interests.iter().all(|i| i.len() <= MAX_ATTRIBUTE_LEN),
+            crate::error::RecruSearchError::InvalidProfileAttribute
+        );
+        self.profile.interests = interests;
         Ok(())
     }
     pub fn increment_active_studies(&mut self) -> Result<()> {
@@ -91,38 +86,40 @@ impl Participant {
             .ok_or(crate::error::RecruSearchError::InvalidParticipantStatus)?;
         Ok(())
     }
-    pub fn update_consent_status(&mut self, has_consent: bool) -> Result<()> {
-        self.has_active_consent = has_consent;
-        if has_consent {
-            self.consent_issued_at = Clock::get()?.unix_timestamp;
-            self.consent_revoked_at = None;
-        } else {
-            self.consent_revoked_at = Some(Clock::get()?.unix_timestamp);
-        }
+    pub fn open_dispute(&mut self) -> Result<()> {
+        self.open_disputes = self.open_disputes.checked_add(1)
+            .ok_or(crate::error::RecruSearchError::ArithmeticOverflow)?;
         Ok(())
     }
-}
-
-impl ParticipantProfile {
-    pub fn calculate_reputation_score(&self) -> u32 {
-        let base_score = self.completed_studies.len() as u32 * 10;
-        let rating_bonus: u32 = self.completed_studies
-            .iter()
-            .filter_map(|study| study.rating)
-            .map(|rating| rating as u32)
-            .sum();
-        base_score + rating_bonus
+    pub fn close_dispute(&mut self) -> Result<()> {
+        self.open_disputes = self.open_disputes.checked_sub(1)
+            .ok_or(crate::error::RecruSearchError::ArithmeticOverflow)?;
+        Ok(())
+    }
+    /// Erasure would strand enrollments still in flight and rewards frozen in disputes, and
+    /// must not let a banned or suspended participant come back under a clean profile
+    pub fn require_erasable(&self) -> Result<()> {
+        require!(!self.banned && !self.suspended, crate::error::RecruSearchError::InvalidParticipantStatus);
+        require!(self.active_studies == 0, crate::error::RecruSearchError::ActiveEnrollmentsRemain);
+        require!(self.open_disputes == 0, crate::error::RecruSearchError::OpenDisputesRemain);
+        require!(self.active_consents == 0, crate::error::RecruSearchError::ActiveConsentsRemain);
+        Ok(())
+    }
+    pub fn record_reputation(&mut self, event: ReputationEvent) -> Result<()> {
+        self.reputation.record(event, Clock::get()?.unix_timestamp);
+        Ok(())
+    }
+    pub fn add_consent(&mut self) -> Result<()> {
+        self.active_consents = self.active_consents.checked_add(1)
+            .ok_or(crate::error::RecruSearchError::ArithmeticOverflow)?;
+        self.consent_issued_at = Clock::get()?.unix_timestamp;
+        Ok(())
     }
-    pub fn is_eligible_for_study(&self, _study: &crate::state::study::Study) -> bool {
-        // Basic eligibility criteria
-        let criteria = [
-            // Age group check
-            true, // Replace with actual age group check when implemented
-            // Reputation score check - assuming a minimum reputation score requirement
-            // This is a placeholder as the Study struct doesn't have min_reputation_score
-            self.reputation_score >= 10, // Use a default minimum score of 10
-        ];
-        criteria.iter().all(|&check| check == true)
+    pub fn remove_consent(&mut self) -> Result<()> {
+        self.active_consents = self.active_consents.checked_sub(1)
+            .ok_or(crate::error::RecruSearchError::NoActiveConsent)?;
+        self.consent_revoked_at = Some(Clock::get()?.unix_timestamp);
+        Ok(())
     }
 }
 
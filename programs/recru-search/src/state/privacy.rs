@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
 use std::collections::HashMap;
+use crate::state::reputation::Reputation;
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct PrivacyManager {
@@ -36,14 +38,39 @@ pub enum DataAccessLevel {
     Full,
 }
 
+/// `P` followed by the hex of the first eight bytes of `sha256(salt || participant)`
+pub const PSEUDONYM_LEN: usize = 17;
+
+/// Without the salt a pseudonym cannot be matched against a list of participant keys
+pub fn pseudonym(participant: &Pubkey, salt: &[u8; 32]) -> String {
+    let digest = hashv(&[salt, participant.as_ref()]).to_bytes();
+    let hex = digest[..8].iter().fold(String::new(), |mut output, byte| {
+        output.push_str(&format!("{:02x}", byte));
+        output
+    });
+    format!("P{}", hex)
+}
+
+/// What survives a participant's erasure. The pseudonym is salted by the participant;
+/// handing the salt to a researcher holding their key in a dataset lets the researcher
+/// show the deletion request was honoured.
+///
+/// Seeds: `[b"tombstone", participant]`
+#[account]
+#[derive(InitSpace)]
+pub struct ErasureTombstone {
+    #[max_len(PSEUDONYM_LEN)]
+    pub pseudonym: String,
+    /// Most recent erasure; an authority can register and erase again
+    pub erased_at: i64,
+    /// Carried over if the authority registers again, so erasure does not wipe a poor score
+    pub reputation: Reputation,
+    pub bump: u8,
+}
+
 impl PrivacyManager {
     pub fn generate_pseudonym(&mut self) -> String {
-        let bytes = &self.participant.to_bytes()[0..8];
-        let hex = bytes.iter().fold(String::new(), |mut output, byte| {
-            output.push_str(&format!("{:02x}", byte));
-            output
-        });
-        format!("P{}", hex)
+        pseudonym(&self.participant, &self.master_key)
     }
     pub fn grant_access(&mut self, study_id: Pubkey, _researcher_pubkey: Pubkey, access_level: DataAccessLevel) -> Result<()> {
         let data_key = DataKey {
@@ -64,7 +91,7 @@ impl PrivacyManager {
                 });
                 hex
             },
-            access_level: access_level, // Fix the moved value issue with access_level
+            access_level,
             granted_at: Clock::get()?.unix_timestamp,
             expires_at: None,
             is_revoked: false,
@@ -88,13 +115,13 @@ impl PrivacyManager {
                 return false;
             }
             // Since we've added Copy trait to DataAccessLevel, we can now use it directly
-            match (data_key.access_level, required_level) {
-                (DataAccessLevel::Full, _) => true,
-                (DataAccessLevel::Extended, DataAccessLevel::Extended) => true,
-                (DataAccessLevel::Extended, DataAccessLevel::Basic) => true,
-                (DataAccessLevel::Basic, DataAccessLevel::Basic) => true,
-                _ => false,
-            }
+            matches!(
+                (data_key.access_level, required_level),
+                (DataAccessLevel::Full, _)
+                    | (DataAccessLevel::Extended, DataAccessLevel::Extended)
+                    | (DataAccessLevel::Extended, DataAccessLevel::Basic)
+                    | (DataAccessLevel::Basic, DataAccessLevel::Basic)
+            )
         } else {
             false
         }
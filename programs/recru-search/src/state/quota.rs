@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+use crate::state::eligibility::{AttributeKind, MAX_ATTRIBUTE_LEN};
+
+pub const MAX_QUOTAS: usize = 8;
+
+/// Bounds on how many enrolled participants may share one attribute value,
+/// e.g. at most 40 with gender "F" or at least 20 from region "EU".
+///
+/// Participants whose value matches no quota of a constrained kind still join,
+/// but only into slots the quotas' minimums leave free.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct StratumQuota {
+    pub kind: AttributeKind,
+    #[max_len(MAX_ATTRIBUTE_LEN)]
+    pub value: String,
+    /// Slots held back from other strata of the same kind until reached
+    pub min_count: u32,
+    pub max_count: u32,
+    /// Currently enrolled or completed participants in this stratum
+    pub enrolled: u32,
+}
+
+impl StratumQuota {
+    /// Slots still needed to reach `min_count`
+    pub fn shortfall(&self) -> u32 {
+        self.min_count.saturating_sub(self.enrolled)
+    }
+    pub fn is_full(&self) -> bool {
+        self.enrolled >= self.max_count
+    }
+}
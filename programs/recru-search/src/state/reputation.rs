@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+
+/// Scores are fixed-point with three decimal places: 50_000 is 50.0
+pub const REPUTATION_SCALE: u32 = 1_000;
+/// Where new accounts start and where idle scores drift back to
+pub const NEUTRAL_REPUTATION: u32 = 50 * REPUTATION_SCALE;
+pub const MAX_REPUTATION: u32 = 100 * REPUTATION_SCALE;
+/// Time for a score's distance from neutral to halve without new activity
+pub const REPUTATION_HALF_LIFE: i64 = 180 * 24 * 60 * 60;
+
+/// Something that moves a participant's or researcher's reputation
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ReputationEvent {
+    Completed,
+    /// A 1-5 star rating; 3 is neutral
+    RatingReceived(u8),
+    /// Let the completion deadline pass
+    NoShow,
+    /// Withdrew before finishing
+    Abandoned,
+    DisputeWon,
+    DisputeLost,
+}
+
+impl ReputationEvent {
+    /// Change in `REPUTATION_SCALE` units
+    pub fn delta(&self) -> i64 {
+        let points: i64 = match *self {
+            ReputationEvent::Completed => 2,
+            ReputationEvent::RatingReceived(rating) => rating.clamp(1, 5) as i64 - 3,
+            ReputationEvent::NoShow => -5,
+            ReputationEvent::Abandoned => -2,
+            ReputationEvent::DisputeWon => 5,
+            ReputationEvent::DisputeLost => -5,
+        };
+        points * REPUTATION_SCALE as i64
+    }
+}
+
+/// A decaying score. `score` is only exact as of `updated_at`; read it through `current`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct Reputation {
+    pub score: u32,
+    pub updated_at: i64,
+}
+
+impl Reputation {
+    pub fn new(now: i64) -> Self {
+        Self { score: NEUTRAL_REPUTATION, updated_at: now }
+    }
+    /// Score at `now`, its distance from neutral halved for every elapsed half-life and
+    /// interpolated linearly in between
+    pub fn current(&self, now: i64) -> u32 {
+        let elapsed = now.saturating_sub(self.updated_at).max(0);
+        let halvings = elapsed / REPUTATION_HALF_LIFE;
+        if halvings >= 32 {
+            return NEUTRAL_REPUTATION;
+        }
+        let deviation = (self.score as i64 - NEUTRAL_REPUTATION as i64) / (1 << halvings);
+        let partial = elapsed % REPUTATION_HALF_LIFE;
+        let decayed = deviation - deviation * partial / (2 * REPUTATION_HALF_LIFE);
+        (NEUTRAL_REPUTATION as i64 + decayed) as u32
+    }
+    /// Applies pending decay, then the event
+    pub fn record(&mut self, event: ReputationEvent, now: i64) {
+        let score = self.current(now) as i64 + event.delta();
+        self.score = score.clamp(0, MAX_REPUTATION as i64) as u32;
+        self.updated_at = now;
+    }
+}
@@ -1,16 +1,25 @@
 use anchor_lang::prelude::*;
+use crate::state::reputation::{Reputation, ReputationEvent};
 
+pub const MAX_INSTITUTION_LEN: usize = 100;
+pub const MAX_CREDENTIALS_HASH_LEN: usize = 64;
+
+/// Seeds: `[b"researcher", authority]`
 #[account]
+#[derive(InitSpace)]
 pub struct Researcher {
     pub authority: Pubkey,
+    #[max_len(MAX_INSTITUTION_LEN)]
     pub institution: String,
+    #[max_len(MAX_CREDENTIALS_HASH_LEN)]
     pub credentials_hash: String,
     pub is_verified: bool,
     pub registered_at: i64,
     pub studies_created: u32,
     pub active_studies: u32,
     pub total_participants: u32,
-    pub reputation_score: u32,
+    pub reputation: Reputation,
+    pub bump: u8,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -21,7 +30,6 @@ pub struct ResearcherProfile {
     pub created_studies: Vec<Pubkey>,
     pub active_studies: Vec<Pubkey>,
     pub completed_studies: Vec<Pubkey>,
-    pub reputation_score: u32,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -46,9 +54,16 @@ impl Researcher {
         authority: Pubkey,
         institution: String,
         credentials_hash: String,
+        bump: u8,
     ) -> Result<()> {
-        require!(institution.len() > 0, crate::error::RecruSearchError::InvalidInstitutionName);
-        require!(credentials_hash.len() > 0, crate::error::RecruSearchError::InvalidCredentials);
+        require!(
+            !institution.is_empty() && institution.len() <= MAX_INSTITUTION_LEN,
+            crate::error::RecruSearchError::InvalidInstitutionName
+        );
+        require!(
+            !credentials_hash.is_empty() && credentials_hash.len() <= MAX_CREDENTIALS_HASH_LEN,
+            crate::error::RecruSearchError::InvalidCredentials
+        );
         self.authority = authority;
         self.institution = institution;
         self.credentials_hash = credentials_hash;
@@ -57,7 +72,8 @@ impl Researcher {
         self.studies_created = 0;
         self.active_studies = 0;
         self.total_participants = 0;
-        self.reputation_score = 0;
+        self.reputation = Reputation::new(self.registered_at);
+        self.bump = bump;
         Ok(())
     }
     pub fn increment_studies_created(&mut self) -> Result<()> {
@@ -74,9 +90,6 @@ impl Researcher {
             self.active_studies = self.active_studies.checked_sub((-delta) as u32)
                 .ok_or(crate::error::RecruSearchError::InvalidStudyParameters)?;
         }
-        self.update_reputation_score(
-            self.reputation_score + (delta.abs() as u32 * 10)
-        )?;
         Ok(())
     }
     pub fn update_total_participants(&mut self, delta: i32) -> Result<()> {
@@ -89,22 +102,13 @@ impl Researcher {
         }
         Ok(())
     }
-    pub fn update_reputation_score(&mut self, score: u32) -> Result<()> {
-        self.reputation_score = score;
+    pub fn record_reputation(&mut self, event: ReputationEvent) -> Result<()> {
+        self.reputation.record(event, Clock::get()?.unix_timestamp);
         Ok(())
     }
 }
 
 impl ResearcherProfile {
-    pub fn can_create_study(&self) -> bool {
-        self.is_verified && self.reputation_score >= 50
-    }
-    pub fn update_reputation(&mut self, study: &crate::state::study::Study) {
-        let base_score = 10;
-        let completion_bonus = (study.analytics.completion_rate * 20.0) as u32;
-        let rating_bonus = (study.analytics.average_rating * 10.0) as u32;
-        self.reputation_score += base_score + completion_bonus + rating_bonus;
-    }
     pub fn verify_credentials(&mut self) -> bool {
         let valid_credentials = self.credentials.iter()
             .filter(|cred| {
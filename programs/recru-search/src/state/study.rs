@@ -11,7 +11,7 @@ pub struct StudyFilter {
     pub duration: Option<StudyDuration>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, InitSpace)]
 pub enum StudyType {
     Survey,
     Interview,
@@ -26,34 +26,166 @@ pub struct StudyDuration {
     pub max_days: u32,
 }
 
+/// When a study recruits and how long participants have to finish.
+/// Unset fields impose no limit.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, InitSpace)]
+pub struct StudySchedule {
+    pub recruitment_opens_at: Option<i64>,
+    pub recruitment_closes_at: Option<i64>,
+    /// Seconds each participant has to complete, counted from joining
+    pub completion_window: Option<i64>,
+    /// After this the study can be cranked to Closed
+    pub ends_at: Option<i64>,
+}
+
+impl StudySchedule {
+    pub fn validate(&self, now: i64) -> Result<()> {
+        if let (Some(opens), Some(closes)) = (self.recruitment_opens_at, self.recruitment_closes_at) {
+            require!(opens < closes, crate::error::RecruSearchError::InvalidSchedule);
+        }
+        if let Some(closes) = self.recruitment_closes_at {
+            require!(closes > now, crate::error::RecruSearchError::InvalidSchedule);
+        }
+        if let Some(ends) = self.ends_at {
+            require!(ends > now, crate::error::RecruSearchError::InvalidSchedule);
+            for bound in [self.recruitment_opens_at, self.recruitment_closes_at].into_iter().flatten() {
+                require!(bound <= ends, crate::error::RecruSearchError::InvalidSchedule);
+            }
+        }
+        if let Some(window) = self.completion_window {
+            require!(window > 0, crate::error::RecruSearchError::InvalidSchedule);
+        }
+        Ok(())
+    }
+    pub fn require_recruiting(&self, now: i64) -> Result<()> {
+        if let Some(opens) = self.recruitment_opens_at {
+            require!(now >= opens, crate::error::RecruSearchError::RecruitmentNotOpen);
+        }
+        if let Some(closes) = self.recruitment_closes_at {
+            require!(now < closes, crate::error::RecruSearchError::RecruitmentClosed);
+        }
+        Ok(())
+    }
+    /// Completion deadline for a participant joining at `joined_at`, never past the study's end
+    pub fn deadline_from(&self, joined_at: i64) -> Option<i64> {
+        let window_end = self.completion_window.map(|w| joined_at.saturating_add(w));
+        match (window_end, self.ends_at) {
+            (Some(deadline), Some(ends)) => Some(deadline.min(ends)),
+            (deadline, ends) => deadline.or(ends),
+        }
+    }
+    pub fn has_ended(&self, now: i64) -> bool {
+        self.ends_at.is_some_and(|ends| now >= ends)
+    }
+}
+
 // Import the StudyStatus enum
-use crate::state::admin::StudyStatus;
+use crate::state::admin::{StatusAuthority, StudyStatus};
+use crate::state::consent::MAX_CONSENT_VERSION_LEN;
+use crate::state::eligibility::{disclosed_value, AttributeDisclosure, AttributeKind, EligibilityCriteria, MAX_ATTRIBUTE_LEN};
+use crate::state::quota::{StratumQuota, MAX_QUOTAS};
+
+pub const MAX_TITLE_LEN: usize = 100;
+pub const MAX_DESCRIPTION_LEN: usize = 500;
+pub const MAX_CRITERIA_HASH_LEN: usize = 100;
+pub const MAX_SESSION_SLOTS: usize = 16;
+/// How long a researcher has to answer a completion claim unless the study sets its own
+pub const DEFAULT_CONFIRMATION_WINDOW: i64 = 7 * 24 * 60 * 60;
 
+/// A bookable interview session
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct SessionSlot {
+    pub starts_at: i64,
+    pub duration_minutes: u16,
+    pub capacity: u8,
+}
+
+/// Seeds: `[b"study", researcher, index]`, where `index` is the researcher's
+/// `studies_created` counter (little-endian u32) at creation time.
 #[account]
+#[derive(InitSpace)]
 pub struct Study {
     pub authority: Pubkey,
     pub status: StudyStatus,
+    #[max_len(MAX_TITLE_LEN)]
     pub title: String,
+    #[max_len(MAX_DESCRIPTION_LEN)]
     pub description: String,
+    #[max_len(MAX_CRITERIA_HASH_LEN)]
     pub criteria_hash: String,
     pub reward_amount: u64,
     pub max_participants: u32,
     pub current_participants: u32,
     pub completed_participants: u32,
-    pub is_active: bool,
+    /// Slots held for waitlisted participants with a live offer
+    pub reserved_slots: u32,
+    /// Participants queued on the study's waitlist
+    pub waitlisted: u32,
     pub created_at: i64,
     pub study_type: StudyType,
     pub analytics: StudyAnalytics,
+    /// Mint the rewards are paid in
+    pub reward_mint: Pubkey,
+    /// Bump of the study's reward vault PDA (`[b"vault", study]`)
+    pub vault_bump: u8,
+    /// Structured screening rules, checked on join and consent
+    pub criteria: EligibilityCriteria,
+    /// Pay participants who leave early a share of the reward proportional to progress
+    pub prorate_on_exit: bool,
+    /// Consent needs a guardian's signature and the participant's assent, see `Guardianship`
+    pub requires_guardian_consent: bool,
+    /// Seconds the researcher has to confirm or reject a completion claim
+    pub confirmation_window: i64,
+    pub schedule: StudySchedule,
+    /// Current written-consent document; consent NFTs must be issued against this version
+    #[max_len(MAX_CONSENT_VERSION_LEN)]
+    pub consent_version: Option<String>,
+    pub consent_document_hash: [u8; 32],
+    /// Interview sessions participants can book
+    #[max_len(MAX_SESSION_SLOTS)]
+    pub session_slots: Vec<SessionSlot>,
+    /// Demographic balance rules, checked against disclosed attributes on join
+    #[max_len(MAX_QUOTAS)]
+    pub quotas: Vec<StratumQuota>,
+    /// Joins draw an arm from the study's `Allocation`
+    pub randomized: bool,
+    /// Enrollments that have submitted a response
+    pub responses: u32,
+    /// Merkle root over every submission, anchored at close (see `dataset_leaf`)
+    pub dataset_root: Option<[u8; 32]>,
+    /// Bumped each time the researcher re-anchors a corrected dataset
+    pub dataset_version: u32,
+    /// Rewards held by participant-opened disputes over enrollments that no longer hold a slot
+    pub frozen_rewards: u64,
+    /// Enrollments with a completion claimed but not yet settled, including rejected
+    /// claims awaiting a ruling
+    pub pending_claims: u32,
+    /// Status the study was suspended from; an admin can only resume it there
+    pub suspended_from: Option<StudyStatus>,
+    /// Position in the researcher's `studies_created` sequence, part of the seeds
+    pub index: u32,
+    pub bump: u8,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+/// Fixed-point scale for `StudyAnalytics::average_rating` (4.25 stars == 425)
+pub const RATING_SCALE: u64 = 100;
+/// Basis points, the scale for `StudyAnalytics::completion_rate`
+pub const BPS_SCALE: u64 = 10_000;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
 pub struct StudyAnalytics {
-    pub completion_rate: f32,
+    /// Completed / enrolled participants, in basis points
+    pub completion_rate: u32,
+    /// Everyone who ever enrolled, including those who later left
     pub total_participants: u32,
-    pub average_rating: f32,
+    /// Mean rating scaled by `RATING_SCALE`
+    pub average_rating: u32,
+    pub rating_count: u32,
+    pub rating_sum: u64,
 }
 
 impl Study {
+    #[allow(clippy::too_many_arguments)]
     pub fn create(
         &mut self,
         authority: Pubkey,
@@ -63,48 +195,262 @@ impl Study {
         reward_amount: u64,
         max_participants: u32,
         study_type: StudyType,
+        reward_mint: Pubkey,
+        vault_bump: u8,
+        index: u32,
+        bump: u8,
     ) -> Result<()> {
+        require!(reward_amount > 0, crate::error::RecruSearchError::InvalidRewardAmount);
+        require!(max_participants > 0, crate::error::RecruSearchError::InvalidStudyParameters);
+        require!(
+            !title.is_empty()
+                && title.len() <= MAX_TITLE_LEN
+                && description.len() <= MAX_DESCRIPTION_LEN
+                && criteria_hash.len() <= MAX_CRITERIA_HASH_LEN,
+            crate::error::RecruSearchError::InvalidStudyParameters
+        );
         self.authority = authority;
+        self.status = StudyStatus::Draft;
         self.title = title;
         self.description = description;
         self.criteria_hash = criteria_hash;
         self.reward_amount = reward_amount;
         self.max_participants = max_participants;
         self.current_participants = 0;
-        self.is_active = true;
+        self.reserved_slots = 0;
+        self.waitlisted = 0;
         self.created_at = Clock::get()?.unix_timestamp;
         self.completed_participants = 0;
         self.analytics = StudyAnalytics {
-            completion_rate: 0.0,
+            completion_rate: 0,
             total_participants: 0,
-            average_rating: 0.0,
+            average_rating: 0,
+            rating_count: 0,
+            rating_sum: 0,
         };
         self.study_type = study_type;
+        self.reward_mint = reward_mint;
+        self.vault_bump = vault_bump;
+        self.criteria = EligibilityCriteria::default();
+        self.prorate_on_exit = false;
+        self.confirmation_window = DEFAULT_CONFIRMATION_WINDOW;
+        self.schedule = StudySchedule::default();
+        self.consent_version = None;
+        self.consent_document_hash = [0; 32];
+        self.session_slots = Vec::new();
+        self.quotas = Vec::new();
+        self.randomized = false;
+        self.responses = 0;
+        self.dataset_root = None;
+        self.dataset_version = 0;
+        self.frozen_rewards = 0;
+        self.pending_claims = 0;
+        self.requires_guardian_consent = false;
+        self.index = index;
+        self.bump = bump;
+        Ok(())
+    }
+    /// Total amount the researcher escrows up front: one reward per slot
+    pub fn total_escrow(&self) -> Result<u64> {
+        self.reward_amount
+            .checked_mul(self.max_participants as u64)
+            .ok_or(crate::error::RecruSearchError::RewardOverflow.into())
+    }
+    /// Rewards still owed to participants who joined but have not completed, plus any
+    /// frozen by open disputes
+    pub fn outstanding_rewards(&self) -> Result<u64> {
+        let pending = self.current_participants
+            .checked_sub(self.completed_participants)
+            .ok_or(crate::error::RecruSearchError::InvalidParticipantStatus)?;
+        self.reward_amount
+            .checked_mul(pending as u64)
+            .and_then(|owed| owed.checked_add(self.frozen_rewards))
+            .ok_or(crate::error::RecruSearchError::RewardOverflow.into())
+    }
+    /// Rewards the vault must keep even after a cancellation: every claim made before it
+    /// and every frozen dispute
+    pub fn claimed_rewards(&self) -> Result<u64> {
+        self.reward_amount
+            .checked_mul(self.pending_claims as u64)
+            .and_then(|owed| owed.checked_add(self.frozen_rewards))
+            .ok_or(crate::error::RecruSearchError::RewardOverflow.into())
+    }
+    pub fn add_pending_claim(&mut self) -> Result<()> {
+        self.pending_claims = self.pending_claims.checked_add(1)
+            .ok_or(crate::error::RecruSearchError::ArithmeticOverflow)?;
+        Ok(())
+    }
+    pub fn settle_pending_claim(&mut self) -> Result<()> {
+        self.pending_claims = self.pending_claims.checked_sub(1)
+            .ok_or(crate::error::RecruSearchError::ArithmeticOverflow)?;
+        Ok(())
+    }
+    /// Holds `amount` in the vault until the dispute over it is resolved
+    pub fn freeze_reward(&mut self, amount: u64) -> Result<()> {
+        self.frozen_rewards = self.frozen_rewards.checked_add(amount)
+            .ok_or(crate::error::RecruSearchError::RewardOverflow)?;
+        Ok(())
+    }
+    pub fn release_frozen_reward(&mut self, amount: u64) -> Result<()> {
+        self.frozen_rewards = self.frozen_rewards.checked_sub(amount)
+            .ok_or(crate::error::RecruSearchError::ArithmeticOverflow)?;
+        Ok(())
+    }
+    /// Rejects a new enrollment the vault could not pay out alongside everyone already enrolled.
+    /// Prorated exits draw on the escrow, so a freed slot is not always a funded one.
+    pub fn require_funded_slot(&self, vault_balance: u64) -> Result<()> {
+        let required = self.outstanding_rewards()?
+            .checked_add(self.reward_amount)
+            .ok_or(crate::error::RecruSearchError::RewardOverflow)?;
+        require!(required <= vault_balance, crate::error::RecruSearchError::InsufficientEscrow);
+        Ok(())
+    }
+    /// Share of the reward owed to a participant leaving at `progress` percent
+    pub fn exit_payout(&self, progress: u8) -> u64 {
+        if !self.prorate_on_exit {
+            return 0;
+        }
+        (self.reward_amount as u128 * progress.min(100) as u128 / 100) as u64
+    }
+    /// Moves the study to `to` if the role may make that transition, returning the old status
+    pub fn transition(&mut self, to: StudyStatus, by: StatusAuthority) -> Result<StudyStatus> {
+        require!(
+            self.status.can_transition(to, by),
+            crate::error::RecruSearchError::InvalidStatusTransition
+        );
+        let from = self.status;
+        if from == StudyStatus::Suspended {
+            // Resuming goes back to where the study left off; closing or cancelling is always open
+            if matches!(to, StudyStatus::Recruiting | StudyStatus::Active) {
+                require!(
+                    self.suspended_from == Some(to),
+                    crate::error::RecruSearchError::InvalidStatusTransition
+                );
+            }
+            self.suspended_from = None;
+        }
+        if to == StudyStatus::Suspended {
+            self.suspended_from = Some(from);
+        }
+        self.status = to;
+        Ok(from)
+    }
+    pub fn require_status(&self, allowed: &[StudyStatus]) -> Result<()> {
+        require!(
+            allowed.contains(&self.status),
+            crate::error::RecruSearchError::InvalidStudyPhase
+        );
         Ok(())
     }
-    pub fn update_progress(&mut self, progress: u8) -> Result<()> {
-        require!(progress <= 100, crate::error::RecruSearchError::InvalidProgress);
+    /// Study configuration is only editable before recruitment opens
+    pub fn require_draft(&self) -> Result<()> {
+        self.require_status(&[StudyStatus::Draft])
+    }
+    /// Responses are only taken while recruiting or running, so a dataset anchored at
+    /// close covers every submission
+    pub fn require_in_progress(&self) -> Result<()> {
+        self.require_status(&[StudyStatus::Recruiting, StudyStatus::Active])
+    }
+    /// Participants still enrolled at close can finish and claim; their rewards stay
+    /// reserved in the vault until then (see `outstanding_rewards`)
+    pub fn require_completable(&self) -> Result<()> {
+        self.require_status(&[StudyStatus::Recruiting, StudyStatus::Active, StudyStatus::Closed])
+    }
+    /// Per-type prerequisites for opening recruitment
+    pub fn check_type_requirements(&self) -> Result<()> {
+        match self.study_type {
+            // Every study also needs an ethics approval, checked against its own account
+            StudyType::Clinical | StudyType::Experimental => require!(
+                self.consent_version.is_some(),
+                crate::error::RecruSearchError::MissingConsentVersion
+            ),
+            StudyType::Interview => require!(
+                !self.session_slots.is_empty(),
+                crate::error::RecruSearchError::MissingSessionSlots
+            ),
+            StudyType::Survey | StudyType::Observational => {}
+        }
         Ok(())
     }
-    pub fn submit_feedback(&mut self, rating: u8, feedback: Option<String>) -> Result<()> {
-        require!(rating <= 5, crate::error::RecruSearchError::InvalidRating);
-        if let Some(feedback) = feedback {
-            require!(feedback.len() <= 500, crate::error::RecruSearchError::FeedbackTooLong);
+    /// Whether joining needs an active consent NFT; surveys may be waived platform-wide
+    pub fn requires_consent(&self, survey_consent_waiver: bool) -> bool {
+        !(self.study_type == StudyType::Survey && survey_consent_waiver)
+    }
+    /// Closes a live study whose end date has passed, returning the old status
+    pub fn close_if_ended(&mut self, now: i64) -> Option<StudyStatus> {
+        if !self.schedule.has_ended(now)
+            || !matches!(self.status, StudyStatus::Recruiting | StudyStatus::Active)
+        {
+            return None;
         }
+        let from = self.status;
+        self.status = StudyStatus::Closed;
+        Some(from)
+    }
+    /// Folds a new rating into the running average
+    pub fn record_rating(&mut self, rating: u8) -> Result<()> {
+        let analytics = &mut self.analytics;
+        analytics.rating_sum = analytics.rating_sum.checked_add(rating as u64)
+            .ok_or(crate::error::RecruSearchError::ArithmeticOverflow)?;
+        analytics.rating_count = analytics.rating_count.checked_add(1)
+            .ok_or(crate::error::RecruSearchError::ArithmeticOverflow)?;
+        analytics.average_rating =
+            (analytics.rating_sum * RATING_SCALE / analytics.rating_count as u64) as u32;
         Ok(())
     }
+    fn refresh_completion_rate(&mut self) {
+        let enrolled = self.analytics.total_participants;
+        self.analytics.completion_rate = if enrolled == 0 {
+            0
+        } else {
+            (self.completed_participants as u64 * BPS_SCALE / enrolled as u64) as u32
+        };
+    }
     pub fn can_accept_participants(&self) -> Result<()> {
-        require!(self.is_active, crate::error::RecruSearchError::StudyInactive);
         require!(
-            self.current_participants < self.max_participants,
+            self.status == StudyStatus::Recruiting,
+            crate::error::RecruSearchError::StudyInactive
+        );
+        self.schedule.require_recruiting(Clock::get()?.unix_timestamp)?;
+        require!(
+            self.has_open_slot(),
             crate::error::RecruSearchError::StudyFull
         );
         Ok(())
     }
+    /// Whether a slot is free that no waitlist offer is holding
+    pub fn has_open_slot(&self) -> bool {
+        self.current_participants.saturating_add(self.reserved_slots) < self.max_participants
+    }
+    /// A study with anyone queued or holding an offer must have its waitlist passed in
+    pub fn waitlist_in_use(&self) -> bool {
+        self.waitlisted > 0 || self.reserved_slots > 0
+    }
+    pub fn add_to_waitlist(&mut self) -> Result<()> {
+        self.waitlisted = self.waitlisted.checked_add(1)
+            .ok_or(crate::error::RecruSearchError::ArithmeticOverflow)?;
+        Ok(())
+    }
+    /// Turns a queued entry into a held slot
+    pub fn reserve_slot(&mut self) -> Result<()> {
+        self.waitlisted = self.waitlisted.checked_sub(1)
+            .ok_or(crate::error::RecruSearchError::ArithmeticOverflow)?;
+        self.reserved_slots = self.reserved_slots.checked_add(1)
+            .ok_or(crate::error::RecruSearchError::ArithmeticOverflow)?;
+        Ok(())
+    }
+    pub fn release_reserved_slots(&mut self, count: u32) -> Result<()> {
+        self.reserved_slots = self.reserved_slots.checked_sub(count)
+            .ok_or(crate::error::RecruSearchError::ArithmeticOverflow)?;
+        Ok(())
+    }
     pub fn add_participant(&mut self) -> Result<()> {
         self.can_accept_participants()?;
         self.current_participants = self.current_participants.checked_add(1)
             .ok_or(crate::error::RecruSearchError::MaxParticipantsExceeded)?;
+        self.analytics.total_participants = self.analytics.total_participants.checked_add(1)
+            .ok_or(crate::error::RecruSearchError::ArithmeticOverflow)?;
+        self.refresh_completion_rate();
         Ok(())
     }
     pub fn remove_participant(&mut self) -> Result<()> {
@@ -115,6 +461,97 @@ impl Study {
     pub fn complete_participant(&mut self) -> Result<()> {
         self.completed_participants = self.completed_participants.checked_add(1)
             .ok_or(crate::error::RecruSearchError::InvalidParticipantStatus)?;
+        self.refresh_completion_rate();
+        Ok(())
+    }
+    /// Replaces the study's quotas, starting every counter at zero
+    pub fn set_quotas(&mut self, mut quotas: Vec<StratumQuota>) -> Result<()> {
+        require!(quotas.len() <= MAX_QUOTAS, crate::error::RecruSearchError::InvalidQuota);
+        for (i, quota) in quotas.iter().enumerate() {
+            require!(
+                !quota.value.is_empty()
+                    && quota.value.len() <= MAX_ATTRIBUTE_LEN
+                    && quota.max_count > 0
+                    && quota.min_count <= quota.max_count
+                    && quota.max_count <= self.max_participants,
+                crate::error::RecruSearchError::InvalidQuota
+            );
+            require!(
+                !quotas[..i].iter().any(|q| q.kind == quota.kind && q.value == quota.value),
+                crate::error::RecruSearchError::InvalidQuota
+            );
+        }
+        // Minimums of one kind must all fit in the study at once
+        for kind in [AttributeKind::AgeGroup, AttributeKind::Gender, AttributeKind::Region] {
+            let reserved: u64 = quotas.iter()
+                .filter(|q| q.kind == kind)
+                .map(|q| q.min_count as u64)
+                .sum();
+            require!(
+                reserved <= self.max_participants as u64,
+                crate::error::RecruSearchError::InvalidQuota
+            );
+        }
+        for quota in quotas.iter_mut() {
+            quota.enrolled = 0;
+        }
+        self.quotas = quotas;
+        Ok(())
+    }
+    /// Counts a newly added participant against the quotas, returning the indices
+    /// of the strata they fell into. Each constrained attribute must be disclosed;
+    /// the join is rejected if it fills a stratum past its maximum or takes a slot
+    /// another stratum of the same kind still needs to reach its minimum.
+    pub fn fill_strata(&mut self, root: &[u8; 32], disclosures: &[AttributeDisclosure]) -> Result<Vec<u8>> {
+        let free = self.max_participants
+            .saturating_sub(self.current_participants.saturating_add(self.reserved_slots));
+        let mut strata = Vec::new();
+        for kind in [AttributeKind::AgeGroup, AttributeKind::Gender, AttributeKind::Region] {
+            if !self.quotas.iter().any(|q| q.kind == kind) {
+                continue;
+            }
+            let value = disclosed_value(kind, root, disclosures)?;
+            let matched = self.quotas.iter().position(|q| q.kind == kind && q.value == value);
+            if let Some(index) = matched {
+                require!(!self.quotas[index].is_full(), crate::error::RecruSearchError::StratumFull);
+            }
+            let held_for_others: u32 = self.quotas.iter()
+                .enumerate()
+                .filter(|(i, q)| q.kind == kind && Some(*i) != matched)
+                .map(|(_, q)| q.shortfall())
+                .sum();
+            require!(free >= held_for_others, crate::error::RecruSearchError::StratumFull);
+            strata.extend(matched.map(|index| index as u8));
+        }
+        for &index in &strata {
+            let quota = &mut self.quotas[index as usize];
+            quota.enrolled = quota.enrolled.checked_add(1)
+                .ok_or(crate::error::RecruSearchError::ArithmeticOverflow)?;
+        }
+        Ok(strata)
+    }
+    /// Frees the strata a departing participant was counted in
+    pub fn release_strata(&mut self, strata: &[u8]) -> Result<()> {
+        for &index in strata {
+            let quota = self.quotas.get_mut(index as usize)
+                .ok_or(crate::error::RecruSearchError::InvalidQuota)?;
+            quota.enrolled = quota.enrolled.checked_sub(1)
+                .ok_or(crate::error::RecruSearchError::ArithmeticOverflow)?;
+        }
+        Ok(())
+    }
+    pub fn record_response(&mut self) -> Result<()> {
+        self.responses = self.responses.checked_add(1)
+            .ok_or(crate::error::RecruSearchError::ArithmeticOverflow)?;
+        Ok(())
+    }
+    /// Publishes the dataset root; it must carry one leaf per submission
+    pub fn anchor_dataset(&mut self, root: [u8; 32], entries: u32) -> Result<()> {
+        self.require_status(&[StudyStatus::Closed, StudyStatus::Archived])?;
+        require!(entries == self.responses, crate::error::RecruSearchError::DatasetIncomplete);
+        self.dataset_root = Some(root);
+        self.dataset_version = self.dataset_version.checked_add(1)
+            .ok_or(crate::error::RecruSearchError::ArithmeticOverflow)?;
         Ok(())
     }
     pub fn increment_consent(&mut self) -> Result<()> {
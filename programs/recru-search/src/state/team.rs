@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use crate::error::RecruSearchError;
+
+pub const MAX_TEAM_MEMBERS: usize = 16;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum TeamRole {
+    PrincipalInvestigator,
+    CoInvestigator,
+    ResearchAssistant,
+}
+
+/// What a researcher-gated study instruction needs from the caller
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TeamPermission {
+    /// Configuration, lifecycle transitions and escrow
+    ManageStudy,
+    /// Removing participants from the study and rejecting their completion claims
+    ManageParticipants,
+    /// Marking progress and completion
+    RecordProgress,
+}
+
+impl TeamRole {
+    pub fn allows(&self, permission: TeamPermission) -> bool {
+        match self {
+            TeamRole::PrincipalInvestigator => true,
+            TeamRole::CoInvestigator => permission != TeamPermission::ManageStudy,
+            TeamRole::ResearchAssistant => permission == TeamPermission::RecordProgress,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct TeamMember {
+    /// The member's `Researcher` account
+    pub researcher: Pubkey,
+    pub role: TeamRole,
+    pub added_at: i64,
+}
+
+/// Researchers working on a study and their roles.
+///
+/// Seeds: `[b"study_team", study]`. Created with the study, with its creator as
+/// the sole principal investigator.
+#[account]
+#[derive(InitSpace)]
+pub struct StudyTeam {
+    pub study: Pubkey,
+    #[max_len(MAX_TEAM_MEMBERS)]
+    pub members: Vec<TeamMember>,
+    pub bump: u8,
+}
+
+impl StudyTeam {
+    pub fn create(&mut self, study: Pubkey, principal_investigator: Pubkey, bump: u8) -> Result<()> {
+        self.study = study;
+        self.members = vec![TeamMember {
+            researcher: principal_investigator,
+            role: TeamRole::PrincipalInvestigator,
+            added_at: Clock::get()?.unix_timestamp,
+        }];
+        self.bump = bump;
+        Ok(())
+    }
+    pub fn role_of(&self, researcher: &Pubkey) -> Option<TeamRole> {
+        self.members.iter().find(|m| &m.researcher == researcher).map(|m| m.role)
+    }
+    pub fn allows(&self, researcher: &Pubkey, permission: TeamPermission) -> bool {
+        self.role_of(researcher).is_some_and(|role| role.allows(permission))
+    }
+    /// Adds a co-investigator or research assistant; a study has exactly one PI
+    pub fn add_member(&mut self, researcher: Pubkey, role: TeamRole, now: i64) -> Result<()> {
+        require!(role != TeamRole::PrincipalInvestigator, RecruSearchError::InvalidTeamRole);
+        require!(self.role_of(&researcher).is_none(), RecruSearchError::AlreadyTeamMember);
+        require!(self.members.len() < MAX_TEAM_MEMBERS, RecruSearchError::TeamFull);
+        self.members.push(TeamMember { researcher, role, added_at: now });
+        Ok(())
+    }
+    pub fn remove_member(&mut self, researcher: &Pubkey) -> Result<TeamRole> {
+        let index = self.members.iter()
+            .position(|m| &m.researcher == researcher)
+            .ok_or(RecruSearchError::NotTeamMember)?;
+        let role = self.members[index].role;
+        require!(role != TeamRole::PrincipalInvestigator, RecruSearchError::InvalidTeamRole);
+        self.members.remove(index);
+        Ok(role)
+    }
+}
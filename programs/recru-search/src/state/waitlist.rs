@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use crate::error::RecruSearchError;
+
+pub const MAX_WAITLIST_LEN: usize = 32;
+/// How long a promoted participant has to accept a freed slot
+pub const WAITLIST_OFFER_TTL: i64 = 48 * 60 * 60;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct WaitlistEntry {
+    pub participant: Pubkey,
+    pub joined_at: i64,
+}
+
+/// A freed slot held for a promoted participant until `expires_at`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct WaitlistOffer {
+    pub participant: Pubkey,
+    pub expires_at: i64,
+}
+
+/// FIFO queue of participants waiting for a slot in a full study.
+///
+/// Seeds: `[b"waitlist", study]`. Every outstanding offer is mirrored in
+/// `Study::reserved_slots` and every queued entry in `Study::waitlisted`.
+#[account]
+#[derive(InitSpace)]
+pub struct Waitlist {
+    pub study: Pubkey,
+    #[max_len(MAX_WAITLIST_LEN)]
+    pub queue: Vec<WaitlistEntry>,
+    #[max_len(MAX_WAITLIST_LEN)]
+    pub offers: Vec<WaitlistOffer>,
+    pub bump: u8,
+}
+
+impl Waitlist {
+    pub fn contains(&self, participant: &Pubkey) -> bool {
+        self.queue.iter().any(|e| &e.participant == participant)
+            || self.offers.iter().any(|o| &o.participant == participant)
+    }
+    /// Appends a participant, returning their 1-based position in the queue
+    pub fn enqueue(&mut self, participant: Pubkey, now: i64) -> Result<u32> {
+        require!(!self.contains(&participant), RecruSearchError::AlreadyWaitlisted);
+        require!(
+            self.queue.len() + self.offers.len() < MAX_WAITLIST_LEN,
+            RecruSearchError::WaitlistFull
+        );
+        self.queue.push(WaitlistEntry { participant, joined_at: now });
+        Ok(self.queue.len() as u32)
+    }
+    /// Moves the head of the queue into an offer, if anyone is waiting
+    pub fn promote_next(&mut self, now: i64) -> Option<WaitlistOffer> {
+        if self.queue.is_empty() {
+            return None;
+        }
+        let entry = self.queue.remove(0);
+        let offer = WaitlistOffer {
+            participant: entry.participant,
+            expires_at: now + WAITLIST_OFFER_TTL,
+        };
+        self.offers.push(offer);
+        Some(offer)
+    }
+    /// Drops lapsed offers, returning how many slots they were holding
+    pub fn expire_offers(&mut self, now: i64) -> u32 {
+        let before = self.offers.len();
+        self.offers.retain(|o| o.expires_at >= now);
+        (before - self.offers.len()) as u32
+    }
+    /// Consumes a live offer for `participant`, returning whether one existed
+    pub fn take_offer(&mut self, participant: &Pubkey) -> bool {
+        match self.offers.iter().position(|o| &o.participant == participant) {
+            Some(index) => {
+                self.offers.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+}
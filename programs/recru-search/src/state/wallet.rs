@@ -1,7 +1,11 @@
 use anchor_lang::prelude::*;
 
+pub const MAX_METADATA_URI_LEN: usize = 200;
+
 /// Represents a participant's wallet in the RecruSearch platform.
+/// Seeds: `[b"wallet", participant]`
 #[account]
+#[derive(InitSpace)]
 pub struct ParticipantWallet {
     pub participant: Pubkey,
     pub phantom_public_key: Pubkey,
@@ -11,6 +15,7 @@ pub struct ParticipantWallet {
     pub total_rewards: u64,
     pub last_reward_at: i64,
     pub is_active: bool,
+    #[max_len(MAX_METADATA_URI_LEN)]
     pub metadata_uri: Option<String>,
 }
 
@@ -53,21 +58,11 @@ impl ParticipantWallet {
     }
 
     pub fn update_metadata_uri(&mut self, uri: Option<String>) -> Result<()> {
+        require!(
+            uri.as_ref().map_or(true, |u| u.len() <= MAX_METADATA_URI_LEN),
+            crate::error::RecruSearchError::InputTooLong
+        );
         self.metadata_uri = uri;
         self.update_activity()
     }
 }
-
-pub fn get_wallet_size() -> usize {
-    let mut size = 8; // discriminator
-    size += 32; // participant
-    size += 32; // phantom_public_key
-    size += 1;  // bump
-    size += 8;  // created_at
-    size += 8;  // last_activity
-    size += 8;  // total_rewards
-    size += 8;  // last_reward_at
-    size += 1;  // is_active
-    size += 1 + 4 + 200; // Option<String>: discriminator + length + max URI
-    size
-}
@@ -1,8 +1,17 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program, Idl } from "@coral-xyz/anchor";
-import { PublicKey, Keypair, SystemProgram, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  LAMPORTS_PER_SOL,
+  Ed25519Program,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  SYSVAR_SLOT_HASHES_PUBKEY,
+} from "@solana/web3.js";
 import { TOKEN_PROGRAM_ID, createMint, createAccount, mintTo, getAccount, closeAccount } from "@solana/spl-token";
 import { expect } from "chai";
+import { createHash, randomBytes } from "crypto";
 
 // Import the IDL type
 import { RecruSearch } from "../target/types/recru_search";
@@ -20,6 +29,58 @@ type StudyType = {
   experimental: Record<string, never>;
 };
 
+type AttributeKind = "ageGroup" | "gender" | "region";
+
+// Mirrors the on-chain attribute commitment: salted leaves at fixed positions, one per kind
+const ATTRIBUTE_KIND_INDEX: Record<AttributeKind, number> = { ageGroup: 0, gender: 1, region: 2 };
+const ATTRIBUTE_TREE_LEAVES = 4;
+const sha256 = (...parts: Buffer[]) => createHash("sha256").update(Buffer.concat(parts)).digest();
+const attributeLeaf = (kind: AttributeKind, value: string, salt: Buffer) =>
+  sha256(Buffer.from([0]), Buffer.from([ATTRIBUTE_KIND_INDEX[kind]]), Buffer.from(value), salt);
+const hashPair = (a: Buffer, b: Buffer) =>
+  Buffer.compare(a, b) <= 0 ? sha256(Buffer.from([1]), a, b) : sha256(Buffer.from([1]), b, a);
+const hashChildren = (left: Buffer, right: Buffer) => sha256(Buffer.from([1]), left, right);
+
+// Leaves are placed by position; unused positions stay zero
+const buildAttributeTree = (
+  attributes: { kind: AttributeKind; value: string }[],
+  positions: number[] = attributes.map((a) => ATTRIBUTE_KIND_INDEX[a.kind])
+) => {
+  const salts = attributes.map(() => randomBytes(32));
+  const leaves: Buffer[] = Array.from({ length: ATTRIBUTE_TREE_LEAVES }, () => Buffer.alloc(32));
+  attributes.forEach((a, i) => {
+    leaves[positions[i]] = attributeLeaf(a.kind, a.value, salts[i]);
+  });
+  const layers: Buffer[][] = [leaves];
+  while (layers[layers.length - 1].length > 1) {
+    const prev = layers[layers.length - 1];
+    const next: Buffer[] = [];
+    for (let i = 0; i < prev.length; i += 2) {
+      next.push(hashChildren(prev[i], prev[i + 1]));
+    }
+    layers.push(next);
+  }
+
+  // Reveals one attribute together with its inclusion proof
+  const disclose = (kind: AttributeKind, value?: string) => {
+    const entry = attributes.findIndex((a) => a.kind === kind && (value === undefined || a.value === value));
+    const proof: number[][] = [];
+    let index = positions[entry];
+    for (const layer of layers.slice(0, -1)) {
+      proof.push([...layer[index ^ 1]]);
+      index >>= 1;
+    }
+    return {
+      kind: { [kind]: {} },
+      value: attributes[entry].value,
+      salt: [...salts[entry]],
+      proof,
+    };
+  };
+
+  return { root: [...layers[layers.length - 1][0]], disclose };
+};
+
 describe("recru-search", () => {
   // Configure the client to use the local cluster
   const provider = anchor.AnchorProvider.env();
@@ -34,6 +95,19 @@ describe("recru-search", () => {
   const studyMint = Keypair.generate();
   const psyPointsMint = Keypair.generate();
   
+  // Screening attributes the participant commits to at registration
+  const participantAttributes = buildAttributeTree([
+    { kind: "ageGroup", value: "25-34" },
+    { kind: "gender", value: "female" },
+    { kind: "region", value: "EU" },
+  ]);
+
+  // Off-chain screening service trusted by the admin
+  const verifier = Keypair.generate();
+
+  // Ethics review board recognised by the admin
+  const board = Keypair.generate();
+
   // Token accounts
   let researcherTokenAccount: PublicKey;
   let participantTokenAccount: PublicKey;
@@ -56,6 +130,102 @@ describe("recru-search", () => {
   );
 
   let studyPda: PublicKey;
+
+  // Studies are numbered per researcher by its studies_created counter
+  const studyFor = (researcherAccount: PublicKey, index: number) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("study"), researcherAccount.toBuffer(), new anchor.BN(index).toArrayLike(Buffer, "le", 4)],
+      program.programId
+    )[0];
+
+  // Reward escrow owned by each study
+  const vaultFor = (study: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), study.toBuffer()],
+      program.programId
+    )[0];
+
+  const teamFor = (study: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("study_team"), study.toBuffer()],
+      program.programId
+    )[0];
+
+  const studyConsentFor = (study: PublicKey, participantAccount: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("study_consent"), study.toBuffer(), participantAccount.toBuffer()],
+      program.programId
+    )[0];
+  const tombstoneFor = (participantAccount: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("tombstone"), participantAccount.toBuffer()],
+      program.programId
+    )[0];
+
+  // One ethics approval per study
+  const ethicsApprovalFor = (study: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("ethics_approval"), study.toBuffer()],
+      program.programId
+    )[0];
+
+  // The registered review board approves a study's protocol for the next year
+  const approveStudy = (study: PublicKey) =>
+    program.methods
+      .grantEthicsApproval(
+        [...sha256(Buffer.from("protocol-2024-001"))],
+        [...sha256(Buffer.from("approval-letter"))],
+        new anchor.BN(Math.floor(Date.now() / 1000) - 60),
+        new anchor.BN(Math.floor(Date.now() / 1000) + 365 * 86400)
+      )
+      .accounts({
+        admin: adminPda,
+        study,
+        ethicsApproval: ethicsApprovalFor(study),
+        board: board.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([board])
+      .rpc();
+
+  // Spent attestation nonces, one per (verifier, nonce)
+  const attestationNonceFor = (verifierKey: PublicKey, nonce: anchor.BN) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("attestation_nonce"), verifierKey.toBuffer(), nonce.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
+  // Verifier signs participant || study || expiry || nonce
+  const signAttestation = (
+    signer: Keypair,
+    participantAccount: PublicKey,
+    study: PublicKey,
+    expiry: anchor.BN,
+    nonce: anchor.BN
+  ) =>
+    Ed25519Program.createInstructionWithPrivateKey({
+      privateKey: signer.secretKey,
+      message: Buffer.concat([
+        participantAccount.toBuffer(),
+        study.toBuffer(),
+        expiry.toArrayLike(Buffer, "le", 8),
+        nonce.toArrayLike(Buffer, "le", 8),
+      ]),
+    });
+
+  // One feedback record per (study, participant)
+  const feedbackFor = (study: PublicKey, participantAccount: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("feedback"), study.toBuffer(), participantAccount.toBuffer()],
+      program.programId
+    )[0];
+
+  // One enrollment per (study, participant)
+  const enrollmentFor = (study: PublicKey, participantAccount: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("enrollment"), study.toBuffer(), participantAccount.toBuffer()],
+      program.programId
+    )[0];
   let isInitialized = false;
 
   // Setup before all tests
@@ -66,6 +236,7 @@ describe("recru-search", () => {
       await provider.connection.requestAirdrop(admin.publicKey, airdropAmount);
       await provider.connection.requestAirdrop(researcher.publicKey, airdropAmount);
       await provider.connection.requestAirdrop(participant.publicKey, airdropAmount);
+      await provider.connection.requestAirdrop(board.publicKey, airdropAmount);
       
       // Create token mints
       await createMint(
@@ -115,14 +286,11 @@ describe("recru-search", () => {
         studyMint.publicKey,
         researcherTokenAccount,
         admin.publicKey,
-        10000000000 // 10 tokens
+        100000000000 // 100 tokens, enough to fund several studies
       );
 
-      // Initialize study PDA
-      [studyPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("study"), researcherPda.toBuffer()],
-        program.programId
-      );
+      // The researcher's first study
+      studyPda = studyFor(researcherPda, 0);
     } catch (error) {
       console.error("Setup error:", error);
       throw error;
@@ -222,6 +390,81 @@ describe("recru-search", () => {
         throw error;
       }
     });
+
+    it("Registers a trusted eligibility verifier", async () => {
+      try {
+        await program.methods
+          .addTrustedVerifier(verifier.publicKey)
+          .accounts({
+            admin: adminPda,
+            authority: admin.publicKey,
+          })
+          .signers([admin])
+          .rpc();
+
+        const adminAccount = await program.account.admin.fetch(adminPda);
+        expect(adminAccount.trustedVerifiers).to.deep.include(verifier.publicKey);
+      } catch (error) {
+        console.error("Trusted verifier error:", error);
+        throw error;
+      }
+    });
+
+    it("Fails to register a verifier as non-admin", async () => {
+      try {
+        await program.methods
+          .addTrustedVerifier(Keypair.generate().publicKey)
+          .accounts({
+            admin: adminPda,
+            authority: researcher.publicKey,
+          })
+          .signers([researcher])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error).to.exist;
+      }
+    });
+
+    it("Registers an ethics review board", async () => {
+      await program.methods
+        .addReviewBoard(board.publicKey)
+        .accounts({
+          admin: adminPda,
+          authority: admin.publicKey,
+        })
+        .signers([admin])
+        .rpc();
+
+      const adminAccount = await program.account.admin.fetch(adminPda);
+      expect(adminAccount.reviewBoards).to.deep.include(board.publicKey);
+    });
+
+    it("Waives the consent NFT for surveys", async () => {
+      const tx = await program.methods
+        .setSurveyConsentWaiver(true)
+        .accounts({
+          admin: adminPda,
+          authority: admin.publicKey,
+        })
+        .signers([admin])
+        .rpc({ commitment: "confirmed" });
+
+      const adminAccount = await program.account.admin.fetch(adminPda);
+      expect(adminAccount.surveyConsentWaiver).to.equal(true);
+
+      // The change is announced with both the old and new setting
+      const { meta } = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const events = [...new anchor.EventParser(program.programId, program.coder).parseLogs(meta.logMessages)];
+      const event = events.find((e) => e.name.toLowerCase() === "surveyconsentwaiverupdated");
+      expect(event).to.exist;
+      expect(event.data.previous).to.equal(false);
+      expect(event.data.waived).to.equal(true);
+    });
   });
 
   describe("Researcher Management", () => {
@@ -331,11 +574,18 @@ describe("recru-search", () => {
             description,
             criteriaHash,
             rewardAmount,
-            maxParticipants
+            maxParticipants,
+            { survey: {} }
           )
           .accounts({
             study: studyPda,
+            team: teamFor(studyPda),
             researcher: researcherPda,
+            vault: vaultFor(studyPda),
+            rewardMint: studyMint.publicKey,
+            researcherTokenAccount: researcherTokenAccount,
+            authority: researcher.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
           })
           .signers([researcher])
@@ -351,7 +601,18 @@ describe("recru-search", () => {
         expect(studyAccount.rewardAmount.toString()).to.equal(rewardAmount.toString());
         expect(studyAccount.maxParticipants).to.equal(maxParticipants);
         expect(studyAccount.currentParticipants).to.equal(0);
-        expect(studyAccount.isActive).to.be.true;
+        expect(studyAccount.status).to.have.property("draft");
+        expect(studyAccount.rewardMint).to.eql(studyMint.publicKey);
+        expect(studyAccount.index).to.equal(0);
+
+        const researcherAccount = await program.account.researcher.fetch(researcherPda);
+        expect(researcherAccount.studiesCreated).to.equal(1);
+
+        // Verify the escrow was funded for every slot
+        const vaultAccount = await getAccount(provider.connection, vaultFor(studyPda));
+        expect(vaultAccount.amount.toString()).to.equal(
+          rewardAmount.muln(maxParticipants).toString()
+        );
       } catch (error) {
         console.error("Study creation error:", error);
         throw error;
@@ -372,11 +633,18 @@ describe("recru-search", () => {
             "A test study for research",
             "test_criteria_hash",
             new anchor.BN(1000000000),
-            10
+            10,
+            { survey: {} }
           )
           .accounts({
             study: studyPda,
+            team: teamFor(studyPda),
             researcher: unverifiedResearcherPda,
+            vault: vaultFor(studyPda),
+            rewardMint: studyMint.publicKey,
+            researcherTokenAccount: researcherTokenAccount,
+            authority: researcher.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
           })
           .signers([unverifiedResearcher])
@@ -396,11 +664,18 @@ describe("recru-search", () => {
             "Test Description",
             "test_criteria_hash",
             0, // Invalid reward amount
-            10
+            10,
+            { survey: {} }
           )
           .accounts({
             study: studyPda,
+            team: teamFor(studyPda),
             researcher: researcherPda,
+            vault: vaultFor(studyPda),
+            rewardMint: studyMint.publicKey,
+            researcherTokenAccount: researcherTokenAccount,
+            authority: researcher.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
           })
           .signers([researcher])
@@ -412,6 +687,39 @@ describe("recru-search", () => {
       }
     });
 
+    it("Fails to create study with an over-long title", async () => {
+      const nextStudyPda = studyFor(researcherPda, 1);
+
+      try {
+        await program.methods
+          .createStudy(
+            "T".repeat(101),
+            "Test Description",
+            "test_criteria_hash",
+            new anchor.BN(1000000000),
+            10,
+            { survey: {} }
+          )
+          .accounts({
+            study: nextStudyPda,
+            team: teamFor(nextStudyPda),
+            researcher: researcherPda,
+            vault: vaultFor(nextStudyPda),
+            rewardMint: studyMint.publicKey,
+            researcherTokenAccount: researcherTokenAccount,
+            authority: researcher.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([researcher])
+          .rpc();
+
+        expect.fail("Should have thrown an error for an over-long title");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("InvalidStudyParameters");
+      }
+    });
+
     it("Fails to create study with excessive max participants", async () => {
       try {
         await program.methods
@@ -420,11 +728,18 @@ describe("recru-search", () => {
             "Test Description",
             "test_criteria_hash",
             1000,
-            2000 // Exceeds maximum limit
+            2000, // Exceeds maximum limit
+            { survey: {} }
           )
           .accounts({
             study: studyPda,
+            team: teamFor(studyPda),
             researcher: researcherPda,
+            vault: vaultFor(studyPda),
+            rewardMint: studyMint.publicKey,
+            researcherTokenAccount: researcherTokenAccount,
+            authority: researcher.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
           })
           .signers([researcher])
@@ -435,15 +750,194 @@ describe("recru-search", () => {
         expect(error).to.exist;
       }
     });
+
+    it("Sets eligibility criteria while in draft", async () => {
+      try {
+        const criteria = {
+          allowedAgeGroups: ["18-24", "25-34"],
+          allowedGenders: [],
+          allowedRegions: ["EU"],
+          requiredInterests: ["psychology"],
+          minReputation: 0,
+          minCompletedStudies: 0,
+          requiresAttestation: false,
+          requiresIdentity: false,
+        };
+
+        await program.methods
+          .setEligibilityCriteria(criteria)
+          .accounts({
+            study: studyPda,
+            team: teamFor(studyPda),
+            researcher: researcherPda,
+            authority: researcher.publicKey,
+          })
+          .signers([researcher])
+          .rpc();
+
+        const studyAccount = await program.account.study.fetch(studyPda);
+        expect(studyAccount.criteria.allowedRegions).to.eql(["EU"]);
+        expect(studyAccount.criteria.requiredInterests).to.eql(["psychology"]);
+      } catch (error) {
+        console.error("Eligibility criteria error:", error);
+        throw error;
+      }
+    });
+
+    it("Rejects skipping straight from draft to active", async () => {
+      try {
+        await program.methods
+          .transitionStudy({ active: {} })
+          .accounts({
+            study: studyPda,
+            team: teamFor(studyPda),
+            researcher: researcherPda,
+            admin: adminPda,
+            ethicsApproval: null,
+            authority: researcher.publicKey,
+          })
+          .signers([researcher])
+          .rpc();
+
+        expect.fail("Should have thrown an error for an illegal transition");
+      } catch (error) {
+        expect(error).to.exist;
+      }
+    });
+
+    it("Refuses to open recruitment without an ethics approval", async () => {
+      try {
+        await program.methods
+          .transitionStudy({ recruiting: {} })
+          .accounts({
+            study: studyPda,
+            team: teamFor(studyPda),
+            researcher: researcherPda,
+            admin: adminPda,
+            ethicsApproval: null,
+            authority: researcher.publicKey,
+          })
+          .signers([researcher])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("MissingEthicsApproval");
+      }
+    });
+
+    it("Records a review board's ethics approval", async () => {
+      await approveStudy(studyPda);
+
+      const approval = await program.account.ethicsApproval.fetch(ethicsApprovalFor(studyPda));
+      expect(approval.study).to.eql(studyPda);
+      expect(approval.board).to.eql(board.publicKey);
+      expect(approval.revokedAt).to.be.null;
+    });
+
+    it("Rejects approvals from unregistered boards", async () => {
+      const rogueBoard = Keypair.generate();
+      try {
+        await program.methods
+          .grantEthicsApproval(
+            [...sha256(Buffer.from("protocol"))],
+            [...sha256(Buffer.from("letter"))],
+            new anchor.BN(0),
+            new anchor.BN(Math.floor(Date.now() / 1000) + 3600)
+          )
+          .accounts({
+            admin: adminPda,
+            study: studyPda,
+            ethicsApproval: ethicsApprovalFor(studyPda),
+            board: rogueBoard.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([rogueBoard])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error).to.exist;
+      }
+    });
+
+    it("Opens recruitment for a study", async () => {
+      try {
+        await program.methods
+          .transitionStudy({ recruiting: {} })
+          .accounts({
+            study: studyPda,
+            team: teamFor(studyPda),
+            researcher: researcherPda,
+            admin: adminPda,
+            ethicsApproval: ethicsApprovalFor(studyPda),
+            authority: researcher.publicKey,
+          })
+          .signers([researcher])
+          .rpc();
+
+        const studyAccount = await program.account.study.fetch(studyPda);
+        expect(studyAccount.status).to.have.property("recruiting");
+      } catch (error) {
+        console.error("Open recruitment error:", error);
+        throw error;
+      }
+    });
+
+    it("Fails to suspend a study as the researcher", async () => {
+      try {
+        await program.methods
+          .transitionStudy({ suspended: {} })
+          .accounts({
+            study: studyPda,
+            team: teamFor(studyPda),
+            researcher: researcherPda,
+            admin: adminPda,
+            ethicsApproval: null,
+            authority: researcher.publicKey,
+          })
+          .signers([researcher])
+          .rpc();
+
+        expect.fail("Only the admin may suspend a study");
+      } catch (error) {
+        expect(error).to.exist;
+      }
+    });
+
+    it("Resumes a suspended study only to the status it was suspended from", async () => {
+      const setStatus = (status: object, ethicsApproval: PublicKey | null) =>
+        program.methods
+          .updateStudyStatus(status as any)
+          .accounts({ admin: adminPda, study: studyPda, ethicsApproval, authority: admin.publicKey })
+          .signers([admin])
+          .rpc();
+
+      await setStatus({ suspended: {} }, null);
+      let studyAccount = await program.account.study.fetch(studyPda);
+      expect(studyAccount.status).to.have.property("suspended");
+      expect(studyAccount.suspendedFrom).to.have.property("recruiting");
+
+      try {
+        await setStatus({ active: {} }, null);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("InvalidStatusTransition");
+      }
+
+      await setStatus({ recruiting: {} }, ethicsApprovalFor(studyPda));
+      studyAccount = await program.account.study.fetch(studyPda);
+      expect(studyAccount.status).to.have.property("recruiting");
+      expect(studyAccount.suspendedFrom).to.be.null;
+    });
   });
 
   describe("Participant Management", () => {
     it("Registers a new participant", async () => {
       try {
-        const eligibilityProof = "proof123";
 
         const tx = await program.methods
-          .registerParticipant(eligibilityProof)
+          .registerParticipant(participantAttributes.root)
           .accounts({
             participant: participantPda,
             authority: participant.publicKey,
@@ -457,7 +951,7 @@ describe("recru-search", () => {
         // Verify participant was registered
         const participantAccount = await program.account.participant.fetch(participantPda);
         expect(participantAccount.authority).to.eql(participant.publicKey);
-        expect(participantAccount.eligibilityProof).to.equal(eligibilityProof);
+        expect(participantAccount.attributesRoot).to.eql(participantAttributes.root);
         expect(participantAccount.activeStudies).to.equal(0);
         expect(participantAccount.completedStudies).to.equal(0);
       } catch (error) {
@@ -469,7 +963,7 @@ describe("recru-search", () => {
     it("Fails to register same participant twice", async () => {
       try {
         await program.methods
-          .registerParticipant("test_eligibility_proof")
+          .registerParticipant(participantAttributes.root)
           .accounts({
             participant: participantPda,
             authority: participant.publicKey,
@@ -484,64 +978,280 @@ describe("recru-search", () => {
       }
     });
 
-    it("Initializes Phantom wallet for participant", async () => {
+    it("Fails to join when the profile does not meet the criteria", async () => {
       try {
-        const tx = await program.methods
-          .initializePhantomWallet()
+        await program.methods
+          .joinStudy([], null)
           .accounts({
+            study: studyPda,
+            admin: adminPda,
             participant: participantPda,
+            enrollment: enrollmentFor(studyPda, participantPda),
+            ethicsApproval: ethicsApprovalFor(studyPda),
+            studyConsent: null,
+            guardianship: null,
+            vault: vaultFor(studyPda),
+            waitlist: null,
+            allocation: null,
+            attestationNonce: null,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
             authority: participant.publicKey,
             systemProgram: SystemProgram.programId,
           })
           .signers([participant])
           .rpc();
-        
-        console.log("Phantom wallet initialization tx:", tx);
-        
-        // Verify participant wallet was initialized
-        const participantAccount = await program.account.participant.fetch(participantPda);
-        expect(participantAccount.wallet).to.exist;
+
+        expect.fail("Should have thrown an error");
       } catch (error) {
-        console.error("Phantom wallet initialization error:", error);
-        throw error;
+        expect(error.error?.errorCode?.code).to.equal("MissingAttributeDisclosure");
       }
     });
-  });
 
-  describe("Study Participation", () => {
-    it("Allows participant to join a study", async () => {
+    it("Fails to join with a tampered attribute disclosure", async () => {
       try {
-        const tx = await program.methods
-          .joinStudy()
+        const forged = { ...participantAttributes.disclose("ageGroup"), value: "18-24" };
+        await program.methods
+          .joinStudy([forged, participantAttributes.disclose("region")], null)
           .accounts({
             study: studyPda,
+            admin: adminPda,
             participant: participantPda,
+            enrollment: enrollmentFor(studyPda, participantPda),
+            ethicsApproval: ethicsApprovalFor(studyPda),
+            studyConsent: null,
+            guardianship: null,
+            vault: vaultFor(studyPda),
+            waitlist: null,
+            allocation: null,
+            attestationNonce: null,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
             authority: participant.publicKey,
+            systemProgram: SystemProgram.programId,
           })
           .signers([participant])
           .rpc();
-        
-        console.log("Join study tx:", tx);
-        
-        // Verify study and participant accounts were updated
-        const studyAccount = await program.account.study.fetch(studyPda);
-        const participantAccount = await program.account.participant.fetch(participantPda);
-        
-        expect(studyAccount.currentParticipants).to.equal(1);
-        expect(participantAccount.activeStudies).to.equal(1);
+
+        expect.fail("Should have thrown an error");
       } catch (error) {
-        console.error("Join study error:", error);
-        throw error;
+        expect(error.error?.errorCode?.code).to.equal("InvalidAttributeProof");
       }
     });
 
-    it("Fails to join study when at capacity", async () => {
-      // Create a study at capacity
-      const fullStudyMint = Keypair.generate();
-      const [fullStudyPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("study"), researcherPda.toBuffer()],
-        program.programId
-      );
+    it("Only accepts each attribute from its own position in the commitment", async () => {
+      const commit = (root: number[]) =>
+        program.methods
+          .commitAttributes(root)
+          .accounts({ authority: participant.publicKey, participant: participantPda })
+          .signers([participant])
+          .rpc();
+
+      // A second region slipped into the gender position
+      const doubled = buildAttributeTree(
+        [
+          { kind: "ageGroup", value: "25-34" },
+          { kind: "region", value: "EU" },
+          { kind: "region", value: "US" },
+        ],
+        [0, 1, 2]
+      );
+      await commit(doubled.root);
+      try {
+        await program.methods
+          .joinStudy([doubled.disclose("ageGroup"), doubled.disclose("region", "EU")], null)
+          .accounts({
+            study: studyPda,
+            admin: adminPda,
+            participant: participantPda,
+            enrollment: enrollmentFor(studyPda, participantPda),
+            ethicsApproval: ethicsApprovalFor(studyPda),
+            studyConsent: null,
+            guardianship: null,
+            vault: vaultFor(studyPda),
+            waitlist: null,
+            allocation: null,
+            attestationNonce: null,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            authority: participant.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([participant])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("InvalidAttributeProof");
+      } finally {
+        await commit(participantAttributes.root);
+      }
+    });
+
+    it("Updates participant interests", async () => {
+      try {
+        await program.methods
+          .updateParticipantProfile(["psychology", "sleep"])
+          .accounts({
+            authority: participant.publicKey,
+            participant: participantPda,
+          })
+          .signers([participant])
+          .rpc();
+
+        const participantAccount = await program.account.participant.fetch(participantPda);
+        expect(participantAccount.profile.interests).to.include("psychology");
+      } catch (error) {
+        console.error("Profile update error:", error);
+        throw error;
+      }
+    });
+
+    it("Initializes Phantom wallet for participant", async () => {
+      try {
+        const tx = await program.methods
+          .initializePhantomWallet()
+          .accounts({
+            participant: participantPda,
+            authority: participant.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([participant])
+          .rpc();
+        
+        console.log("Phantom wallet initialization tx:", tx);
+        
+        // Verify participant wallet was initialized
+        const participantAccount = await program.account.participant.fetch(participantPda);
+        expect(participantAccount.wallet).to.exist;
+      } catch (error) {
+        console.error("Phantom wallet initialization error:", error);
+        throw error;
+      }
+    });
+  });
+
+  describe("Study Participation", () => {
+    it("Rejects an attestation from an untrusted verifier", async () => {
+      const impostor = Keypair.generate();
+      const expiry = new anchor.BN(Math.floor(Date.now() / 1000) + 3600);
+      const nonce = new anchor.BN(1);
+
+      try {
+        await program.methods
+          .joinStudy(
+            [participantAttributes.disclose("ageGroup"), participantAttributes.disclose("region")],
+            { verifier: impostor.publicKey, expiry, nonce }
+          )
+          .accounts({
+            study: studyPda,
+            admin: adminPda,
+            participant: participantPda,
+            enrollment: enrollmentFor(studyPda, participantPda),
+            ethicsApproval: ethicsApprovalFor(studyPda),
+            studyConsent: null,
+            guardianship: null,
+            vault: vaultFor(studyPda),
+            waitlist: null,
+            allocation: null,
+            attestationNonce: attestationNonceFor(impostor.publicKey, nonce),
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            authority: participant.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .preInstructions([signAttestation(impostor, participantPda, studyPda, expiry, nonce)])
+          .signers([participant])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("UntrustedVerifier");
+      }
+    });
+
+    it("Allows participant to join a study", async () => {
+      try {
+        const expiry = new anchor.BN(Math.floor(Date.now() / 1000) + 3600);
+        const nonce = new anchor.BN(1);
+
+        // Criteria screen on age group and region only; gender stays private
+        const tx = await program.methods
+          .joinStudy(
+            [participantAttributes.disclose("ageGroup"), participantAttributes.disclose("region")],
+            { verifier: verifier.publicKey, expiry, nonce }
+          )
+          .accounts({
+            study: studyPda,
+            admin: adminPda,
+            participant: participantPda,
+            enrollment: enrollmentFor(studyPda, participantPda),
+            ethicsApproval: ethicsApprovalFor(studyPda),
+            studyConsent: null,
+            guardianship: null,
+            vault: vaultFor(studyPda),
+            waitlist: null,
+            allocation: null,
+            attestationNonce: attestationNonceFor(verifier.publicKey, nonce),
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            authority: participant.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .preInstructions([signAttestation(verifier, participantPda, studyPda, expiry, nonce)])
+          .signers([participant])
+          .rpc();
+        
+        console.log("Join study tx:", tx);
+        
+        // Verify study and participant accounts were updated
+        const studyAccount = await program.account.study.fetch(studyPda);
+        const participantAccount = await program.account.participant.fetch(participantPda);
+        
+        expect(studyAccount.currentParticipants).to.equal(1);
+        expect(participantAccount.activeStudies).to.equal(1);
+
+        const enrollmentAccount = await program.account.enrollment.fetch(
+          enrollmentFor(studyPda, participantPda)
+        );
+        expect(enrollmentAccount.study).to.eql(studyPda);
+        expect(enrollmentAccount.participant).to.eql(participantPda);
+        expect(enrollmentAccount.status).to.have.property("enrolled");
+        expect(enrollmentAccount.progress).to.equal(0);
+      } catch (error) {
+        console.error("Join study error:", error);
+        throw error;
+      }
+    });
+
+    it("Fails to join the same study twice", async () => {
+      try {
+        await program.methods
+          .joinStudy([], null)
+          .accounts({
+            study: studyPda,
+            admin: adminPda,
+            participant: participantPda,
+            enrollment: enrollmentFor(studyPda, participantPda),
+            ethicsApproval: ethicsApprovalFor(studyPda),
+            studyConsent: null,
+            guardianship: null,
+            vault: vaultFor(studyPda),
+            waitlist: null,
+            allocation: null,
+            attestationNonce: null,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            authority: participant.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([participant])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error).to.exist;
+      }
+    });
+
+    it("Fails to join study when at capacity", async () => {
+      // Create a study at capacity
+      const fullStudyMint = Keypair.generate();
+      const fullStudyPda = studyFor(researcherPda, 1);
 
       try {
         await program.methods
@@ -550,11 +1260,18 @@ describe("recru-search", () => {
             "A study at capacity",
             "test_criteria_hash",
             new anchor.BN(1000000000),
-            1 // maxParticipants = 1
+            1, // maxParticipants = 1
+            { survey: {} }
           )
           .accounts({
             study: fullStudyPda,
+            team: teamFor(fullStudyPda),
             researcher: researcherPda,
+            vault: vaultFor(fullStudyPda),
+            rewardMint: studyMint.publicKey,
+            researcherTokenAccount: researcherTokenAccount,
+            authority: researcher.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
           })
           .signers([researcher])
@@ -568,11 +1285,22 @@ describe("recru-search", () => {
         );
 
         await program.methods
-          .joinStudy()
+          .joinStudy([], null)
           .accounts({
             study: fullStudyPda,
+            admin: adminPda,
             participant: secondParticipantPda,
+            enrollment: enrollmentFor(fullStudyPda, secondParticipantPda),
+            ethicsApproval: ethicsApprovalFor(fullStudyPda),
+            studyConsent: null,
+            guardianship: null,
+            vault: vaultFor(fullStudyPda),
+            waitlist: null,
+            allocation: null,
+            attestationNonce: null,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
             authority: secondParticipant.publicKey,
+            systemProgram: SystemProgram.programId,
           })
           .signers([secondParticipant])
           .rpc();
@@ -591,13 +1319,68 @@ describe("recru-search", () => {
           .trackStudyProgress(progress)
           .accounts({
             study: studyPda,
+            team: teamFor(studyPda),
+            researcher: researcherPda,
             participant: participantPda,
+            enrollment: enrollmentFor(studyPda, participantPda),
             authority: participant.publicKey,
           })
           .signers([participant])
           .rpc();
         
         console.log("Study progress tx:", tx);
+
+        const enrollmentAccount = await program.account.enrollment.fetch(
+          enrollmentFor(studyPda, participantPda)
+        );
+        expect(enrollmentAccount.progress).to.equal(progress);
+        expect(enrollmentAccount.lastUpdate.toNumber()).to.be.greaterThan(0);
+      } catch (error) {
+        console.error("Study progress error:", error);
+        throw error;
+      }
+    });
+
+    it("Fails to move progress backwards", async () => {
+      try {
+        await program.methods
+          .trackStudyProgress(10)
+          .accounts({
+            study: studyPda,
+            team: teamFor(studyPda),
+            researcher: researcherPda,
+            participant: participantPda,
+            enrollment: enrollmentFor(studyPda, participantPda),
+            authority: participant.publicKey,
+          })
+          .signers([participant])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error).to.exist;
+      }
+    });
+
+    it("Lets the researcher mark the study finished", async () => {
+      try {
+        await program.methods
+          .trackStudyProgress(100)
+          .accounts({
+            study: studyPda,
+            team: teamFor(studyPda),
+            researcher: researcherPda,
+            participant: participantPda,
+            enrollment: enrollmentFor(studyPda, participantPda),
+            authority: researcher.publicKey,
+          })
+          .signers([researcher])
+          .rpc();
+
+        const enrollmentAccount = await program.account.enrollment.fetch(
+          enrollmentFor(studyPda, participantPda)
+        );
+        expect(enrollmentAccount.progress).to.equal(100);
       } catch (error) {
         console.error("Study progress error:", error);
         throw error;
@@ -616,7 +1399,10 @@ describe("recru-search", () => {
           .trackStudyProgress(50)
           .accounts({
             study: studyPda,
+            team: teamFor(studyPda),
+            researcher: researcherPda,
             participant: nonParticipantPda,
+            enrollment: enrollmentFor(studyPda, nonParticipantPda),
             authority: nonParticipant.publicKey,
           })
           .signers([nonParticipant])
@@ -628,33 +1414,11 @@ describe("recru-search", () => {
       }
     });
 
-    it("Submits study feedback", async () => {
-      try {
-        const rating = 5;
-        const feedback = "Great study experience!";
-        
-        const tx = await program.methods
-          .submitStudyFeedback(rating, feedback)
-          .accounts({
-            study: studyPda,
-            participant: participantPda,
-            authority: participant.publicKey,
-          })
-          .signers([participant])
-          .rpc();
-        
-        console.log("Study feedback tx:", tx);
-      } catch (error) {
-        console.error("Study feedback error:", error);
-        throw error;
-      }
-    });
-
     it("Completes study and distributes rewards", async () => {
       try {
-        const initialResearcherBalance = await getAccount(
+        const initialVaultBalance = await getAccount(
           provider.connection,
-          researcherTokenAccount
+          vaultFor(studyPda)
         ).then(acc => acc.amount);
 
         const initialParticipantBalance = await getAccount(
@@ -662,14 +1426,29 @@ describe("recru-search", () => {
           participantTokenAccount
         ).then(acc => acc.amount);
 
+        await program.methods
+          .claimCompletion()
+          .accounts({
+            study: studyPda,
+            participant: participantPda,
+            enrollment: enrollmentFor(studyPda, participantPda),
+            authority: participant.publicKey,
+          })
+          .signers([participant])
+          .rpc();
+
         const tx = await program.methods
           .completeStudy()
           .accounts({
             study: studyPda,
+            team: teamFor(studyPda),
             participant: participantPda,
+            enrollment: enrollmentFor(studyPda, participantPda),
             researcher: researcherPda,
-            researcherTokenAccount: researcherTokenAccount,
+            leadResearcher: researcherPda,
+            vault: vaultFor(studyPda),
             participantTokenAccount: participantTokenAccount,
+            authority: researcher.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
           })
@@ -679,9 +1458,9 @@ describe("recru-search", () => {
         console.log("Study completion tx:", tx);
 
         // Verify token transfers
-        const finalResearcherBalance = await getAccount(
+        const finalVaultBalance = await getAccount(
           provider.connection,
-          researcherTokenAccount
+          vaultFor(studyPda)
         ).then(acc => acc.amount);
 
         const finalParticipantBalance = await getAccount(
@@ -690,11 +1469,11 @@ describe("recru-search", () => {
         ).then(acc => acc.amount);
 
         const studyAccount = await program.account.study.fetch(studyPda);
-        expect(finalResearcherBalance).to.equal(
-          initialResearcherBalance - studyAccount.rewardAmount.toNumber()
+        expect(Number(finalVaultBalance)).to.equal(
+          Number(initialVaultBalance) - studyAccount.rewardAmount.toNumber()
         );
-        expect(finalParticipantBalance).to.equal(
-          initialParticipantBalance + studyAccount.rewardAmount.toNumber()
+        expect(Number(finalParticipantBalance)).to.equal(
+          Number(initialParticipantBalance) + studyAccount.rewardAmount.toNumber()
         );
 
         // Verify study and participant stats
@@ -702,55 +1481,142 @@ describe("recru-search", () => {
         expect(participantAccount.activeStudies).to.equal(0);
         expect(participantAccount.completedStudies).to.equal(1);
         expect(studyAccount.completedParticipants).to.equal(1);
+
+        const enrollmentAccount = await program.account.enrollment.fetch(
+          enrollmentFor(studyPda, participantPda)
+        );
+        expect(enrollmentAccount.status).to.have.property("completed");
+        expect(enrollmentAccount.rewardPaid.toString()).to.equal(
+          studyAccount.rewardAmount.toString()
+        );
+        // The completion is folded into the participant's reputation as of completion time
+        expect(participantAccount.reputation.updatedAt.toNumber()).to.equal(
+          enrollmentAccount.completedAt.toNumber()
+        );
       } catch (error) {
         console.error("Study completion error:", error);
         throw error;
       }
     });
 
-    it("Fails to complete study with insufficient funds", async () => {
-      const poorResearcher = Keypair.generate();
-      const [poorResearcherPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("researcher"), poorResearcher.publicKey.toBuffer()],
-        program.programId
-      );
-
+    it("Submits study feedback", async () => {
       try {
-        await program.methods
-          .completeStudy()
+        const rating = 5;
+        const feedback = "Great study experience!";
+        const { reputation: before } = await program.account.researcher.fetch(researcherPda);
+
+        const tx = await program.methods
+          .submitStudyFeedback(rating, feedback)
           .accounts({
             study: studyPda,
+            researcher: researcherPda,
             participant: participantPda,
-            researcher: poorResearcherPda,
-            researcherTokenAccount: researcherTokenAccount,
-            participantTokenAccount: participantTokenAccount,
-            tokenProgram: TOKEN_PROGRAM_ID,
+            enrollment: enrollmentFor(studyPda, participantPda),
+            feedback: feedbackFor(studyPda, participantPda),
+            authority: participant.publicKey,
             systemProgram: SystemProgram.programId,
           })
-          .signers([poorResearcher])
+          .signers([participant])
           .rpc();
         
-        expect.fail("Should have thrown an error");
+        console.log("Study feedback tx:", tx);
+
+        const feedbackAccount = await program.account.feedback.fetch(
+          feedbackFor(studyPda, participantPda)
+        );
+        expect(feedbackAccount.rating).to.equal(rating);
+        expect(feedbackAccount.comment).to.equal(feedback);
+
+        // Ratings are aggregated in fixed point (x100)
+        const studyAccount = await program.account.study.fetch(studyPda);
+        expect(studyAccount.analytics.ratingCount).to.equal(1);
+        expect(studyAccount.analytics.averageRating).to.equal(rating * 100);
+
+        // A top rating lifts the lead researcher's fixed-point reputation
+        const { reputation: after } = await program.account.researcher.fetch(researcherPda);
+        expect(after.score).to.be.greaterThan(before.score);
       } catch (error) {
-        expect(error).to.exist;
+        console.error("Study feedback error:", error);
+        throw error;
       }
     });
-  });
 
-  describe("Error Handling", () => {
-    it("Fails to create study with invalid parameters", async () => {
+    it("Fails to submit feedback twice", async () => {
       try {
         await program.methods
-          .createStudy(
-            "Test Study",
-            "Test Description",
-            "test_criteria_hash",
-            0, // Invalid reward amount
-            10
-          )
+          .submitStudyFeedback(1, null)
           .accounts({
             study: studyPda,
             researcher: researcherPda,
+            participant: participantPda,
+            enrollment: enrollmentFor(studyPda, participantPda),
+            feedback: feedbackFor(studyPda, participantPda),
+            authority: participant.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([participant])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error).to.exist;
+      }
+    });
+
+    it("Fails to complete study with insufficient funds", async () => {
+      const poorResearcher = Keypair.generate();
+      const [poorResearcherPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("researcher"), poorResearcher.publicKey.toBuffer()],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .completeStudy()
+          .accounts({
+            study: studyPda,
+            team: teamFor(studyPda),
+            participant: participantPda,
+            enrollment: enrollmentFor(studyPda, participantPda),
+            researcher: poorResearcherPda,
+            leadResearcher: researcherPda,
+            vault: vaultFor(studyPda),
+            participantTokenAccount: participantTokenAccount,
+            authority: poorResearcher.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([poorResearcher])
+          .rpc();
+        
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error).to.exist;
+      }
+    });
+  });
+
+  describe("Error Handling", () => {
+    it("Fails to create study with invalid parameters", async () => {
+      try {
+        await program.methods
+          .createStudy(
+            "Test Study",
+            "Test Description",
+            "test_criteria_hash",
+            0, // Invalid reward amount
+            10,
+            { survey: {} }
+          )
+          .accounts({
+            study: studyPda,
+            team: teamFor(studyPda),
+            researcher: researcherPda,
+            vault: vaultFor(studyPda),
+            rewardMint: studyMint.publicKey,
+            researcherTokenAccount: researcherTokenAccount,
+            authority: researcher.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
           })
           .signers([researcher])
@@ -770,11 +1636,18 @@ describe("recru-search", () => {
             "Test Description",
             "test_criteria_hash",
             1000,
-            2000 // Exceeds maximum limit
+            2000, // Exceeds maximum limit
+            { survey: {} }
           )
           .accounts({
             study: studyPda,
+            team: teamFor(studyPda),
             researcher: researcherPda,
+            vault: vaultFor(studyPda),
+            rewardMint: studyMint.publicKey,
+            researcherTokenAccount: researcherTokenAccount,
+            authority: researcher.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
           })
           .signers([researcher])
@@ -795,10 +1668,14 @@ describe("recru-search", () => {
           .completeStudy()
           .accounts({
             study: studyPda,
+            team: teamFor(studyPda),
             participant: participantPda,
+            enrollment: enrollmentFor(studyPda, participantPda),
             researcher: researcherPda,
-            researcherTokenAccount: researcherTokenAccount,
+            leadResearcher: researcherPda,
+            vault: vaultFor(studyPda),
             participantTokenAccount: participantTokenAccount,
+            authority: researcher.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
           })
@@ -845,25 +1722,24 @@ describe("recru-search", () => {
   });
 
   describe("Study Management", () => {
-    it("Properly tracks study progress", async () => {
+    it("Fails to track progress on a completed enrollment", async () => {
       try {
-        const progress = 50; // 50% completion
-        
         await program.methods
-          .trackStudyProgress(progress)
+          .trackStudyProgress(100)
           .accounts({
             study: studyPda,
+            team: teamFor(studyPda),
+            researcher: researcherPda,
             participant: participantPda,
+            enrollment: enrollmentFor(studyPda, participantPda),
             authority: participant.publicKey,
           })
           .signers([participant])
           .rpc();
 
-        const studyAccount = await program.account.study.fetch(studyPda);
-        expect(studyAccount.progress).to.equal(progress);
+        expect.fail("Should have thrown an error");
       } catch (error) {
-        console.error("Study progress tracking error:", error);
-        throw error;
+        expect(error).to.exist;
       }
     });
 
@@ -876,11 +1752,18 @@ describe("recru-search", () => {
             "Test Description",
             "test_criteria_hash",
             1000,
-            10
+            10,
+            { survey: {} }
           )
           .accounts({
             study: studyPda,
+            team: teamFor(studyPda),
             researcher: researcherPda,
+            vault: vaultFor(studyPda),
+            rewardMint: studyMint.publicKey,
+            researcherTokenAccount: researcherTokenAccount,
+            authority: researcher.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
           })
           .signers([researcher])
@@ -892,10 +1775,14 @@ describe("recru-search", () => {
             .completeStudy()
             .accounts({
               study: studyPda,
+              team: teamFor(studyPda),
               participant: participantPda,
+              enrollment: enrollmentFor(studyPda, participantPda),
               researcher: researcherPda,
-              researcherTokenAccount: researcherTokenAccount,
+              leadResearcher: researcherPda,
+              vault: vaultFor(studyPda),
               participantTokenAccount: participantTokenAccount,
+              authority: researcher.publicKey,
               tokenProgram: TOKEN_PROGRAM_ID,
               systemProgram: SystemProgram.programId,
             })
@@ -911,5 +1798,2806 @@ describe("recru-search", () => {
         throw error;
       }
     });
+
+    it("Closes a study and refunds unspent escrow", async () => {
+      try {
+        const initialResearcherBalance = await getAccount(
+          provider.connection,
+          researcherTokenAccount
+        ).then(acc => acc.amount);
+        const vaultBalance = await getAccount(
+          provider.connection,
+          vaultFor(studyPda)
+        ).then(acc => acc.amount);
+
+        await program.methods
+          .transitionStudy({ closed: {} })
+          .accounts({
+            study: studyPda,
+            team: teamFor(studyPda),
+            researcher: researcherPda,
+            admin: adminPda,
+            ethicsApproval: null,
+            authority: researcher.publicKey,
+          })
+          .signers([researcher])
+          .rpc();
+
+        await program.methods
+          .refundEscrow()
+          .accounts({
+            study: studyPda,
+            team: teamFor(studyPda),
+            researcher: researcherPda,
+            vault: vaultFor(studyPda),
+            researcherTokenAccount: researcherTokenAccount,
+            authority: researcher.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([researcher])
+          .rpc();
+
+        const studyAccount = await program.account.study.fetch(studyPda);
+        const outstanding =
+          (studyAccount.currentParticipants - studyAccount.completedParticipants) *
+          studyAccount.rewardAmount.toNumber();
+        const finalResearcherBalance = await getAccount(
+          provider.connection,
+          researcherTokenAccount
+        ).then(acc => acc.amount);
+
+        expect(studyAccount.status).to.have.property("closed");
+        expect(Number(finalResearcherBalance)).to.equal(
+          Number(initialResearcherBalance) + Number(vaultBalance) - outstanding
+        );
+      } catch (error) {
+        console.error("Study close error:", error);
+        throw error;
+      }
+    });
+  });
+
+  describe("Withdrawal and waitlist", () => {
+    const leaver = Keypair.generate();
+    const removed = Keypair.generate();
+    const waiter = Keypair.generate();
+    const [waiterPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("participant"), waiter.publicKey.toBuffer()],
+      program.programId
+    );
+    const [leaverPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("participant"), leaver.publicKey.toBuffer()],
+      program.programId
+    );
+    const [removedPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("participant"), removed.publicKey.toBuffer()],
+      program.programId
+    );
+    const rewardAmount = new anchor.BN(1000000000);
+    let exitStudyPda: PublicKey;
+    let waitlistPda: PublicKey;
+    let leaverTokenAccount: PublicKey;
+    let removedTokenAccount: PublicKey;
+
+    before(async () => {
+      const { studiesCreated } = await program.account.researcher.fetch(researcherPda);
+      exitStudyPda = studyFor(researcherPda, studiesCreated);
+      [waitlistPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("waitlist"), exitStudyPda.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .createStudy("Exit Study", "Prorated exits", "exit_criteria_hash", rewardAmount, 2, { survey: {} })
+        .accounts({
+          study: exitStudyPda,
+          team: teamFor(exitStudyPda),
+          researcher: researcherPda,
+          vault: vaultFor(exitStudyPda),
+          rewardMint: studyMint.publicKey,
+          researcherTokenAccount: researcherTokenAccount,
+          authority: researcher.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([researcher])
+        .rpc();
+
+      await program.methods
+        .setExitPolicy(true)
+        .accounts({ study: exitStudyPda, team: teamFor(exitStudyPda), researcher: researcherPda, authority: researcher.publicKey })
+        .signers([researcher])
+        .rpc();
+
+      await approveStudy(exitStudyPda);
+      await program.methods
+        .transitionStudy({ recruiting: {} })
+        .accounts({
+          study: exitStudyPda,
+          team: teamFor(exitStudyPda),
+          researcher: researcherPda,
+          admin: adminPda,
+          ethicsApproval: ethicsApprovalFor(exitStudyPda),
+          authority: researcher.publicKey,
+        })
+        .signers([researcher])
+        .rpc();
+
+      for (const [keypair, pda] of [[leaver, leaverPda], [removed, removedPda], [waiter, waiterPda]] as [Keypair, PublicKey][]) {
+        const signature = await provider.connection.requestAirdrop(keypair.publicKey, LAMPORTS_PER_SOL);
+        await provider.connection.confirmTransaction(signature);
+
+        await program.methods
+          .registerParticipant(buildAttributeTree([{ kind: "region", value: "EU" }]).root)
+          .accounts({
+            participant: pda,
+            authority: keypair.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([keypair])
+          .rpc();
+      }
+
+      // Two slots, both taken
+      for (const [keypair, pda] of [[leaver, leaverPda], [removed, removedPda]] as [Keypair, PublicKey][]) {
+        await program.methods
+          .joinStudy([], null)
+          .accounts({
+            study: exitStudyPda,
+            admin: adminPda,
+            participant: pda,
+            enrollment: enrollmentFor(exitStudyPda, pda),
+            ethicsApproval: ethicsApprovalFor(exitStudyPda),
+            studyConsent: null,
+            guardianship: null,
+            vault: vaultFor(exitStudyPda),
+            waitlist: null,
+            allocation: null,
+            attestationNonce: null,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            authority: keypair.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([keypair])
+          .rpc();
+      }
+
+      leaverTokenAccount = await createAccount(provider.connection, leaver, studyMint.publicKey, leaver.publicKey);
+      removedTokenAccount = await createAccount(provider.connection, removed, studyMint.publicKey, removed.publicKey);
+
+      await program.methods
+        .trackStudyProgress(50)
+        .accounts({
+          study: exitStudyPda,
+          team: teamFor(exitStudyPda),
+          researcher: researcherPda,
+          participant: leaverPda,
+          enrollment: enrollmentFor(exitStudyPda, leaverPda),
+          authority: researcher.publicKey,
+        })
+        .signers([researcher])
+        .rpc();
+    });
+
+    it("Queues a participant on a full study's waitlist", async () => {
+      await program.methods
+        .joinWaitlist()
+        .accounts({
+          study: exitStudyPda,
+          participant: waiterPda,
+          enrollment: enrollmentFor(exitStudyPda, waiterPda),
+          waitlist: waitlistPda,
+          authority: waiter.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([waiter])
+        .rpc();
+
+      const waitlistAccount = await program.account.waitlist.fetch(waitlistPda);
+      expect(waitlistAccount.queue.map((e) => e.participant)).to.eql([waiterPda]);
+
+      const studyAccount = await program.account.study.fetch(exitStudyPda);
+      expect(studyAccount.waitlisted).to.equal(1);
+    });
+
+    it("Requires the waitlist once participants are queued", async () => {
+      try {
+        await program.methods
+          .withdrawFromStudy({ participantRequest: {} })
+          .accounts({
+            study: exitStudyPda,
+            participant: leaverPda,
+            enrollment: enrollmentFor(exitStudyPda, leaverPda),
+            vault: vaultFor(exitStudyPda),
+            participantTokenAccount: leaverTokenAccount,
+            waitlist: null,
+            studyConsent: null,
+            authority: leaver.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([leaver])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("WaitlistRequired");
+      }
+    });
+
+    it("Pays a prorated reward when a participant withdraws", async () => {
+      await program.methods
+        .withdrawFromStudy({ participantRequest: {} })
+        .accounts({
+          study: exitStudyPda,
+          participant: leaverPda,
+          enrollment: enrollmentFor(exitStudyPda, leaverPda),
+          vault: vaultFor(exitStudyPda),
+          participantTokenAccount: leaverTokenAccount,
+          waitlist: waitlistPda,
+          studyConsent: null,
+          authority: leaver.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([leaver])
+        .rpc();
+
+      const enrollmentAccount = await program.account.enrollment.fetch(
+        enrollmentFor(exitStudyPda, leaverPda)
+      );
+      expect(enrollmentAccount.status).to.have.property("withdrawn");
+      expect(enrollmentAccount.exitReason).to.have.property("participantRequest");
+      expect(enrollmentAccount.rewardPaid.toString()).to.equal(rewardAmount.divn(2).toString());
+
+      const tokenAccount = await getAccount(provider.connection, leaverTokenAccount);
+      expect(tokenAccount.amount.toString()).to.equal(rewardAmount.divn(2).toString());
+
+      const studyAccount = await program.account.study.fetch(exitStudyPda);
+      const participantAccount = await program.account.participant.fetch(leaverPda);
+      expect(studyAccount.currentParticipants).to.equal(1);
+      // Leaving doesn't shrink the enrolled total the completion rate is measured against
+      expect(studyAccount.analytics.totalParticipants).to.equal(2);
+      expect(participantAccount.activeStudies).to.equal(0);
+
+      // The freed slot is held for the head of the waitlist
+      const waitlistAccount = await program.account.waitlist.fetch(waitlistPda);
+      expect(waitlistAccount.queue).to.be.empty;
+      expect(waitlistAccount.offers.map((o) => o.participant)).to.eql([waiterPda]);
+      expect(studyAccount.reservedSlots).to.equal(1);
+    });
+
+    it("Lets the promoted participant take the held slot", async () => {
+      await program.methods
+        .joinStudy([], null)
+        .accounts({
+          study: exitStudyPda,
+          admin: adminPda,
+          participant: waiterPda,
+          enrollment: enrollmentFor(exitStudyPda, waiterPda),
+          ethicsApproval: ethicsApprovalFor(exitStudyPda),
+          studyConsent: null,
+          guardianship: null,
+          vault: vaultFor(exitStudyPda),
+          waitlist: waitlistPda,
+          allocation: null,
+          attestationNonce: null,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          authority: waiter.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([waiter])
+        .rpc();
+
+      const studyAccount = await program.account.study.fetch(exitStudyPda);
+      expect(studyAccount.currentParticipants).to.equal(2);
+      expect(studyAccount.reservedSlots).to.equal(0);
+
+      const waitlistAccount = await program.account.waitlist.fetch(waitlistPda);
+      expect(waitlistAccount.offers).to.be.empty;
+    });
+
+    it("Fails to withdraw from the same study twice", async () => {
+      try {
+        await program.methods
+          .withdrawFromStudy({ participantRequest: {} })
+          .accounts({
+            study: exitStudyPda,
+            participant: leaverPda,
+            enrollment: enrollmentFor(exitStudyPda, leaverPda),
+            vault: vaultFor(exitStudyPda),
+            participantTokenAccount: leaverTokenAccount,
+            waitlist: null,
+            studyConsent: null,
+            authority: leaver.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([leaver])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("NotEnrolled");
+      }
+    });
+
+    it("Allows the researcher to remove a participant", async () => {
+      await program.methods
+        .removeParticipantFromStudy({ nonCompliance: {} })
+        .accounts({
+          study: exitStudyPda,
+          team: teamFor(exitStudyPda),
+          researcher: researcherPda,
+          participant: removedPda,
+          enrollment: enrollmentFor(exitStudyPda, removedPda),
+          vault: vaultFor(exitStudyPda),
+          participantTokenAccount: removedTokenAccount,
+          waitlist: null,
+          studyConsent: null,
+          authority: researcher.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([researcher])
+        .rpc();
+
+      const enrollmentAccount = await program.account.enrollment.fetch(
+        enrollmentFor(exitStudyPda, removedPda)
+      );
+      expect(enrollmentAccount.status).to.have.property("removed");
+      expect(enrollmentAccount.exitReason).to.have.property("nonCompliance");
+      // No progress was made, so nothing is owed
+      expect(enrollmentAccount.rewardPaid.toNumber()).to.equal(0);
+
+      const studyAccount = await program.account.study.fetch(exitStudyPda);
+      expect(studyAccount.currentParticipants).to.equal(1);
+    });
+
+    it("Rejects removal by anyone but the study's researcher", async () => {
+      try {
+        await program.methods
+          .removeParticipantFromStudy({ other: {} })
+          .accounts({
+            study: exitStudyPda,
+            team: teamFor(exitStudyPda),
+            researcher: researcherPda,
+            participant: leaverPda,
+            enrollment: enrollmentFor(exitStudyPda, leaverPda),
+            vault: vaultFor(exitStudyPda),
+            participantTokenAccount: leaverTokenAccount,
+            waitlist: null,
+            studyConsent: null,
+            authority: leaver.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([leaver])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("UnauthorizedResearcher");
+      }
+    });
+  });
+
+  describe("Schedule", () => {
+    const latecomer = Keypair.generate();
+    const [latecomerPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("participant"), latecomer.publicKey.toBuffer()],
+      program.programId
+    );
+    const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+    let scheduledStudyPda: PublicKey;
+
+    const createScheduledStudy = async (schedule: object) => {
+      const { studiesCreated } = await program.account.researcher.fetch(researcherPda);
+      const study = studyFor(researcherPda, studiesCreated);
+      await program.methods
+        .createStudy("Scheduled Study", "Time-boxed", "schedule_criteria_hash", new anchor.BN(1000000), 2, { survey: {} })
+        .accounts({
+          study,
+          team: teamFor(study),
+          researcher: researcherPda,
+          vault: vaultFor(study),
+          rewardMint: studyMint.publicKey,
+          researcherTokenAccount: researcherTokenAccount,
+          authority: researcher.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([researcher])
+        .rpc();
+      await program.methods
+        .setStudySchedule(schedule)
+        .accounts({ study, team: teamFor(study), researcher: researcherPda, authority: researcher.publicKey })
+        .signers([researcher])
+        .rpc();
+      await approveStudy(study);
+      await program.methods
+        .transitionStudy({ recruiting: {} })
+        .accounts({
+          study,
+          team: teamFor(study),
+          researcher: researcherPda,
+          admin: adminPda,
+          ethicsApproval: ethicsApprovalFor(study),
+          authority: researcher.publicKey,
+        })
+        .signers([researcher])
+        .rpc();
+      return study;
+    };
+
+    const join = (study: PublicKey) =>
+      program.methods
+        .joinStudy([], null)
+        .accounts({
+          study,
+          admin: adminPda,
+          participant: latecomerPda,
+          enrollment: enrollmentFor(study, latecomerPda),
+          ethicsApproval: ethicsApprovalFor(study),
+          studyConsent: null,
+          guardianship: null,
+          vault: vaultFor(study),
+          waitlist: null,
+          allocation: null,
+          attestationNonce: null,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          authority: latecomer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([latecomer])
+        .rpc();
+
+    before(async () => {
+      const signature = await provider.connection.requestAirdrop(latecomer.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(signature);
+      await program.methods
+        .registerParticipant(buildAttributeTree([{ kind: "region", value: "EU" }]).root)
+        .accounts({
+          participant: latecomerPda,
+          authority: latecomer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([latecomer])
+        .rpc();
+    });
+
+    it("Rejects a schedule whose recruitment closes before it opens", async () => {
+      const now = Math.floor(Date.now() / 1000);
+      try {
+        await createScheduledStudy({
+          recruitmentOpensAt: new anchor.BN(now + 7200),
+          recruitmentClosesAt: new anchor.BN(now + 3600),
+          completionWindow: null,
+          endsAt: null,
+        });
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("InvalidSchedule");
+      }
+    });
+
+    it("Rejects joins before recruitment opens", async () => {
+      const now = Math.floor(Date.now() / 1000);
+      const study = await createScheduledStudy({
+        recruitmentOpensAt: new anchor.BN(now + 3600),
+        recruitmentClosesAt: null,
+        completionWindow: null,
+        endsAt: null,
+      });
+
+      try {
+        await join(study);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("RecruitmentNotOpen");
+      }
+    });
+
+    it("Stamps a completion deadline on new enrollments", async () => {
+      const now = Math.floor(Date.now() / 1000);
+      scheduledStudyPda = await createScheduledStudy({
+        recruitmentOpensAt: null,
+        recruitmentClosesAt: null,
+        completionWindow: new anchor.BN(2),
+        endsAt: new anchor.BN(now + 10),
+      });
+      await join(scheduledStudyPda);
+
+      const enrollmentAccount = await program.account.enrollment.fetch(
+        enrollmentFor(scheduledStudyPda, latecomerPda)
+      );
+      expect(enrollmentAccount.deadline.toNumber()).to.equal(enrollmentAccount.joinedAt.toNumber() + 2);
+    });
+
+    it("Cranks overdue enrollments to expired and closes ended studies", async () => {
+      await sleep(11000);
+      const cranker = Keypair.generate();
+      const { reputation } = await program.account.participant.fetch(latecomerPda);
+
+      await program.methods
+        .crankExpire()
+        .accounts({
+          study: scheduledStudyPda,
+          waitlist: null,
+          cranker: cranker.publicKey,
+        })
+        .remainingAccounts([
+          { pubkey: enrollmentFor(scheduledStudyPda, latecomerPda), isSigner: false, isWritable: true },
+          { pubkey: latecomerPda, isSigner: false, isWritable: true },
+          // Never created: the enrollment did not need consent
+          { pubkey: studyConsentFor(scheduledStudyPda, latecomerPda), isSigner: false, isWritable: true },
+        ])
+        .signers([cranker])
+        .rpc();
+
+      const enrollmentAccount = await program.account.enrollment.fetch(
+        enrollmentFor(scheduledStudyPda, latecomerPda)
+      );
+      expect(enrollmentAccount.status).to.have.property("expired");
+
+      const studyAccount = await program.account.study.fetch(scheduledStudyPda);
+      expect(studyAccount.currentParticipants).to.equal(0);
+      expect(studyAccount.status).to.have.property("closed");
+
+      const latecomer = await program.account.participant.fetch(latecomerPda);
+      expect(latecomer.reputation.score).to.be.lessThan(reputation.score);
+    });
+  });
+
+  describe("Study types", () => {
+    const createTypedStudy = async (studyType: StudyType) => {
+      const { studiesCreated } = await program.account.researcher.fetch(researcherPda);
+      const study = studyFor(researcherPda, studiesCreated);
+      await program.methods
+        .createStudy("Typed Study", "Type rules", "typed_criteria_hash", new anchor.BN(1000000), 1, studyType)
+        .accounts({
+          study,
+          team: teamFor(study),
+          researcher: researcherPda,
+          vault: vaultFor(study),
+          rewardMint: studyMint.publicKey,
+          researcherTokenAccount: researcherTokenAccount,
+          authority: researcher.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([researcher])
+        .rpc();
+      return study;
+    };
+
+    const openRecruitment = async (study: PublicKey) => {
+      await approveStudy(study);
+      await program.methods
+        .transitionStudy({ recruiting: {} })
+        .accounts({
+          study,
+          team: teamFor(study),
+          researcher: researcherPda,
+          admin: adminPda,
+          ethicsApproval: ethicsApprovalFor(study),
+          authority: researcher.publicKey,
+        })
+        .signers([researcher])
+        .rpc();
+    };
+
+    it("Requires session slots before an interview study recruits", async () => {
+      const study = await createTypedStudy({ interview: {} });
+
+      try {
+        await openRecruitment(study);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("MissingSessionSlots");
+      }
+
+      await program.methods
+        .setSessionSlots([
+          { startsAt: new anchor.BN(Math.floor(Date.now() / 1000) + 86400), durationMinutes: 45, capacity: 1 },
+        ])
+        .accounts({ study, team: teamFor(study), researcher: researcherPda, authority: researcher.publicKey })
+        .signers([researcher])
+        .rpc();
+      await openRecruitment(study);
+
+      const studyAccount = await program.account.study.fetch(study);
+      expect(studyAccount.studyType).to.have.property("interview");
+      expect(studyAccount.status).to.have.property("recruiting");
+    });
+
+    it("Requires a consent version before a clinical study recruits", async () => {
+      const study = await createTypedStudy({ clinical: {} });
+
+      try {
+        await openRecruitment(study);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("MissingConsentVersion");
+      }
+
+      await program.methods
+        .publishConsentVersion("v1", [...sha256(Buffer.from("consent-form-v1"))])
+        .accounts({ study, team: teamFor(study), researcher: researcherPda, authority: researcher.publicKey })
+        .signers([researcher])
+        .rpc();
+      await openRecruitment(study);
+
+      const studyAccount = await program.account.study.fetch(study);
+      expect(studyAccount.consentVersion).to.equal("v1");
+    });
+
+    it("Blocks new joins once the ethics approval is revoked", async () => {
+      const study = await createTypedStudy({ observational: {} });
+      await openRecruitment(study);
+
+      await program.methods
+        .revokeEthicsApproval()
+        .accounts({
+          ethicsApproval: ethicsApprovalFor(study),
+          board: board.publicKey,
+        })
+        .signers([board])
+        .rpc();
+
+      try {
+        await program.methods
+          .joinStudy([], null)
+          .accounts({
+            study,
+            admin: adminPda,
+            participant: participantPda,
+            enrollment: enrollmentFor(study, participantPda),
+            ethicsApproval: ethicsApprovalFor(study),
+            studyConsent: null,
+            guardianship: null,
+            vault: vaultFor(study),
+            waitlist: null,
+            allocation: null,
+            attestationNonce: null,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            authority: participant.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([participant])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("EthicsApprovalInactive");
+      }
+    });
+  });
+
+  describe("Study team", () => {
+    const assistant = Keypair.generate();
+    const [assistantPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("researcher"), assistant.publicKey.toBuffer()],
+      program.programId
+    );
+    let teamStudyPda: PublicKey;
+
+    before(async () => {
+      const signature = await provider.connection.requestAirdrop(assistant.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(signature);
+      await program.methods
+        .registerResearcher("Assistant University", "assistant_credentials_hash")
+        .accounts({
+          researcher: assistantPda,
+          authority: assistant.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([assistant])
+        .rpc();
+
+      const { studiesCreated } = await program.account.researcher.fetch(researcherPda);
+      teamStudyPda = studyFor(researcherPda, studiesCreated);
+      await program.methods
+        .createStudy("Team Study", "Shared study", "team_criteria_hash", new anchor.BN(1000000), 1, { survey: {} })
+        .accounts({
+          study: teamStudyPda,
+          team: teamFor(teamStudyPda),
+          researcher: researcherPda,
+          vault: vaultFor(teamStudyPda),
+          rewardMint: studyMint.publicKey,
+          researcherTokenAccount: researcherTokenAccount,
+          authority: researcher.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([researcher])
+        .rpc();
+    });
+
+    it("Makes the study creator its principal investigator", async () => {
+      const team = await program.account.studyTeam.fetch(teamFor(teamStudyPda));
+      expect(team.members.length).to.equal(1);
+      expect(team.members[0].researcher.toString()).to.equal(researcherPda.toString());
+      expect(team.members[0].role).to.deep.equal({ principalInvestigator: {} });
+    });
+
+    it("Lets the PI add a research assistant", async () => {
+      await program.methods
+        .addTeamMember({ researchAssistant: {} })
+        .accounts({
+          study: teamStudyPda,
+          researcher: researcherPda,
+          team: teamFor(teamStudyPda),
+          member: assistantPda,
+          authority: researcher.publicKey,
+        })
+        .signers([researcher])
+        .rpc();
+
+      const team = await program.account.studyTeam.fetch(teamFor(teamStudyPda));
+      expect(team.members.length).to.equal(2);
+      expect(team.members[1].role).to.deep.equal({ researchAssistant: {} });
+    });
+
+    it("Rejects a second principal investigator", async () => {
+      try {
+        await program.methods
+          .addTeamMember({ principalInvestigator: {} })
+          .accounts({
+            study: teamStudyPda,
+            researcher: researcherPda,
+            team: teamFor(teamStudyPda),
+            member: assistantPda,
+            authority: researcher.publicKey,
+          })
+          .signers([researcher])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("InvalidTeamRole");
+      }
+    });
+
+    it("Stops a research assistant from configuring the study", async () => {
+      try {
+        await program.methods
+          .setExitPolicy(true)
+          .accounts({
+            study: teamStudyPda,
+            team: teamFor(teamStudyPda),
+            researcher: assistantPda,
+            authority: assistant.publicKey,
+          })
+          .signers([assistant])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("UnauthorizedResearcher");
+      }
+    });
+
+    it("Only lets the PI manage the team", async () => {
+      try {
+        await program.methods
+          .removeTeamMember()
+          .accounts({
+            study: teamStudyPda,
+            researcher: assistantPda,
+            team: teamFor(teamStudyPda),
+            member: assistantPda,
+            authority: assistant.publicKey,
+          })
+          .signers([assistant])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("UnauthorizedResearcher");
+      }
+
+      await program.methods
+        .removeTeamMember()
+        .accounts({
+          study: teamStudyPda,
+          researcher: researcherPda,
+          team: teamFor(teamStudyPda),
+          member: assistantPda,
+          authority: researcher.publicKey,
+        })
+        .signers([researcher])
+        .rpc();
+
+      const team = await program.account.studyTeam.fetch(teamFor(teamStudyPda));
+      expect(team.members.length).to.equal(1);
+    });
+  });
+
+  describe("Demographic quotas", () => {
+    const members = ["EU", "EU", "APAC"].map((region) => {
+      const keypair = Keypair.generate();
+      const [pda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("participant"), keypair.publicKey.toBuffer()],
+        program.programId
+      );
+      return { keypair, pda, attributes: buildAttributeTree([{ kind: "region", value: region }]) };
+    });
+    const [firstEu, secondEu, apac] = members;
+    let quotaStudyPda: PublicKey;
+
+    const joinQuotaStudy = (member: typeof firstEu) =>
+      program.methods
+        .joinStudy([member.attributes.disclose("region")], null)
+        .accounts({
+          study: quotaStudyPda,
+          admin: adminPda,
+          participant: member.pda,
+          enrollment: enrollmentFor(quotaStudyPda, member.pda),
+          ethicsApproval: ethicsApprovalFor(quotaStudyPda),
+          studyConsent: null,
+          guardianship: null,
+          vault: vaultFor(quotaStudyPda),
+          waitlist: null,
+          allocation: null,
+          attestationNonce: null,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          authority: member.keypair.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([member.keypair])
+        .rpc();
+
+    before(async () => {
+      for (const member of members) {
+        const signature = await provider.connection.requestAirdrop(member.keypair.publicKey, LAMPORTS_PER_SOL);
+        await provider.connection.confirmTransaction(signature);
+        await program.methods
+          .registerParticipant(member.attributes.root)
+          .accounts({
+            participant: member.pda,
+            authority: member.keypair.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([member.keypair])
+          .rpc();
+      }
+
+      const { studiesCreated } = await program.account.researcher.fetch(researcherPda);
+      quotaStudyPda = studyFor(researcherPda, studiesCreated);
+      await program.methods
+        .createStudy("Quota Study", "Balanced regions", "quota_criteria_hash", new anchor.BN(1000000), 2, { survey: {} })
+        .accounts({
+          study: quotaStudyPda,
+          team: teamFor(quotaStudyPda),
+          researcher: researcherPda,
+          vault: vaultFor(quotaStudyPda),
+          rewardMint: studyMint.publicKey,
+          researcherTokenAccount: researcherTokenAccount,
+          authority: researcher.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([researcher])
+        .rpc();
+
+      // At most one from the EU, and one slot held for the US
+      await program.methods
+        .setQuotas([
+          { kind: { region: {} }, value: "EU", minCount: 0, maxCount: 1, enrolled: 0 },
+          { kind: { region: {} }, value: "US", minCount: 1, maxCount: 2, enrolled: 0 },
+        ])
+        .accounts({ study: quotaStudyPda, team: teamFor(quotaStudyPda), researcher: researcherPda, authority: researcher.publicKey })
+        .signers([researcher])
+        .rpc();
+
+      await approveStudy(quotaStudyPda);
+      await program.methods
+        .transitionStudy({ recruiting: {} })
+        .accounts({
+          study: quotaStudyPda,
+          team: teamFor(quotaStudyPda),
+          researcher: researcherPda,
+          admin: adminPda,
+          ethicsApproval: ethicsApprovalFor(quotaStudyPda),
+          authority: researcher.publicKey,
+        })
+        .signers([researcher])
+        .rpc();
+    });
+
+    it("Rejects quotas whose minimums exceed the study size", async () => {
+      try {
+        await program.methods
+          .setQuotas([{ kind: { gender: {} }, value: "F", minCount: 3, maxCount: 3, enrolled: 0 }])
+          .accounts({ study: quotaStudyPda, team: teamFor(quotaStudyPda), researcher: researcherPda, authority: researcher.publicKey })
+          .signers([researcher])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error).to.exist;
+      }
+    });
+
+    it("Counts a join against the participant's stratum", async () => {
+      await joinQuotaStudy(firstEu);
+
+      const study = await program.account.study.fetch(quotaStudyPda);
+      expect(study.quotas[0].enrolled).to.equal(1);
+      const enrollment = await program.account.enrollment.fetch(enrollmentFor(quotaStudyPda, firstEu.pda));
+      expect(Buffer.from(enrollment.strata)).to.deep.equal(Buffer.from([0]));
+    });
+
+    it("Rejects a join into a stratum at its maximum", async () => {
+      try {
+        await joinQuotaStudy(secondEu);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("StratumFull");
+      }
+    });
+
+    it("Holds the last slot for a stratum below its minimum", async () => {
+      try {
+        await joinQuotaStudy(apac);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("StratumFull");
+      }
+    });
+
+    it("Frees the stratum when the participant withdraws", async () => {
+      const tokenAccount = await createAccount(
+        provider.connection,
+        firstEu.keypair,
+        studyMint.publicKey,
+        firstEu.keypair.publicKey
+      );
+      await program.methods
+        .withdrawFromStudy({ participantRequest: {} })
+        .accounts({
+          study: quotaStudyPda,
+          participant: firstEu.pda,
+          enrollment: enrollmentFor(quotaStudyPda, firstEu.pda),
+          vault: vaultFor(quotaStudyPda),
+          participantTokenAccount: tokenAccount,
+          waitlist: null,
+          studyConsent: null,
+          authority: firstEu.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([firstEu.keypair])
+        .rpc();
+
+      const study = await program.account.study.fetch(quotaStudyPda);
+      expect(study.quotas[0].enrolled).to.equal(0);
+
+      await joinQuotaStudy(secondEu);
+    });
+  });
+
+  describe("Randomization", () => {
+    const seed = randomBytes(32);
+    const subjects = [Keypair.generate(), Keypair.generate()].map((keypair) => {
+      const [pda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("participant"), keypair.publicKey.toBuffer()],
+        program.programId
+      );
+      return { keypair, pda };
+    });
+    let trialPda: PublicKey;
+    let allocationPda: PublicKey;
+
+    const joinTrial = (subject: typeof subjects[number], withAllocation = true) =>
+      program.methods
+        .joinStudy([], null)
+        .accounts({
+          study: trialPda,
+          admin: adminPda,
+          participant: subject.pda,
+          enrollment: enrollmentFor(trialPda, subject.pda),
+          ethicsApproval: ethicsApprovalFor(trialPda),
+          studyConsent: null,
+          guardianship: null,
+          vault: vaultFor(trialPda),
+          waitlist: null,
+          allocation: withAllocation ? allocationPda : null,
+          attestationNonce: null,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          authority: subject.keypair.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([subject.keypair])
+        .rpc();
+
+    const drawArm = (subject: typeof subjects[number]) =>
+      program.methods
+        .drawArm()
+        .accounts({
+          study: trialPda,
+          allocation: allocationPda,
+          enrollment: enrollmentFor(trialPda, subject.pda),
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          cranker: provider.wallet.publicKey,
+        })
+        .rpc();
+
+    const revealSeed = (value: Buffer) =>
+      program.methods
+        .revealAllocationSeed([...value])
+        .accounts({
+          study: trialPda,
+          researcher: researcherPda,
+          team: teamFor(trialPda),
+          allocation: allocationPda,
+          authority: researcher.publicKey,
+        })
+        .signers([researcher])
+        .rpc();
+
+    before(async () => {
+      for (const subject of subjects) {
+        const signature = await provider.connection.requestAirdrop(subject.keypair.publicKey, LAMPORTS_PER_SOL);
+        await provider.connection.confirmTransaction(signature);
+        await program.methods
+          .registerParticipant(buildAttributeTree([{ kind: "region", value: "EU" }]).root)
+          .accounts({
+            participant: subject.pda,
+            authority: subject.keypair.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([subject.keypair])
+          .rpc();
+      }
+
+      const { studiesCreated } = await program.account.researcher.fetch(researcherPda);
+      trialPda = studyFor(researcherPda, studiesCreated);
+      [allocationPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("allocation"), trialPda.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .createStudy("Randomized Study", "Two arms", "trial_criteria_hash", new anchor.BN(1000000), 2, { survey: {} })
+        .accounts({
+          study: trialPda,
+          team: teamFor(trialPda),
+          researcher: researcherPda,
+          vault: vaultFor(trialPda),
+          rewardMint: studyMint.publicKey,
+          researcherTokenAccount: researcherTokenAccount,
+          authority: researcher.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([researcher])
+        .rpc();
+    });
+
+    it("Commits to arms and a seed while the study is a draft", async () => {
+      await program.methods
+        .configureRandomization(
+          { block: {} },
+          false,
+          [
+            { name: "control", ratio: 1, assigned: 0 },
+            { name: "treatment", ratio: 1, assigned: 0 },
+          ],
+          1,
+          [...sha256(seed)]
+        )
+        .accounts({
+          study: trialPda,
+          researcher: researcherPda,
+          team: teamFor(trialPda),
+          allocation: allocationPda,
+          authority: researcher.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([researcher])
+        .rpc();
+
+      const study = await program.account.study.fetch(trialPda);
+      expect(study.randomized).to.be.true;
+
+      try {
+        await revealSeed(seed);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("InvalidStudyPhase");
+      }
+
+      await approveStudy(trialPda);
+      await program.methods
+        .transitionStudy({ recruiting: {} })
+        .accounts({
+          study: trialPda,
+          team: teamFor(trialPda),
+          researcher: researcherPda,
+          admin: adminPda,
+          ethicsApproval: ethicsApprovalFor(trialPda),
+          authority: researcher.publicKey,
+        })
+        .signers([researcher])
+        .rpc();
+    });
+
+    it("Requires the allocation accounts and a revealed seed to join", async () => {
+      try {
+        await joinTrial(subjects[0], false);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("AllocationRequired");
+      }
+
+      try {
+        await joinTrial(subjects[0]);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("AllocationSeedNotRevealed");
+      }
+    });
+
+    it("Rejects a seed that does not match the commitment", async () => {
+      try {
+        await revealSeed(randomBytes(32));
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("InvalidAllocationSeed");
+      }
+      await revealSeed(seed);
+    });
+
+    it("Queues the draw at join so the arm is not known beforehand", async () => {
+      for (const subject of subjects) {
+        await joinTrial(subject);
+      }
+
+      const enrollment = await program.account.enrollment.fetch(enrollmentFor(trialPda, subjects[1].pda));
+      expect(enrollment.arm).to.be.null;
+      expect(enrollment.pendingDraw.sequence).to.equal(1);
+
+      const allocation = await program.account.allocation.fetch(allocationPda);
+      expect(allocation.queued).to.equal(2);
+      expect(allocation.allocations).to.equal(0);
+
+      try {
+        await drawArm(subjects[1]);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("DrawOutOfOrder");
+      }
+    });
+
+    it("Fills a 1:1 block with one participant per arm", async () => {
+      // The join slots' hashes are recorded once a later slot starts
+      const joined = (await program.account.enrollment.fetch(enrollmentFor(trialPda, subjects[1].pda))).pendingDraw.slot;
+      while ((await provider.connection.getSlot()) <= joined.toNumber()) {
+        await new Promise((resolve) => setTimeout(resolve, 200));
+      }
+      for (const subject of subjects) {
+        await drawArm(subject);
+      }
+
+      const arms = await Promise.all(
+        subjects.map(async (subject) => (await program.account.enrollment.fetch(enrollmentFor(trialPda, subject.pda))).arm)
+      );
+      expect([...arms].sort()).to.deep.equal([0, 1]);
+
+      const allocation = await program.account.allocation.fetch(allocationPda);
+      expect(allocation.allocations).to.equal(2);
+      expect(allocation.arms.map((arm) => arm.assigned)).to.deep.equal([1, 1]);
+    });
+  });
+
+  describe("Blinded allocation", () => {
+    const key = randomBytes(32);
+    const plannedArms = [1, 0];
+    const subjects = [Keypair.generate(), Keypair.generate()].map((keypair) => {
+      const [pda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("participant"), keypair.publicKey.toBuffer()],
+        program.programId
+      );
+      return { keypair, pda };
+    });
+    let blindStudyPda: PublicKey;
+    let allocationPda: PublicKey;
+    let commitments: Buffer[];
+
+    const listHash = (entries: Buffer[]) =>
+      entries.reduceRight((rest, commitment) => sha256(commitment, rest), Buffer.alloc(32));
+    const postCommitments = (entries: Buffer[], rest: Buffer) =>
+      program.methods
+        .postArmCommitments(null, entries.map((entry) => [...entry]), [...rest])
+        .accounts({
+          study: blindStudyPda,
+          researcher: researcherPda,
+          team: teamFor(blindStudyPda),
+          allocation: allocationPda,
+          authority: researcher.publicKey,
+        })
+        .signers([researcher])
+        .rpc();
+    // Unstratified commitments use 0xff as the stratum byte
+    const saltFor = (sequence: number) => {
+      const index = Buffer.alloc(4);
+      index.writeUInt32LE(sequence);
+      return sha256(key, blindStudyPda.toBuffer(), Buffer.from([0xff]), index);
+    };
+    const unblindingFor = (enrollment: PublicKey) =>
+      PublicKey.findProgramAddressSync([Buffer.from("unblinding"), enrollment.toBuffer()], program.programId)[0];
+
+    const emergencyUnblind = (coSigner: Keypair, arm: number, salt: Buffer) => {
+      const enrollment = enrollmentFor(blindStudyPda, subjects[0].pda);
+      return program.methods
+        .emergencyUnblind(arm, [...salt], "Serious adverse event")
+        .accounts({
+          study: blindStudyPda,
+          researcher: researcherPda,
+          team: teamFor(blindStudyPda),
+          allocation: allocationPda,
+          enrollment,
+          unblindingRecord: unblindingFor(enrollment),
+          admin: adminPda,
+          ethicsApproval: ethicsApprovalFor(blindStudyPda),
+          coSigner: coSigner.publicKey,
+          authority: researcher.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([researcher, coSigner])
+        .rpc();
+    };
+
+    const revealAllocations = () =>
+      program.methods
+        .revealAllocations([...key])
+        .accounts({
+          study: blindStudyPda,
+          researcher: researcherPda,
+          team: teamFor(blindStudyPda),
+          allocation: allocationPda,
+          authority: researcher.publicKey,
+        })
+        .remainingAccounts(
+          subjects.map((subject) => ({
+            pubkey: enrollmentFor(blindStudyPda, subject.pda),
+            isWritable: true,
+            isSigner: false,
+          }))
+        )
+        .signers([researcher])
+        .rpc();
+
+    before(async () => {
+      for (const subject of subjects) {
+        const signature = await provider.connection.requestAirdrop(subject.keypair.publicKey, LAMPORTS_PER_SOL);
+        await provider.connection.confirmTransaction(signature);
+        await program.methods
+          .registerParticipant(buildAttributeTree([{ kind: "region", value: "EU" }]).root)
+          .accounts({
+            participant: subject.pda,
+            authority: subject.keypair.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([subject.keypair])
+          .rpc();
+      }
+
+      const { studiesCreated } = await program.account.researcher.fetch(researcherPda);
+      blindStudyPda = studyFor(researcherPda, studiesCreated);
+      [allocationPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("allocation"), blindStudyPda.toBuffer()],
+        program.programId
+      );
+      commitments = plannedArms.map((arm, sequence) => sha256(Buffer.from([arm]), saltFor(sequence)));
+      await program.methods
+        .createStudy("Blinded Study", "Double blind", "blind_criteria_hash", new anchor.BN(1000000), 2, { survey: {} })
+        .accounts({
+          study: blindStudyPda,
+          team: teamFor(blindStudyPda),
+          researcher: researcherPda,
+          vault: vaultFor(blindStudyPda),
+          rewardMint: studyMint.publicKey,
+          researcherTokenAccount: researcherTokenAccount,
+          authority: researcher.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([researcher])
+        .rpc();
+
+      await program.methods
+        .configureRandomization(
+          { block: {} },
+          true,
+          [
+            { name: "placebo", ratio: 1, assigned: 0 },
+            { name: "active", ratio: 1, assigned: 0 },
+          ],
+          1,
+          [...sha256(key)]
+        )
+        .accounts({
+          study: blindStudyPda,
+          researcher: researcherPda,
+          team: teamFor(blindStudyPda),
+          allocation: allocationPda,
+          authority: researcher.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([researcher])
+        .rpc();
+
+      await program.methods
+        .commitAllocationList(null, [...listHash(commitments)])
+        .accounts({
+          study: blindStudyPda,
+          researcher: researcherPda,
+          team: teamFor(blindStudyPda),
+          allocation: allocationPda,
+          authority: researcher.publicKey,
+        })
+        .signers([researcher])
+        .rpc();
+
+      // The first entry goes up now, the rest once recruiting
+      await postCommitments(commitments.slice(0, 1), listHash(commitments.slice(1)));
+
+      await approveStudy(blindStudyPda);
+      await program.methods
+        .transitionStudy({ recruiting: {} })
+        .accounts({
+          study: blindStudyPda,
+          team: teamFor(blindStudyPda),
+          researcher: researcherPda,
+          admin: adminPda,
+          ethicsApproval: ethicsApprovalFor(blindStudyPda),
+          authority: researcher.publicKey,
+        })
+        .signers([researcher])
+        .rpc();
+
+      try {
+        await postCommitments([sha256(Buffer.from([1]), saltFor(1))], Buffer.alloc(32));
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("AllocationListMismatch");
+      }
+      await postCommitments(commitments.slice(1), Buffer.alloc(32));
+
+      for (const subject of subjects) {
+        await program.methods
+          .joinStudy([], null)
+          .accounts({
+            study: blindStudyPda,
+            admin: adminPda,
+            participant: subject.pda,
+            enrollment: enrollmentFor(blindStudyPda, subject.pda),
+            ethicsApproval: ethicsApprovalFor(blindStudyPda),
+            studyConsent: null,
+            guardianship: null,
+            vault: vaultFor(blindStudyPda),
+            waitlist: null,
+            allocation: allocationPda,
+            attestationNonce: null,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            authority: subject.keypair.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([subject.keypair])
+          .rpc();
+      }
+    });
+
+    it("Stores only a commitment on each enrollment", async () => {
+      const enrollment = await program.account.enrollment.fetch(enrollmentFor(blindStudyPda, subjects[1].pda));
+      expect(enrollment.arm).to.be.null;
+      expect(enrollment.armCommitment.sequence).to.equal(1);
+
+      try {
+        await program.methods
+          .revealAllocationSeed([...key])
+          .accounts({
+            study: blindStudyPda,
+            researcher: researcherPda,
+            team: teamFor(blindStudyPda),
+            allocation: allocationPda,
+            authority: researcher.publicKey,
+          })
+          .signers([researcher])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("AllocationBlinded");
+      }
+    });
+
+    it("Needs an admin or ethics board co-signature to unblind one participant", async () => {
+      try {
+        await emergencyUnblind(Keypair.generate(), plannedArms[0], saltFor(0));
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("UnauthorizedUnblinding");
+      }
+
+      await emergencyUnblind(board, plannedArms[0], saltFor(0));
+
+      const enrollment = enrollmentFor(blindStudyPda, subjects[0].pda);
+      expect((await program.account.enrollment.fetch(enrollment)).arm).to.equal(plannedArms[0]);
+      const record = await program.account.unblindingRecord.fetch(unblindingFor(enrollment));
+      expect(record.reason).to.equal("Serious adverse event");
+      expect(record.authorizedBy.toString()).to.equal(board.publicKey.toString());
+    });
+
+    it("Unblinds the rest of the study once it closes", async () => {
+      try {
+        await revealAllocations();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("InvalidStudyPhase");
+      }
+
+      await program.methods
+        .transitionStudy({ closed: {} })
+        .accounts({
+          study: blindStudyPda,
+          team: teamFor(blindStudyPda),
+          researcher: researcherPda,
+          admin: adminPda,
+          ethicsApproval: null,
+          authority: researcher.publicKey,
+        })
+        .signers([researcher])
+        .rpc();
+      await revealAllocations();
+
+      const enrollment = await program.account.enrollment.fetch(enrollmentFor(blindStudyPda, subjects[1].pda));
+      expect(enrollment.arm).to.equal(plannedArms[1]);
+      const allocation = await program.account.allocation.fetch(allocationPda);
+      expect(allocation.arms.map((arm) => arm.assigned)).to.deep.equal([1, 1]);
+    });
+  });
+
+  describe("Dataset anchoring", () => {
+    const [kept, dropped] = [Keypair.generate(), Keypair.generate()].map((keypair) => {
+      const [pda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("participant"), keypair.publicKey.toBuffer()],
+        program.programId
+      );
+      return { keypair, pda, responseHash: sha256(randomBytes(64)) };
+    });
+    let datasetStudyPda: PublicKey;
+
+    const datasetLeaf = (subject: typeof kept, retained: boolean) =>
+      sha256(
+        Buffer.from([0]),
+        enrollmentFor(datasetStudyPda, subject.pda).toBuffer(),
+        subject.responseHash,
+        Buffer.from([retained ? 1 : 0])
+      );
+
+    const verifyEntry = (subject: typeof kept, retained: boolean, proof: Buffer[]) =>
+      program.methods
+        .verifyDatasetEntry(retained, proof.map((node) => [...node]))
+        .accounts({
+          study: datasetStudyPda,
+          enrollment: enrollmentFor(datasetStudyPda, subject.pda),
+        })
+        .rpc();
+
+    const anchorDataset = (root: Buffer, entries: number) =>
+      program.methods
+        .anchorDataset([...root], entries)
+        .accounts({
+          study: datasetStudyPda,
+          team: teamFor(datasetStudyPda),
+          researcher: researcherPda,
+          authority: researcher.publicKey,
+        })
+        .signers([researcher])
+        .rpc();
+
+    before(async () => {
+      const { studiesCreated } = await program.account.researcher.fetch(researcherPda);
+      datasetStudyPda = studyFor(researcherPda, studiesCreated);
+      await program.methods
+        .createStudy("Dataset Study", "Anchored responses", "dataset_criteria_hash", new anchor.BN(1000000), 2, { survey: {} })
+        .accounts({
+          study: datasetStudyPda,
+          team: teamFor(datasetStudyPda),
+          researcher: researcherPda,
+          vault: vaultFor(datasetStudyPda),
+          rewardMint: studyMint.publicKey,
+          researcherTokenAccount: researcherTokenAccount,
+          authority: researcher.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([researcher])
+        .rpc();
+      await approveStudy(datasetStudyPda);
+      await program.methods
+        .transitionStudy({ recruiting: {} })
+        .accounts({
+          study: datasetStudyPda,
+          team: teamFor(datasetStudyPda),
+          researcher: researcherPda,
+          admin: adminPda,
+          ethicsApproval: ethicsApprovalFor(datasetStudyPda),
+          authority: researcher.publicKey,
+        })
+        .signers([researcher])
+        .rpc();
+
+      for (const subject of [kept, dropped]) {
+        const signature = await provider.connection.requestAirdrop(subject.keypair.publicKey, LAMPORTS_PER_SOL);
+        await provider.connection.confirmTransaction(signature);
+        await program.methods
+          .registerParticipant(buildAttributeTree([{ kind: "region", value: "EU" }]).root)
+          .accounts({
+            participant: subject.pda,
+            authority: subject.keypair.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([subject.keypair])
+          .rpc();
+        await program.methods
+          .joinStudy([], null)
+          .accounts({
+            study: datasetStudyPda,
+            admin: adminPda,
+            participant: subject.pda,
+            enrollment: enrollmentFor(datasetStudyPda, subject.pda),
+            ethicsApproval: ethicsApprovalFor(datasetStudyPda),
+            studyConsent: null,
+            guardianship: null,
+            vault: vaultFor(datasetStudyPda),
+            waitlist: null,
+            allocation: null,
+            attestationNonce: null,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            authority: subject.keypair.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([subject.keypair])
+          .rpc();
+      }
+    });
+
+    it("Records a response commitment on the enrollment", async () => {
+      for (const subject of [kept, dropped]) {
+        await program.methods
+          .submitResponse([...subject.responseHash], "ar://response-" + subject.keypair.publicKey.toBase58().slice(0, 8))
+          .accounts({
+            study: datasetStudyPda,
+            participant: subject.pda,
+            enrollment: enrollmentFor(datasetStudyPda, subject.pda),
+            authority: subject.keypair.publicKey,
+          })
+          .signers([subject.keypair])
+          .rpc();
+      }
+
+      const enrollment = await program.account.enrollment.fetch(enrollmentFor(datasetStudyPda, kept.pda));
+      expect(Buffer.from(enrollment.responseHash)).to.deep.equal(kept.responseHash);
+      const study = await program.account.study.fetch(datasetStudyPda);
+      expect(study.responses).to.equal(2);
+    });
+
+    it("Anchors a dataset covering every submission at close", async () => {
+      const tokenAccount = await createAccount(
+        provider.connection,
+        dropped.keypair,
+        studyMint.publicKey,
+        dropped.keypair.publicKey
+      );
+      await program.methods
+        .withdrawFromStudy({ participantRequest: {} })
+        .accounts({
+          study: datasetStudyPda,
+          participant: dropped.pda,
+          enrollment: enrollmentFor(datasetStudyPda, dropped.pda),
+          vault: vaultFor(datasetStudyPda),
+          participantTokenAccount: tokenAccount,
+          waitlist: null,
+          studyConsent: null,
+          authority: dropped.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([dropped.keypair])
+        .rpc();
+      await program.methods
+        .transitionStudy({ closed: {} })
+        .accounts({
+          study: datasetStudyPda,
+          team: teamFor(datasetStudyPda),
+          researcher: researcherPda,
+          admin: adminPda,
+          ethicsApproval: null,
+          authority: researcher.publicKey,
+        })
+        .signers([researcher])
+        .rpc();
+
+      const root = hashPair(datasetLeaf(kept, true), datasetLeaf(dropped, false));
+      try {
+        await anchorDataset(root, 1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("DatasetIncomplete");
+      }
+      await anchorDataset(root, 2);
+
+      const study = await program.account.study.fetch(datasetStudyPda);
+      expect(Buffer.from(study.datasetRoot)).to.deep.equal(root);
+      expect(study.datasetVersion).to.equal(1);
+    });
+
+    it("Lets participants prove inclusion or removal", async () => {
+      await verifyEntry(kept, true, [datasetLeaf(dropped, false)]);
+      await verifyEntry(dropped, false, [datasetLeaf(kept, true)]);
+
+      try {
+        await verifyEntry(dropped, true, [datasetLeaf(kept, true)]);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("NotInDataset");
+      }
+    });
+
+    it("Lets a participant still enrolled at close finish and claim", async () => {
+      await program.methods
+        .trackStudyProgress(100)
+        .accounts({
+          study: datasetStudyPda,
+          team: teamFor(datasetStudyPda),
+          researcher: researcherPda,
+          participant: kept.pda,
+          enrollment: enrollmentFor(datasetStudyPda, kept.pda),
+          authority: kept.keypair.publicKey,
+        })
+        .signers([kept.keypair])
+        .rpc();
+      await program.methods
+        .claimCompletion()
+        .accounts({
+          study: datasetStudyPda,
+          participant: kept.pda,
+          enrollment: enrollmentFor(datasetStudyPda, kept.pda),
+          authority: kept.keypair.publicKey,
+        })
+        .signers([kept.keypair])
+        .rpc();
+
+      const enrollment = await program.account.enrollment.fetch(enrollmentFor(datasetStudyPda, kept.pda));
+      expect(enrollment.status).to.deep.equal({ completionClaimed: {} });
+      expect((await program.account.study.fetch(datasetStudyPda)).pendingClaims).to.equal(1);
+    });
+  });
+
+  describe("Completion claims", () => {
+    const [rejected, ignored] = [Keypair.generate(), Keypair.generate()].map((keypair) => {
+      const [pda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("participant"), keypair.publicKey.toBuffer()],
+        program.programId
+      );
+      return { keypair, pda, tokenAccount: null as PublicKey | null };
+    });
+    let claimStudyPda: PublicKey;
+
+    const disputeFor = (enrollment: PublicKey) =>
+      PublicKey.findProgramAddressSync([Buffer.from("dispute"), enrollment.toBuffer()], program.programId)[0];
+
+    const finalize = (subject: typeof rejected) =>
+      program.methods
+        .finalizeCompletion()
+        .accounts({
+          study: claimStudyPda,
+          researcher: researcherPda,
+          participant: subject.pda,
+          enrollment: enrollmentFor(claimStudyPda, subject.pda),
+          vault: vaultFor(claimStudyPda),
+          participantTokenAccount: subject.tokenAccount,
+          cranker: admin.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([admin])
+        .rpc();
+
+    before(async () => {
+      const { studiesCreated } = await program.account.researcher.fetch(researcherPda);
+      claimStudyPda = studyFor(researcherPda, studiesCreated);
+      await program.methods
+        .createStudy("Claim Study", "Claim and confirm", "claim_criteria_hash", new anchor.BN(1000000), 2, { survey: {} })
+        .accounts({
+          study: claimStudyPda,
+          team: teamFor(claimStudyPda),
+          researcher: researcherPda,
+          vault: vaultFor(claimStudyPda),
+          rewardMint: studyMint.publicKey,
+          researcherTokenAccount: researcherTokenAccount,
+          authority: researcher.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([researcher])
+        .rpc();
+      await program.methods
+        .setConfirmationWindow(new anchor.BN(4))
+        .accounts({ study: claimStudyPda, team: teamFor(claimStudyPda), researcher: researcherPda, authority: researcher.publicKey })
+        .signers([researcher])
+        .rpc();
+      await approveStudy(claimStudyPda);
+      await program.methods
+        .transitionStudy({ recruiting: {} })
+        .accounts({
+          study: claimStudyPda,
+          team: teamFor(claimStudyPda),
+          researcher: researcherPda,
+          admin: adminPda,
+          ethicsApproval: ethicsApprovalFor(claimStudyPda),
+          authority: researcher.publicKey,
+        })
+        .signers([researcher])
+        .rpc();
+
+      for (const subject of [rejected, ignored]) {
+        const signature = await provider.connection.requestAirdrop(subject.keypair.publicKey, LAMPORTS_PER_SOL);
+        await provider.connection.confirmTransaction(signature);
+        subject.tokenAccount = await createAccount(
+          provider.connection,
+          subject.keypair,
+          studyMint.publicKey,
+          subject.keypair.publicKey
+        );
+        await program.methods
+          .registerParticipant(buildAttributeTree([{ kind: "region", value: "EU" }]).root)
+          .accounts({
+            participant: subject.pda,
+            authority: subject.keypair.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([subject.keypair])
+          .rpc();
+        await program.methods
+          .joinStudy([], null)
+          .accounts({
+            study: claimStudyPda,
+            admin: adminPda,
+            participant: subject.pda,
+            enrollment: enrollmentFor(claimStudyPda, subject.pda),
+            ethicsApproval: ethicsApprovalFor(claimStudyPda),
+            studyConsent: null,
+            guardianship: null,
+            vault: vaultFor(claimStudyPda),
+            waitlist: null,
+            allocation: null,
+            attestationNonce: null,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            authority: subject.keypair.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([subject.keypair])
+          .rpc();
+        await program.methods
+          .trackStudyProgress(100)
+          .accounts({
+            study: claimStudyPda,
+            team: teamFor(claimStudyPda),
+            researcher: researcherPda,
+            participant: subject.pda,
+            enrollment: enrollmentFor(claimStudyPda, subject.pda),
+            authority: subject.keypair.publicKey,
+          })
+          .signers([subject.keypair])
+          .rpc();
+        await program.methods
+          .claimCompletion()
+          .accounts({
+            study: claimStudyPda,
+            participant: subject.pda,
+            enrollment: enrollmentFor(claimStudyPda, subject.pda),
+            authority: subject.keypair.publicKey,
+          })
+          .signers([subject.keypair])
+          .rpc();
+      }
+    });
+
+    it("Opens a dispute when the researcher rejects a claim", async () => {
+      const enrollment = enrollmentFor(claimStudyPda, rejected.pda);
+      await program.methods
+        .rejectCompletion([...sha256(Buffer.from("responses incomplete"))])
+        .accounts({
+          study: claimStudyPda,
+          researcher: researcherPda,
+          team: teamFor(claimStudyPda),
+          participant: rejected.pda,
+          enrollment,
+          dispute: disputeFor(enrollment),
+          authority: researcher.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([researcher])
+        .rpc();
+
+      expect((await program.account.enrollment.fetch(enrollment)).status).to.deep.equal({ disputed: {} });
+      const dispute = await program.account.dispute.fetch(disputeFor(enrollment));
+      expect(dispute.openedBy).to.deep.equal({ researcher: {} });
+      expect(dispute.amount.toNumber()).to.equal(1000000);
+    });
+
+    it("Leaves rejecting a claim to the PI and co-investigators", async () => {
+      const assistant = Keypair.generate();
+      const [assistantPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("researcher"), assistant.publicKey.toBuffer()],
+        program.programId
+      );
+      const signature = await provider.connection.requestAirdrop(assistant.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(signature);
+      await program.methods
+        .registerResearcher("Assistant University", "claim_assistant_credentials_hash")
+        .accounts({
+          researcher: assistantPda,
+          authority: assistant.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([assistant])
+        .rpc();
+      await program.methods
+        .addTeamMember({ researchAssistant: {} })
+        .accounts({
+          study: claimStudyPda,
+          researcher: researcherPda,
+          team: teamFor(claimStudyPda),
+          member: assistantPda,
+          authority: researcher.publicKey,
+        })
+        .signers([researcher])
+        .rpc();
+
+      const enrollment = enrollmentFor(claimStudyPda, rejected.pda);
+      try {
+        await program.methods
+          .rejectCompletion([...sha256(Buffer.from("responses incomplete"))])
+          .accounts({
+            study: claimStudyPda,
+            researcher: assistantPda,
+            team: teamFor(claimStudyPda),
+            participant: rejected.pda,
+            enrollment,
+            dispute: disputeFor(enrollment),
+            authority: assistant.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([assistant])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("UnauthorizedResearcher");
+      }
+    });
+
+    it("Auto-confirms an unanswered claim once the window lapses", async () => {
+      try {
+        await finalize(ignored);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("ConfirmationWindowOpen");
+      }
+
+      await new Promise((resolve) => setTimeout(resolve, 5000));
+      const { totalParticipants } = await program.account.researcher.fetch(researcherPda);
+      await finalize(ignored);
+      // The lead researcher is credited even though nobody on the team confirmed
+      expect((await program.account.researcher.fetch(researcherPda)).totalParticipants).to.equal(totalParticipants + 1);
+
+      const enrollment = await program.account.enrollment.fetch(enrollmentFor(claimStudyPda, ignored.pda));
+      expect(enrollment.status).to.deep.equal({ completed: {} });
+      const tokenAccount = await getAccount(provider.connection, ignored.tokenAccount);
+      expect(Number(tokenAccount.amount)).to.equal(1000000);
+      // The rejected claim stays reserved in escrow until its dispute is ruled on
+      expect((await program.account.study.fetch(claimStudyPda)).pendingClaims).to.equal(1);
+
+      try {
+        await finalize(rejected);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("CompletionNotClaimed");
+      }
+    });
+
+    describe("Disputes", () => {
+      const arbitrator = Keypair.generate();
+      const resolve = (ruling: object, signer: Keypair) => {
+        const enrollment = enrollmentFor(claimStudyPda, rejected.pda);
+        return program.methods
+          .resolveDispute(ruling as any)
+          .accounts({
+            admin: adminPda,
+            study: claimStudyPda,
+            researcher: researcherPda,
+            participant: rejected.pda,
+            enrollment,
+            dispute: disputeFor(enrollment),
+            vault: vaultFor(claimStudyPda),
+            participantTokenAccount: rejected.tokenAccount,
+            waitlist: null,
+            studyConsent: null,
+            arbitrator: signer.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([signer])
+          .rpc();
+      };
+
+      it("Collects evidence from both sides", async () => {
+        const enrollment = enrollmentFor(claimStudyPda, rejected.pda);
+        const submit = (authority: Keypair, researcherAccount: PublicKey | null, evidence: string) =>
+          program.methods
+            .submitDisputeEvidence([...sha256(Buffer.from(evidence))])
+            .accounts({
+              study: claimStudyPda,
+              team: teamFor(claimStudyPda),
+              researcher: researcherAccount,
+              participant: rejected.pda,
+              dispute: disputeFor(enrollment),
+              authority: authority.publicKey,
+            })
+            .signers([authority])
+            .rpc();
+
+        await submit(rejected.keypair, null, "session recordings");
+        await submit(researcher, researcherPda, "missing questionnaire pages");
+        try {
+          await submit(ignored.keypair, null, "unrelated");
+          expect.fail("Should have thrown an error");
+        } catch (error) {
+          expect(error.error?.errorCode?.code).to.equal("NotDisputeParty");
+        }
+
+        const dispute = await program.account.dispute.fetch(disputeFor(enrollment));
+        expect(dispute.participantEvidence).to.have.length(1);
+        expect(dispute.researcherEvidence).to.have.length(1);
+      });
+
+      it("Only lets the participant contest a removal or expiry", async () => {
+        const enrollment = enrollmentFor(claimStudyPda, ignored.pda);
+        try {
+          await program.methods
+            .openDispute([...sha256(Buffer.from("underpaid"))])
+            .accounts({
+              study: claimStudyPda,
+              participant: ignored.pda,
+              enrollment,
+              vault: vaultFor(claimStudyPda),
+              dispute: disputeFor(enrollment),
+              authority: ignored.keypair.publicKey,
+              systemProgram: SystemProgram.programId,
+            })
+            .signers([ignored.keypair])
+            .rpc();
+          expect.fail("Should have thrown an error");
+        } catch (error) {
+          expect(error.error?.errorCode?.code).to.equal("EnrollmentNotContestable");
+        }
+      });
+
+      it("Refuses to erase a participant while their claim is disputed", async () => {
+        try {
+          await program.methods
+            .eraseParticipant([...randomBytes(32)])
+            .accounts({
+              participant: rejected.pda,
+              wallet: null,
+              tombstone: tombstoneFor(rejected.pda),
+              guardianship: null,
+              guardian: null,
+              authority: rejected.keypair.publicKey,
+              systemProgram: SystemProgram.programId,
+            })
+            .signers([rejected.keypair])
+            .rpc();
+          expect.fail("Should have thrown an error");
+        } catch (error) {
+          expect(error.error?.errorCode?.code).to.equal("ActiveEnrollmentsRemain");
+        }
+      });
+
+      it("Resolves with a partial payment from an appointed arbitrator", async () => {
+        try {
+          await resolve({ fullPayment: {} }, arbitrator);
+          expect.fail("Should have thrown an error");
+        } catch (error) {
+          expect(error.error?.errorCode?.code).to.equal("UnauthorizedArbitrator");
+        }
+
+        await program.methods
+          .addArbitrator(arbitrator.publicKey)
+          .accounts({ admin: adminPda, authority: admin.publicKey })
+          .signers([admin])
+          .rpc();
+
+        try {
+          await resolve({ partialPayment: { amount: new anchor.BN(1000000) } }, arbitrator);
+          expect.fail("Should have thrown an error");
+        } catch (error) {
+          expect(error.error?.errorCode?.code).to.equal("InvalidRuling");
+        }
+
+        await resolve({ partialPayment: { amount: new anchor.BN(400000) } }, arbitrator);
+
+        const enrollmentPda = enrollmentFor(claimStudyPda, rejected.pda);
+        const dispute = await program.account.dispute.fetch(disputeFor(enrollmentPda));
+        expect(dispute.status).to.deep.equal({ resolved: {} });
+        expect(dispute.awarded.toNumber()).to.equal(400000);
+        expect(dispute.resolvedBy.toBase58()).to.equal(arbitrator.publicKey.toBase58());
+        const enrollment = await program.account.enrollment.fetch(enrollmentPda);
+        expect(enrollment.status).to.deep.equal({ completed: {} });
+        expect(enrollment.rewardPaid.toNumber()).to.equal(400000);
+        const tokenAccount = await getAccount(provider.connection, rejected.tokenAccount);
+        expect(Number(tokenAccount.amount)).to.equal(400000);
+
+        try {
+          await resolve({ fullPayment: {} }, admin);
+          expect.fail("Should have thrown an error");
+        } catch (error) {
+          expect(error.error?.errorCode?.code).to.equal("DisputeNotOpen");
+        }
+      });
+    });
+  });
+
+  describe("Cancellation", () => {
+    const [claimer, leaver] = [Keypair.generate(), Keypair.generate()].map((keypair) => {
+      const [pda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("participant"), keypair.publicKey.toBuffer()],
+        program.programId
+      );
+      return { keypair, pda, tokenAccount: null as PublicKey | null };
+    });
+    let cancelledStudyPda: PublicKey;
+
+    before(async () => {
+      const { studiesCreated } = await program.account.researcher.fetch(researcherPda);
+      cancelledStudyPda = studyFor(researcherPda, studiesCreated);
+      await program.methods
+        .createStudy("Cancelled Study", "Cancelled mid-run", "cancel_criteria_hash", new anchor.BN(1000000), 2, { survey: {} })
+        .accounts({
+          study: cancelledStudyPda,
+          team: teamFor(cancelledStudyPda),
+          researcher: researcherPda,
+          vault: vaultFor(cancelledStudyPda),
+          rewardMint: studyMint.publicKey,
+          researcherTokenAccount: researcherTokenAccount,
+          authority: researcher.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([researcher])
+        .rpc();
+      await program.methods
+        .setExitPolicy(true)
+        .accounts({ study: cancelledStudyPda, team: teamFor(cancelledStudyPda), researcher: researcherPda, authority: researcher.publicKey })
+        .signers([researcher])
+        .rpc();
+      await program.methods
+        .setConfirmationWindow(new anchor.BN(2))
+        .accounts({ study: cancelledStudyPda, team: teamFor(cancelledStudyPda), researcher: researcherPda, authority: researcher.publicKey })
+        .signers([researcher])
+        .rpc();
+      await approveStudy(cancelledStudyPda);
+      await program.methods
+        .transitionStudy({ recruiting: {} })
+        .accounts({
+          study: cancelledStudyPda,
+          team: teamFor(cancelledStudyPda),
+          researcher: researcherPda,
+          admin: adminPda,
+          ethicsApproval: ethicsApprovalFor(cancelledStudyPda),
+          authority: researcher.publicKey,
+        })
+        .signers([researcher])
+        .rpc();
+
+      for (const [subject, progress] of [[claimer, 100], [leaver, 50]] as const) {
+        const signature = await provider.connection.requestAirdrop(subject.keypair.publicKey, LAMPORTS_PER_SOL);
+        await provider.connection.confirmTransaction(signature);
+        subject.tokenAccount = await createAccount(
+          provider.connection,
+          subject.keypair,
+          studyMint.publicKey,
+          subject.keypair.publicKey
+        );
+        await program.methods
+          .registerParticipant(buildAttributeTree([{ kind: "region", value: "EU" }]).root)
+          .accounts({
+            participant: subject.pda,
+            authority: subject.keypair.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([subject.keypair])
+          .rpc();
+        await program.methods
+          .joinStudy([], null)
+          .accounts({
+            study: cancelledStudyPda,
+            admin: adminPda,
+            participant: subject.pda,
+            enrollment: enrollmentFor(cancelledStudyPda, subject.pda),
+            ethicsApproval: ethicsApprovalFor(cancelledStudyPda),
+            studyConsent: null,
+            guardianship: null,
+            vault: vaultFor(cancelledStudyPda),
+            waitlist: null,
+            allocation: null,
+            attestationNonce: null,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            authority: subject.keypair.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([subject.keypair])
+          .rpc();
+        await program.methods
+          .trackStudyProgress(progress)
+          .accounts({
+            study: cancelledStudyPda,
+            team: teamFor(cancelledStudyPda),
+            researcher: researcherPda,
+            participant: subject.pda,
+            enrollment: enrollmentFor(cancelledStudyPda, subject.pda),
+            authority: subject.keypair.publicKey,
+          })
+          .signers([subject.keypair])
+          .rpc();
+      }
+      await program.methods
+        .claimCompletion()
+        .accounts({
+          study: cancelledStudyPda,
+          participant: claimer.pda,
+          enrollment: enrollmentFor(cancelledStudyPda, claimer.pda),
+          authority: claimer.keypair.publicKey,
+        })
+        .signers([claimer.keypair])
+        .rpc();
+    });
+
+    it("Keeps a pre-cancellation claim's reward out of later exit payouts", async () => {
+      await program.methods
+        .transitionStudy({ cancelled: {} })
+        .accounts({
+          study: cancelledStudyPda,
+          team: teamFor(cancelledStudyPda),
+          researcher: researcherPda,
+          admin: adminPda,
+          ethicsApproval: null,
+          authority: researcher.publicKey,
+        })
+        .signers([researcher])
+        .rpc();
+      await program.methods
+        .refundEscrow()
+        .accounts({
+          study: cancelledStudyPda,
+          team: teamFor(cancelledStudyPda),
+          researcher: researcherPda,
+          vault: vaultFor(cancelledStudyPda),
+          researcherTokenAccount: researcherTokenAccount,
+          authority: researcher.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([researcher])
+        .rpc();
+      expect(Number((await getAccount(provider.connection, vaultFor(cancelledStudyPda))).amount)).to.equal(1000000);
+
+      // The prorated share would come out of the claimed reward, so the leaver gets nothing
+      await program.methods
+        .withdrawFromStudy({ participantRequest: {} })
+        .accounts({
+          study: cancelledStudyPda,
+          participant: leaver.pda,
+          enrollment: enrollmentFor(cancelledStudyPda, leaver.pda),
+          vault: vaultFor(cancelledStudyPda),
+          participantTokenAccount: leaver.tokenAccount,
+          waitlist: null,
+          studyConsent: null,
+          authority: leaver.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([leaver.keypair])
+        .rpc();
+      const left = await program.account.enrollment.fetch(enrollmentFor(cancelledStudyPda, leaver.pda));
+      expect(left.status).to.deep.equal({ withdrawn: {} });
+      expect(left.rewardPaid.toNumber()).to.equal(0);
+      expect(Number((await getAccount(provider.connection, leaver.tokenAccount)).amount)).to.equal(0);
+
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+      await program.methods
+        .finalizeCompletion()
+        .accounts({
+          study: cancelledStudyPda,
+          researcher: researcherPda,
+          participant: claimer.pda,
+          enrollment: enrollmentFor(cancelledStudyPda, claimer.pda),
+          vault: vaultFor(cancelledStudyPda),
+          participantTokenAccount: claimer.tokenAccount,
+          cranker: admin.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([admin])
+        .rpc();
+
+      const claimed = await program.account.enrollment.fetch(enrollmentFor(cancelledStudyPda, claimer.pda));
+      expect(claimed.status).to.deep.equal({ completed: {} });
+      expect(Number((await getAccount(provider.connection, claimer.tokenAccount)).amount)).to.equal(1000000);
+      expect((await program.account.study.fetch(cancelledStudyPda)).pendingClaims).to.equal(0);
+    });
+  });
+
+  describe("Overturned claims", () => {
+    const subject = Keypair.generate();
+    const [subjectPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("participant"), subject.publicKey.toBuffer()],
+      program.programId
+    );
+    let overturnStudyPda: PublicKey;
+    let subjectTokenAccount: PublicKey;
+
+    before(async () => {
+      const { studiesCreated } = await program.account.researcher.fetch(researcherPda);
+      overturnStudyPda = studyFor(researcherPda, studiesCreated);
+      await program.methods
+        .createStudy("Overturn Study", "Rejected and upheld", "overturn_criteria_hash", new anchor.BN(1000000), 1, { survey: {} })
+        .accounts({
+          study: overturnStudyPda,
+          team: teamFor(overturnStudyPda),
+          researcher: researcherPda,
+          vault: vaultFor(overturnStudyPda),
+          rewardMint: studyMint.publicKey,
+          researcherTokenAccount: researcherTokenAccount,
+          authority: researcher.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([researcher])
+        .rpc();
+      await approveStudy(overturnStudyPda);
+      await program.methods
+        .transitionStudy({ recruiting: {} })
+        .accounts({
+          study: overturnStudyPda,
+          team: teamFor(overturnStudyPda),
+          researcher: researcherPda,
+          admin: adminPda,
+          ethicsApproval: ethicsApprovalFor(overturnStudyPda),
+          authority: researcher.publicKey,
+        })
+        .signers([researcher])
+        .rpc();
+
+      const signature = await provider.connection.requestAirdrop(subject.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(signature);
+      subjectTokenAccount = await createAccount(provider.connection, subject, studyMint.publicKey, subject.publicKey);
+      await program.methods
+        .registerParticipant(buildAttributeTree([{ kind: "region", value: "EU" }]).root)
+        .accounts({ participant: subjectPda, authority: subject.publicKey, systemProgram: SystemProgram.programId })
+        .signers([subject])
+        .rpc();
+      await program.methods
+        .joinStudy([], null)
+        .accounts({
+          study: overturnStudyPda,
+          admin: adminPda,
+          participant: subjectPda,
+          enrollment: enrollmentFor(overturnStudyPda, subjectPda),
+          ethicsApproval: ethicsApprovalFor(overturnStudyPda),
+          studyConsent: null,
+          guardianship: null,
+          vault: vaultFor(overturnStudyPda),
+          waitlist: null,
+          allocation: null,
+          attestationNonce: null,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          authority: subject.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([subject])
+        .rpc();
+      await program.methods
+        .trackStudyProgress(100)
+        .accounts({
+          study: overturnStudyPda,
+          team: teamFor(overturnStudyPda),
+          researcher: researcherPda,
+          participant: subjectPda,
+          enrollment: enrollmentFor(overturnStudyPda, subjectPda),
+          authority: subject.publicKey,
+        })
+        .signers([subject])
+        .rpc();
+      await program.methods
+        .claimCompletion()
+        .accounts({
+          study: overturnStudyPda,
+          participant: subjectPda,
+          enrollment: enrollmentFor(overturnStudyPda, subjectPda),
+          authority: subject.publicKey,
+        })
+        .signers([subject])
+        .rpc();
+      const enrollment = enrollmentFor(overturnStudyPda, subjectPda);
+      await program.methods
+        .rejectCompletion([...sha256(Buffer.from("no responses recorded"))])
+        .accounts({
+          study: overturnStudyPda,
+          researcher: researcherPda,
+          team: teamFor(overturnStudyPda),
+          participant: subjectPda,
+          enrollment,
+          dispute: PublicKey.findProgramAddressSync([Buffer.from("dispute"), enrollment.toBuffer()], program.programId)[0],
+          authority: researcher.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([researcher])
+        .rpc();
+    });
+
+    it("Frees the slot like a removal when the arbitrator upholds the rejection", async () => {
+      const enrollment = enrollmentFor(overturnStudyPda, subjectPda);
+      await program.methods
+        .resolveDispute({ noPayment: {} })
+        .accounts({
+          admin: adminPda,
+          study: overturnStudyPda,
+          researcher: researcherPda,
+          participant: subjectPda,
+          enrollment,
+          dispute: PublicKey.findProgramAddressSync([Buffer.from("dispute"), enrollment.toBuffer()], program.programId)[0],
+          vault: vaultFor(overturnStudyPda),
+          participantTokenAccount: subjectTokenAccount,
+          waitlist: null,
+          studyConsent: null,
+          arbitrator: admin.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([admin])
+        .rpc();
+
+      const ended = await program.account.enrollment.fetch(enrollment);
+      expect(ended.status).to.deep.equal({ removed: {} });
+      const study = await program.account.study.fetch(overturnStudyPda);
+      expect(study.currentParticipants).to.equal(0);
+      expect(study.pendingClaims).to.equal(0);
+      const participant = await program.account.participant.fetch(subjectPda);
+      expect(participant.activeStudies).to.equal(0);
+      expect(participant.openDisputes).to.equal(0);
+    });
+  });
+
+  describe("Erasure", () => {
+    const subject = Keypair.generate();
+    const [subjectPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("participant"), subject.publicKey.toBuffer()],
+      program.programId
+    );
+
+    it("Closes the profile and leaves a pseudonymous tombstone", async () => {
+      const signature = await provider.connection.requestAirdrop(subject.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(signature);
+      await program.methods
+        .registerParticipant(buildAttributeTree([{ kind: "region", value: "EU" }]).root)
+        .accounts({
+          participant: subjectPda,
+          authority: subject.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([subject])
+        .rpc();
+
+      const salt = randomBytes(32);
+      await program.methods
+        .eraseParticipant([...salt])
+        .accounts({
+          participant: subjectPda,
+          wallet: null,
+          tombstone: tombstoneFor(subjectPda),
+          guardianship: null,
+          guardian: null,
+          authority: subject.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([subject])
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(subjectPda)).to.be.null;
+      const tombstone = await program.account.erasureTombstone.fetch(tombstoneFor(subjectPda));
+      expect(tombstone.pseudonym).to.equal("P" + sha256(salt, subjectPda.toBuffer()).subarray(0, 8).toString("hex"));
+      expect(tombstone.erasedAt.toNumber()).to.be.greaterThan(0);
+    });
+  });
+
+  describe("Identity attestation", () => {
+    const attester = Keypair.generate();
+    const identityHash = sha256(Buffer.from("passport:GB:123456789"));
+    const [identityRecordPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("identity"), identityHash],
+      program.programId
+    );
+    const [first, second] = [Keypair.generate(), Keypair.generate()].map((keypair) => ({
+      keypair,
+      pda: PublicKey.findProgramAddressSync(
+        [Buffer.from("participant"), keypair.publicKey.toBuffer()],
+        program.programId
+      )[0],
+    }));
+
+    // The stand-in attester signs identity_hash || participant
+    const attest = (subject: typeof first, signer: Keypair) =>
+      program.methods
+        .attestIdentity([...identityHash], signer.publicKey)
+        .accounts({
+          admin: adminPda,
+          participant: subject.pda,
+          identityRecord: identityRecordPda,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          authority: subject.keypair.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([
+          Ed25519Program.createInstructionWithPrivateKey({
+            privateKey: signer.secretKey,
+            message: Buffer.concat([identityHash, subject.pda.toBuffer()]),
+          }),
+        ])
+        .signers([subject.keypair])
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .addIdentityAttester(attester.publicKey)
+        .accounts({ admin: adminPda, authority: admin.publicKey })
+        .signers([admin])
+        .rpc();
+      for (const subject of [first, second]) {
+        const signature = await provider.connection.requestAirdrop(subject.keypair.publicKey, LAMPORTS_PER_SOL);
+        await provider.connection.confirmTransaction(signature);
+        await program.methods
+          .registerParticipant(buildAttributeTree([{ kind: "region", value: "EU" }]).root)
+          .accounts({
+            participant: subject.pda,
+            authority: subject.keypair.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([subject.keypair])
+          .rpc();
+      }
+    });
+
+    it("Rejects an attestation from an unregistered attester", async () => {
+      try {
+        await attest(first, Keypair.generate());
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("UntrustedIdentityAttester");
+      }
+    });
+
+    it("Binds an identity to exactly one participant", async () => {
+      await attest(first, attester);
+      const participantAccount = await program.account.participant.fetch(first.pda);
+      expect(participantAccount.identity.toBase58()).to.equal(identityRecordPda.toBase58());
+
+      try {
+        await attest(second, attester);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("IdentityAlreadyBound");
+      }
+    });
+
+    it("Only revokes the identity from the participant it is bound to", async () => {
+      try {
+        await program.methods
+          .revokeIdentity()
+          .accounts({
+            admin: adminPda,
+            identityRecord: identityRecordPda,
+            participant: second.pda,
+            authority: admin.publicKey,
+          })
+          .signers([admin])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("ConstraintAddress");
+      }
+
+      const bystander = await program.account.participant.fetch(second.pda);
+      expect(bystander.suspended).to.be.false;
+    });
+
+    it("Suspends the participant when the attestation is revoked", async () => {
+      await program.methods
+        .revokeIdentity()
+        .accounts({
+          admin: adminPda,
+          identityRecord: identityRecordPda,
+          participant: first.pda,
+          authority: admin.publicKey,
+        })
+        .signers([admin])
+        .rpc();
+
+      const participantAccount = await program.account.participant.fetch(first.pda);
+      expect(participantAccount.suspended).to.be.true;
+      expect(participantAccount.identity).to.be.null;
+      const record = await program.account.identityRecord.fetch(identityRecordPda);
+      expect(record.revokedAt).to.not.be.null;
+    });
+  });
+
+  describe("Guardianship", () => {
+    const minor = Keypair.generate();
+    const guardian = Keypair.generate();
+    const [minorPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("participant"), minor.publicKey.toBuffer()],
+      program.programId
+    );
+    const [guardianshipPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("guardianship"), minorPda.toBuffer()],
+      program.programId
+    );
+
+    const establish = (approver: Keypair) =>
+      program.methods
+        .establishGuardianship()
+        .accounts({
+          admin: adminPda,
+          participant: minorPda,
+          guardianship: guardianshipPda,
+          guardian: guardian.publicKey,
+          approver: approver.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([guardian, approver])
+        .rpc();
+
+    const openStudy = async (title: string, guarded: boolean) => {
+      const { studiesCreated } = await program.account.researcher.fetch(researcherPda);
+      const studyPda = studyFor(researcherPda, studiesCreated);
+      await program.methods
+        .createStudy(title, "Guardianship test", "guardianship_criteria_hash", new anchor.BN(1000000), 2, { survey: {} })
+        .accounts({
+          study: studyPda,
+          team: teamFor(studyPda),
+          researcher: researcherPda,
+          vault: vaultFor(studyPda),
+          rewardMint: studyMint.publicKey,
+          researcherTokenAccount: researcherTokenAccount,
+          authority: researcher.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([researcher])
+        .rpc();
+      await program.methods
+        .setGuardianRequirement(guarded)
+        .accounts({ study: studyPda, team: teamFor(studyPda), researcher: researcherPda, authority: researcher.publicKey })
+        .signers([researcher])
+        .rpc();
+      await approveStudy(studyPda);
+      await program.methods
+        .transitionStudy({ recruiting: {} })
+        .accounts({
+          study: studyPda,
+          team: teamFor(studyPda),
+          researcher: researcherPda,
+          admin: adminPda,
+          ethicsApproval: ethicsApprovalFor(studyPda),
+          authority: researcher.publicKey,
+        })
+        .signers([researcher])
+        .rpc();
+      return studyPda;
+    };
+
+    const joinAsMinor = (study: PublicKey, guardianship: PublicKey | null, studyConsent: PublicKey | null = null) =>
+      program.methods
+        .joinStudy([], null)
+        .accounts({
+          study,
+          admin: adminPda,
+          participant: minorPda,
+          enrollment: enrollmentFor(study, minorPda),
+          ethicsApproval: ethicsApprovalFor(study),
+          studyConsent,
+          guardianship,
+          vault: vaultFor(study),
+          waitlist: null,
+          allocation: null,
+          attestationNonce: null,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          authority: minor.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([minor])
+        .rpc();
+
+    const recordAssent = (study: PublicKey, signer: Keypair) =>
+      program.methods
+        .recordAssent()
+        .accounts({
+          study,
+          participant: minorPda,
+          studyConsent: studyConsentFor(study, minorPda),
+          authority: signer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([signer])
+        .rpc();
+
+    let minorTokenAccount: PublicKey;
+    let minorStudyPda: PublicKey;
+    let openStudyPda: PublicKey;
+
+    before(async () => {
+      for (const keypair of [minor, guardian]) {
+        const signature = await provider.connection.requestAirdrop(keypair.publicKey, LAMPORTS_PER_SOL);
+        await provider.connection.confirmTransaction(signature);
+      }
+      minorTokenAccount = await createAccount(provider.connection, minor, studyMint.publicKey, minor.publicKey);
+      await program.methods
+        .registerParticipant(buildAttributeTree([{ kind: "ageGroup", value: "under-18" }]).root)
+        .accounts({
+          participant: minorPda,
+          authority: minor.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([minor])
+        .rpc();
+    });
+
+    it("Needs the admin or an identity attester to approve the link", async () => {
+      try {
+        await establish(Keypair.generate());
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("UnauthorizedGuardianshipApproval");
+      }
+
+      await establish(admin);
+
+      const participantAccount = await program.account.participant.fetch(minorPda);
+      expect(participantAccount.guardian.toBase58()).to.equal(guardian.publicKey.toBase58());
+      const guardianship = await program.account.guardianship.fetch(guardianshipPda);
+      expect(guardianship.approvedBy.toBase58()).to.equal(admin.publicKey.toBase58());
+    });
+
+    it("Records the participant's assent to a single study, separately from guardian consent", async () => {
+      minorStudyPda = await openStudy("Minor Study", true);
+      try {
+        await recordAssent(minorStudyPda, guardian);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("ConstraintHasOne");
+      }
+
+      await recordAssent(minorStudyPda, minor);
+
+      const studyConsent = await program.account.studyConsent.fetch(studyConsentFor(minorStudyPda, minorPda));
+      expect(studyConsent.assentedAt).to.not.be.null;
+      expect(studyConsent.guardianConsentedAt).to.be.null;
+      expect(studyConsent.issuedAt.toNumber()).to.equal(0);
+    });
+
+    it("Keeps the participant out of guardian-consent studies until the guardian consents", async () => {
+      // Assent alone is not enough, and neither record can simply be left out
+      const attempts: [PublicKey | null, PublicKey | null][] = [
+        [null, studyConsentFor(minorStudyPda, minorPda)],
+        [guardianshipPda, null],
+        [guardianshipPda, studyConsentFor(minorStudyPda, minorPda)],
+      ];
+      for (const [guardianship, studyConsent] of attempts) {
+        try {
+          await joinAsMinor(minorStudyPda, guardianship, studyConsent);
+          expect.fail("Should have thrown an error");
+        } catch (error) {
+          expect(error.error?.errorCode?.code).to.equal("GuardianConsentRequired");
+        }
+      }
+    });
+
+    it("Refuses a penalty-free exit from a study that never needed guardian consent", async () => {
+      openStudyPda = await openStudy("Open Study", false);
+      await joinAsMinor(openStudyPda, null);
+      await recordAssent(openStudyPda, minor);
+
+      const exitAccounts = {
+        study: openStudyPda,
+        participant: minorPda,
+        enrollment: enrollmentFor(openStudyPda, minorPda),
+        vault: vaultFor(openStudyPda),
+        participantTokenAccount: minorTokenAccount,
+        waitlist: null,
+        studyConsent: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      };
+      try {
+        await program.methods
+          .withdrawGuardianConsent()
+          .accounts({
+            ...exitAccounts,
+            studyConsent: studyConsentFor(openStudyPda, minorPda),
+            guardianship: guardianshipPda,
+            authority: minor.publicKey,
+          })
+          .signers([minor])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("GuardianConsentNotRequired");
+      }
+
+      await program.methods
+        .withdrawFromStudy({ participantRequest: {} })
+        .accounts({ ...exitAccounts, authority: minor.publicKey })
+        .signers([minor])
+        .rpc();
+      const enrollment = await program.account.enrollment.fetch(enrollmentFor(openStudyPda, minorPda));
+      expect(enrollment.status).to.deep.equal({ withdrawn: {} });
+    });
+
+    it("Closes the guardianship back to the guardian on erasure", async () => {
+      const studyConsents = [minorStudyPda, openStudyPda].map((study) => studyConsentFor(study, minorPda));
+      const erase = (withGuardianship: boolean) =>
+        program.methods
+          .eraseParticipant([...randomBytes(32)])
+          .accounts({
+            participant: minorPda,
+            wallet: null,
+            tombstone: tombstoneFor(minorPda),
+            guardianship: withGuardianship ? guardianshipPda : null,
+            guardian: withGuardianship ? guardian.publicKey : null,
+            authority: minor.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts(studyConsents.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true })))
+          .signers([minor])
+          .rpc();
+
+      try {
+        await erase(false);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error?.errorCode?.code).to.equal("GuardianshipRequired");
+      }
+
+      const before = await provider.connection.getBalance(guardian.publicKey);
+      await erase(true);
+
+      expect(await provider.connection.getAccountInfo(guardianshipPda)).to.be.null;
+      expect(await provider.connection.getBalance(guardian.publicKey)).to.be.greaterThan(before);
+      for (const studyConsent of studyConsents) {
+        expect(await provider.connection.getAccountInfo(studyConsent)).to.be.null;
+      }
+    });
+
+    it("Carries the erased profile's reputation over to a new registration", async () => {
+      const { reputation } = await program.account.erasureTombstone.fetch(tombstoneFor(minorPda));
+      // The abandoned open study left the score below neutral
+      expect(reputation.score).to.be.lessThan(50000);
+
+      await program.methods
+        .registerParticipant(buildAttributeTree([{ kind: "region", value: "EU" }]).root)
+        .accounts({
+          participant: minorPda,
+          tombstone: tombstoneFor(minorPda),
+          authority: minor.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([minor])
+        .rpc();
+
+      const participant = await program.account.participant.fetch(minorPda);
+      expect(participant.reputation.score).to.equal(reputation.score);
+      expect(participant.reputation.updatedAt.toNumber()).to.equal(reputation.updatedAt.toNumber());
+    });
   });
 });